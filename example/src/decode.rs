@@ -11,6 +11,12 @@ struct MyId2<'a>(&'a str);
 #[derive(Decode)]
 struct SomeId<T>(T);
 
+#[derive(Debug, PartialEq, Decode, Encode)]
+enum Status {
+    #[postro(rename = "active")]
+    Active,
+}
+
 #[derive(Debug, Deserialize, PartialEq, Eq)]
 struct Foo {
     id: i32,
@@ -35,6 +41,12 @@ pub async fn main() -> Result<()> {
 
     assert_eq!(some_id.0, 420);
 
+    let status: Status = query_scalar("SELECT 'active'::text", &mut conn).fetch_one().await?;
+
+    assert_eq!(status, Status::Active);
+
+    let _ = query("", &mut conn).bind(Status::Active);
+
     // `time`
 
     let now_utc = UtcDateTime::now().replace_millisecond(0).unwrap();
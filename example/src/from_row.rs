@@ -10,6 +10,16 @@ struct Postro {
 #[derive(FromRow)]
 struct PostroTuple(i32,String);
 
+#[derive(FromRow)]
+struct PostroJoin {
+    #[postro(flatten)]
+    postro: Postro,
+    #[postro(rename = "postroCount")]
+    count: i64,
+    #[postro(default)]
+    tags: Option<String>,
+}
+
 pub async fn main() -> Result<()> {
     let mut conn = Connection::connect_env().await?;
 
@@ -35,5 +45,15 @@ pub async fn main() -> Result<()> {
         .fetch_all()
         .await?;
 
+    let datas = query_as::<_, _, PostroJoin>(
+        "SELECT *, count(*) OVER () AS \"postroCount\" FROM postro",
+        &mut conn,
+    )
+    .fetch_all()
+    .await?;
+
+    assert_eq!(datas[0].count, 2);
+    assert!(datas[0].tags.is_none());
+
     Ok(())
 }
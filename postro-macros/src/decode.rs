@@ -6,6 +6,8 @@ use crate::error;
 pub fn decode(input: DeriveInput) -> Result<TokenStream> {
     let DeriveInput { attrs: _, vis: _, ident, mut generics, data } = input;
 
+    let mut format = quote! {};
+
     let q1 = match data {
         Data::Struct(st) => match &st.fields {
             Fields::Unnamed(FieldsUnnamed { unnamed, .. }) => {
@@ -13,6 +15,9 @@ pub fn decode(input: DeriveInput) -> Result<TokenStream> {
                     error!("only one field struct is supported")
                 }
 
+                let ty = &unnamed.first().unwrap().ty;
+                format = quote! { const FORMAT: ::postro::postgres::PgFormat = <#ty as ::postro::Decode>::FORMAT; };
+
                 quote! {
                     Ok(Self(col.decode()?))
                 }
@@ -23,6 +28,8 @@ pub fn decode(input: DeriveInput) -> Result<TokenStream> {
                 }
 
                 let name = named.first().unwrap().ident.as_ref().unwrap();
+                let ty = &named.first().unwrap().ty;
+                format = quote! { const FORMAT: ::postro::postgres::PgFormat = <#ty as ::postro::Decode>::FORMAT; };
 
                 quote! {
                     Ok(Self {
@@ -32,7 +39,20 @@ pub fn decode(input: DeriveInput) -> Result<TokenStream> {
             },
             Fields::Unit => quote! { Ok(Self) }
         },
-        Data::Enum(_) => error!("union is not yet supported"),
+        Data::Enum(data) => {
+            let (variants, labels): (Vec<_>, Vec<_>) = enum_labels(&data)?.into_iter().unzip();
+            let labels = labels.iter().map(|e|LitByteStr::new(e.as_bytes(), ident.span()));
+            let name = ident.to_string();
+
+            quote! {
+                match &col.try_into_value()?[..] {
+                    #(#labels => Ok(Self::#variants),)*
+                    label => Err(::postro::DecodeError::Invalid(format!(
+                        "unknown {} variant {:?}", #name, String::from_utf8_lossy(label),
+                    ).into())),
+                }
+            }
+        },
         Data::Union(_) => error!("union is not supported"),
     };
 
@@ -45,6 +65,8 @@ pub fn decode(input: DeriveInput) -> Result<TokenStream> {
     Ok(quote! {
         #[automatically_derived]
         impl #g1 ::postro::Decode for #ident #g2 #g3 {
+            #format
+
             fn decode(col: ::postro::row::Column) -> Result<Self, ::postro::DecodeError> {
                 #q1
             }
@@ -63,6 +85,10 @@ pub fn encode(input: DeriveInput) -> Result<TokenStream> {
 
     let lt = gt.lifetimes().next().cloned().unwrap();
 
+    if let Data::Enum(data) = &data {
+        return encode_enum(ident, data);
+    }
+
     let q1 = match data {
         Data::Struct(st) => match &st.fields {
             Fields::Unnamed(FieldsUnnamed { unnamed, .. }) => {
@@ -85,7 +111,7 @@ pub fn encode(input: DeriveInput) -> Result<TokenStream> {
             },
             Fields::Unit => quote! { ::postro::encode::Encoded::null() }
         },
-        Data::Enum(_) => error!("union is not yet supported"),
+        Data::Enum(_) => unreachable!(),
         Data::Union(_) => error!("union is not supported"),
     };
 
@@ -108,3 +134,55 @@ pub fn encode(input: DeriveInput) -> Result<TokenStream> {
     }.into())
 }
 
+
+/// Fieldless enum is encoded as postgres enum, with unspecified oid so postgres infer
+/// the actual enum type.
+fn encode_enum(ident: Ident, data: &DataEnum) -> Result<TokenStream> {
+    let (variants, labels): (Vec<_>, Vec<_>) = enum_labels(data)?.into_iter().unzip();
+
+    Ok(quote! {
+        #[automatically_derived]
+        impl ::postro::Encode<'static> for #ident {
+            fn encode(self) -> ::postro::encode::Encoded<'static> {
+                ::postro::Encode::encode(&self)
+            }
+        }
+
+        #[automatically_derived]
+        impl ::postro::Encode<'static> for &#ident {
+            fn encode(self) -> ::postro::encode::Encoded<'static> {
+                let label: &'static str = match self {
+                    #(#ident::#variants => #labels,)*
+                };
+                ::postro::encode::Encoded::from_slice(label.as_bytes(), 0)
+            }
+        }
+    }.into())
+}
+
+/// Returns the variants with its label, which is the variant name or `#[postro(rename = "...")]`.
+fn enum_labels(data: &DataEnum) -> Result<Vec<(Ident, String)>> {
+    data.variants
+        .iter()
+        .map(|variant| {
+            if !matches!(variant.fields, Fields::Unit) {
+                error!("only fieldless enum is supported")
+            }
+
+            let mut label = variant.ident.to_string();
+
+            for attr in variant.attrs.iter().filter(|e|e.path().is_ident("postro")) {
+                attr.parse_nested_meta(|meta| {
+                    if meta.path.is_ident("rename") {
+                        label = meta.value()?.parse::<LitStr>()?.value();
+                        Ok(())
+                    } else {
+                        Err(meta.error("possible value are: `rename = \"...\"`"))
+                    }
+                })?;
+            }
+
+            Ok((variant.ident.clone(), label))
+        })
+        .collect()
+}
@@ -9,10 +9,20 @@ pub fn from_row(input: DeriveInput) -> Result<TokenStream> {
         error!("only struct are currently supported")
     };
 
+    let mut formats = quote! {};
+
     let body = match data.fields {
         Fields::Unnamed(FieldsUnnamed { unnamed, .. }) => {
             let iter = (0..unnamed.len())
                 .map(|_|quote! { iter.try_next()?.decode()?, });
+            let types = unnamed.iter().map(|e|&e.ty);
+
+            // columns is decoded by its position
+            formats = quote! {
+                const FORMATS: &'static [::postro::postgres::PgFormat] = &[
+                    #(<#types as ::postro::Decode>::FORMAT),*
+                ];
+            };
 
             quote! {
                 use ::postro::DecodeError::IndexOutOfBounds as Nope;
@@ -21,31 +31,56 @@ pub fn from_row(input: DeriveInput) -> Result<TokenStream> {
             }
         },
         Fields::Named(FieldsNamed { named, .. }) => {
-            let vars = named
+            let opts = named
                 .iter()
-                .map(|e|e.ident.as_ref().unwrap())
-                .map(|e|(e.to_string(),e))
-                .map(|(name,id)|quote! { let mut #id = Err(Nope(#name.into())); });
-            let arms = named
+                .map(FieldOption::from_field)
+                .collect::<Result<Vec<_>>>()?;
+
+            // flattened field is not a column
+            let names = named
                 .iter()
-                .map(|e|e.ident.as_ref().unwrap())
-                .map(|e|(e.to_string(),e))
-                .map(|(name,id)| quote! { #name => #id = Ok(col.decode()?), });
+                .zip(opts.iter())
+                .filter(|(_,opt)|!opt.flatten)
+                .map(|(field,opt)|opt.column(field));
+
             let iter = named
                 .iter()
-                .map(|e|e.ident.as_ref().unwrap())
-                .map(|id|quote! { #id: #id?, });
+                .zip(opts.iter())
+                .scan(0usize, |i,(field,opt)|{
+                    let id = field.ident.as_ref().unwrap();
+                    if opt.flatten {
+                        return Some(quote! {
+                            #id: ::postro::FromRow::from_row(row.clone())?,
+                        });
+                    }
+                    let nth = *i;
+                    *i += 1;
+                    let missing = match opt.default {
+                        true => quote! { ::core::default::Default::default() },
+                        false => {
+                            let name = opt.column(field);
+                            quote! { return Err(::postro::DecodeError::ColumnNotFound(#name.into())) }
+                        },
+                    };
+                    Some(quote! {
+                        #id: match ordinals[#nth] {
+                            Some(nth) => row.try_get_ordinal(nth)?,
+                            None => #missing,
+                        },
+                    })
+                })
+                .collect::<Vec<_>>();
+
+            let names = names.collect::<Vec<_>>();
+
+            // column ordinals is resolved once per result set
+            let ordinals = match names.is_empty() {
+                true => quote! {},
+                false => quote! { let ordinals = row.ordinals(&[#(#names),*]); },
+            };
 
             quote! {
-                use ::postro::DecodeError::ColumnNotFound as Nope;
-                #(#vars)*
-                for column in row {
-                    let col = column?;
-                    match col.name() {
-                        #(#arms)*
-                        _ => {}
-                    }
-                }
+                #ordinals
                 Ok(Self {
                     #(#iter)*
                 })
@@ -64,6 +99,8 @@ pub fn from_row(input: DeriveInput) -> Result<TokenStream> {
 
     Ok(quote! {
         impl #g1 ::postro::FromRow for #ident #g2 #g3 {
+            #formats
+
             fn from_row(row: ::postro::Row) -> Result<Self, ::postro::DecodeError> {
                 #body
             }
@@ -71,3 +108,49 @@ pub fn from_row(input: DeriveInput) -> Result<TokenStream> {
     }.into())
 }
 
+
+/// Field attributes.
+#[derive(Default)]
+struct FieldOption {
+    /// `#[postro(rename = "...")]`
+    rename: Option<String>,
+    /// `#[postro(default)]`
+    default: bool,
+    /// `#[postro(flatten)]`
+    flatten: bool,
+}
+
+impl FieldOption {
+    fn from_field(field: &Field) -> Result<Self> {
+        let mut opt = Self::default();
+
+        for attr in field.attrs.iter().filter(|e|e.path().is_ident("postro")) {
+            attr.parse_nested_meta(|meta| {
+                if meta.path.is_ident("rename") {
+                    opt.rename = Some(meta.value()?.parse::<LitStr>()?.value());
+                } else if meta.path.is_ident("default") {
+                    opt.default = true;
+                } else if meta.path.is_ident("flatten") {
+                    opt.flatten = true;
+                } else {
+                    return Err(meta.error("possible value are: `rename = \"...\"`, `default` or `flatten`"));
+                }
+                Ok(())
+            })?;
+        }
+
+        if opt.flatten && (opt.rename.is_some() || opt.default) {
+            return Err(Error::new_spanned(field, "`flatten` cannot be combined with other attribute"));
+        }
+
+        Ok(opt)
+    }
+
+    /// Returns the column name.
+    fn column(&self, field: &Field) -> String {
+        match &self.rename {
+            Some(name) => name.clone(),
+            None => field.ident.as_ref().unwrap().to_string(),
+        }
+    }
+}
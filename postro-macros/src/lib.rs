@@ -4,9 +4,17 @@ use syn::DeriveInput;
 mod from_row;
 mod table;
 mod decode;
+mod sql;
+mod migrate;
+mod test;
 
 /// Automatically derive [`FromRow`].
-#[proc_macro_derive(FromRow)]
+///
+/// Field attributes:
+/// - `#[postro(rename = "...")]`, decode from column with given name
+/// - `#[postro(default)]`, use [`Default`] if the column is not found
+/// - `#[postro(flatten)]`, decode nested [`FromRow`] struct from the same row
+#[proc_macro_derive(FromRow,attributes(postro))]
 pub fn from_row(input: TokenStream) -> TokenStream {
     match from_row::from_row(syn::parse_macro_input!(input as DeriveInput)) {
         Ok(ok) => ok,
@@ -24,7 +32,10 @@ pub fn table(input: TokenStream) -> TokenStream {
 }
 
 /// Automatically derive [`Decode`].
-#[proc_macro_derive(Decode)]
+///
+/// Fieldless enum is decoded from postgres enum, the label is the variant name or
+/// `#[postro(rename = "...")]`.
+#[proc_macro_derive(Decode,attributes(postro))]
 pub fn decode(input: TokenStream) -> TokenStream {
     match decode::decode(syn::parse_macro_input!(input as DeriveInput)) {
         Ok(ok) => ok,
@@ -33,7 +44,10 @@ pub fn decode(input: TokenStream) -> TokenStream {
 }
 
 /// Automatically derive [`Encode`].
-#[proc_macro_derive(Encode)]
+///
+/// Fieldless enum is encoded as postgres enum, the label is the variant name or
+/// `#[postro(rename = "...")]`.
+#[proc_macro_derive(Encode,attributes(postro))]
 pub fn encode(input: TokenStream) -> TokenStream {
     match decode::encode(syn::parse_macro_input!(input as DeriveInput)) {
         Ok(ok) => ok,
@@ -41,6 +55,40 @@ pub fn encode(input: TokenStream) -> TokenStream {
     }
 }
 
+/// Include sql file as `&'static str`, validating placeholder numbering at compile time.
+///
+/// The path is relative to the crate root, i.e. `CARGO_MANIFEST_DIR`.
+#[proc_macro]
+pub fn include_sql(input: TokenStream) -> TokenStream {
+    match sql::include_sql(syn::parse_macro_input!(input as syn::LitStr)) {
+        Ok(ok) => ok,
+        Err(err) => err.into_compile_error().into(),
+    }
+}
+
+/// Embed sql migrations in a directory, returns `postro::migrate::Migrator`.
+///
+/// The path is relative to the crate root, i.e. `CARGO_MANIFEST_DIR`. Migration files
+/// must be named `<version>_<name>.sql`.
+#[proc_macro]
+pub fn embed_migrations(input: TokenStream) -> TokenStream {
+    match migrate::embed_migrations(syn::parse_macro_input!(input as syn::LitStr)) {
+        Ok(ok) => ok,
+        Err(err) => err.into_compile_error().into(),
+    }
+}
+
+/// Run async test with a `postro::Pool` connected to a temporary database.
+///
+/// The database is dropped after the test, see `postro::testing::run_test`.
+#[proc_macro_attribute]
+pub fn test(_: TokenStream, input: TokenStream) -> TokenStream {
+    match test::test(syn::parse_macro_input!(input as syn::ItemFn)) {
+        Ok(ok) => ok,
+        Err(err) => err.into_compile_error().into(),
+    }
+}
+
 macro_rules! error {
     ($($tt:tt)*) => {
         return Err(syn::Error::new(proc_macro::Span::call_site().into(), format!($($tt)*)))
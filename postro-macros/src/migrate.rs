@@ -0,0 +1,55 @@
+use proc_macro::TokenStream;
+use quote::quote;
+use syn::*;
+
+pub fn embed_migrations(input: LitStr) -> Result<TokenStream> {
+    let Ok(dir) = std::env::var("CARGO_MANIFEST_DIR") else {
+        return Err(Error::new(input.span(), "CARGO_MANIFEST_DIR is not set"));
+    };
+    let dir = std::path::Path::new(&dir).join(input.value());
+
+    let entries = match std::fs::read_dir(&dir) {
+        Ok(ok) => ok,
+        Err(err) => return Err(Error::new(input.span(), format!("failed to read {}: {err}", dir.display()))),
+    };
+
+    let mut migrations = vec![];
+
+    for entry in entries {
+        let path = match entry {
+            Ok(ok) => ok.path(),
+            Err(err) => return Err(Error::new(input.span(), format!("failed to read {}: {err}", dir.display()))),
+        };
+        if path.extension().is_none_or(|e|e != "sql") {
+            continue;
+        }
+        let file = path.file_name().unwrap_or_default().to_string_lossy().into_owned();
+        let Some((version, name)) = parse_name(&file) else {
+            return Err(Error::new(
+                input.span(),
+                format!("invalid migration file name {file:?}, expected `<version>_<name>.sql`"),
+            ));
+        };
+        migrations.push((version, name.to_owned(), path.to_string_lossy().into_owned()));
+    }
+
+    migrations.sort_by_key(|e|e.0);
+    if let Some(dup) = migrations.windows(2).find(|e|e[0].0 == e[1].0) {
+        return Err(Error::new(input.span(), format!("duplicate migration version {}", dup[0].0)));
+    }
+
+    // `include_str` let cargo track the file changes
+    let migrations = migrations.iter().map(|(version, name, path)| quote! {
+        ::postro::migrate::Migration::new(#version, #name, include_str!(#path))
+    });
+
+    Ok(quote! {
+        ::postro::migrate::Migrator::new([#(#migrations),*])
+    }.into())
+}
+
+/// Parse `<version>_<name>.sql` file name.
+fn parse_name(file: &str) -> Option<(i64, &str)> {
+    let (version, name) = file.strip_suffix(".sql")?.split_once('_')?;
+    Some((version.parse().ok()?, name))
+}
@@ -0,0 +1,122 @@
+use proc_macro::TokenStream;
+use quote::quote;
+use syn::*;
+
+pub fn include_sql(input: LitStr) -> Result<TokenStream> {
+    let Ok(dir) = std::env::var("CARGO_MANIFEST_DIR") else {
+        return Err(Error::new(input.span(), "CARGO_MANIFEST_DIR is not set"));
+    };
+    let path = std::path::Path::new(&dir).join(input.value());
+
+    let sql = match std::fs::read_to_string(&path) {
+        Ok(ok) => ok,
+        Err(err) => return Err(Error::new(input.span(), format!("failed to read {}: {err}", path.display()))),
+    };
+
+    if let Err(err) = placeholders(&sql) {
+        return Err(Error::new(input.span(), format!("{}: {err}", input.value())));
+    }
+
+    // `include_str` let cargo track the file changes
+    let path = path.to_string_lossy();
+    Ok(quote! { include_str!(#path) }.into())
+}
+
+/// Returns the number of `$n` placeholders, or error if the numbering is not contiguous
+/// starting from `$1`.
+///
+/// Placeholders in string literals, quoted identifiers and comments are ignored.
+pub fn placeholders(sql: &str) -> std::result::Result<usize, String> {
+    let bytes = sql.as_bytes();
+    let mut found = Vec::<usize>::new();
+    let mut i = 0;
+
+    // index after `end`, or end of input
+    let skip_until = |from: usize, end: &[u8]| {
+        bytes[from..]
+            .windows(end.len())
+            .position(|e|e == end)
+            .map_or(bytes.len(), |e|from + e + end.len())
+    };
+
+    while i < bytes.len() {
+        match bytes[i] {
+            // string literal and quoted identifier, escaped quote is two adjacent quote,
+            // which is the same as two literal
+            q @ (b'\'' | b'"') => i = skip_until(i + 1, &[q]),
+            b'-' if bytes.get(i + 1) == Some(&b'-') => i = skip_until(i + 2, b"\n"),
+            b'/' if bytes.get(i + 1) == Some(&b'*') => {
+                // block comment can be nested
+                let mut depth = 1;
+                i += 2;
+                while i < bytes.len() && depth != 0 {
+                    match &bytes[i..] {
+                        [b'/', b'*', ..] => { depth += 1; i += 2; },
+                        [b'*', b'/', ..] => { depth -= 1; i += 2; },
+                        _ => i += 1,
+                    }
+                }
+            },
+            b'$' => {
+                let digits = bytes[i + 1..].iter().take_while(|e|e.is_ascii_digit()).count();
+                if digits != 0 {
+                    let n = sql[i + 1..i + 1 + digits].parse().map_err(|_|"placeholder number overflow")?;
+                    found.push(n);
+                    i += 1 + digits;
+                    continue;
+                }
+
+                // dollar quoted string, e.g: `$$body$$` or `$tag$body$tag$`
+                let tag = bytes[i + 1..]
+                    .iter()
+                    .take_while(|e|e.is_ascii_alphanumeric() || **e == b'_')
+                    .count();
+                match bytes.get(i + 1 + tag) {
+                    Some(b'$') => {
+                        let delim = &bytes[i..i + tag + 2];
+                        i = skip_until(i + tag + 2, delim);
+                    },
+                    _ => i += 1,
+                }
+            },
+            _ => i += 1,
+        }
+    }
+
+    found.sort_unstable();
+    found.dedup();
+
+    if found.first().is_some_and(|e|*e == 0) {
+        return Err("placeholder starts from `$1`, found `$0`".into());
+    }
+
+    for (i, n) in found.iter().enumerate() {
+        if *n != i + 1 {
+            return Err(format!("placeholder `${}` is missing, found `${n}`", i + 1));
+        }
+    }
+
+    Ok(found.len())
+}
+
+#[cfg(test)]
+mod test {
+    use super::placeholders;
+
+    #[test]
+    fn placeholder_numbering() {
+        assert_eq!(placeholders("SELECT 1"), Ok(0));
+        assert_eq!(placeholders("SELECT $1, $2, $1"), Ok(2));
+        assert_eq!(placeholders("SELECT $2, $1::int4"), Ok(2));
+        assert!(placeholders("SELECT $1, $3").is_err());
+        assert!(placeholders("SELECT $0").is_err());
+        assert!(placeholders("SELECT $2").is_err());
+
+        // ignored
+        assert_eq!(placeholders("SELECT '$2', \"$3\", $1"), Ok(1));
+        assert_eq!(placeholders("SELECT 'it''s $2', $1"), Ok(1));
+        assert_eq!(placeholders("SELECT $1 -- $3\n, $2"), Ok(2));
+        assert_eq!(placeholders("SELECT /* $3 /* $4 */ */ $1"), Ok(1));
+        assert_eq!(placeholders("SELECT $$ $3 $$, $tag$ $4 $tag$, $1"), Ok(1));
+    }
+}
@@ -0,0 +1,31 @@
+use proc_macro::TokenStream;
+use quote::quote;
+use syn::*;
+use crate::error;
+
+pub fn test(input: ItemFn) -> Result<TokenStream> {
+    let ItemFn { attrs, vis, sig, block } = input;
+
+    if sig.asyncness.is_none() {
+        error!("test function must be async")
+    }
+
+    let ident = &sig.ident;
+    let output = &sig.output;
+    let inputs = &sig.inputs;
+
+    let call = match inputs.len() {
+        0 => quote! { |_| #ident() },
+        1 => quote! { #ident },
+        _ => error!("test function accept at most one `Pool` argument"),
+    };
+
+    Ok(quote! {
+        #(#attrs)*
+        #[::core::prelude::v1::test]
+        #vis fn #ident() #output {
+            async fn #ident(#inputs) #output #block
+            ::postro::testing::run_test(::core::stringify!(#ident), #call)
+        }
+    }.into())
+}
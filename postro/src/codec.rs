@@ -0,0 +1,353 @@
+//! Custom type codec registration.
+//!
+//! Extension types often have no fixed [`Oid`], and implementing [`Decode`] for them
+//! is impossible without knowing the [`Oid`] upfront. A [`Codec`] can be registered
+//! to a [`Config`][crate::Config], keyed by [`Oid`] or type name, which resolved to [`Oid`]
+//! when connecting.
+//!
+//! # Example
+//!
+//! ```no_run
+//! use postro::{Config, Connection, codec::{Codec, Custom}};
+//!
+//! struct Point(i32, i32);
+//!
+//! # async fn app() -> postro::Result<()> {
+//! let codec = Codec::with_type_name(
+//!     "my_point",
+//!     |value| Ok(Point(value[0] as _, value[1] as _)),
+//!     |point: &Point| vec![point.0 as u8, point.1 as u8],
+//! );
+//!
+//! let mut conn = Connection::connect_with(Config::from_env().codec(codec)).await?;
+//!
+//! let point = conn.codecs().encode(&Point(4, 2)).unwrap();
+//! let (Custom(point),) = postro::query_as::<_, _, (Custom<Point>,)>("SELECT $1", &mut conn)
+//!     .bind(point)
+//!     .fetch_one()
+//!     .await?;
+//! # Ok(())
+//! # }
+//! ```
+//!
+//! # User-defined types
+//!
+//! Enums, composites and domains created with `CREATE TYPE` or `CREATE DOMAIN` also have
+//! no fixed [`Oid`]. Their [`TypeInfo`] can be resolved when connecting via
+//! [`Config::resolve_type`][crate::Config::resolve_type], which is then available from
+//! [`Codecs::type_info`] and [`Column::type_info`].
+//!
+//! Resolved enum is decodable as [`String`], and resolved composite as
+//! [`Composite`][crate::types::Composite].
+use bytes::Bytes;
+use std::{
+    any::{Any, TypeId},
+    borrow::Cow,
+    collections::HashMap,
+    fmt,
+    sync::Arc,
+};
+
+use crate::{
+    Decode, DecodeError, Result,
+    common::ByteStr,
+    encode::Encoded,
+    postgres::Oid,
+    row::{Column, OidMissmatch},
+    transport::PgTransport,
+};
+
+type DecodeFn = dyn Fn(&[u8]) -> Result<Box<dyn Any>, DecodeError> + Send + Sync;
+type EncodeFn = dyn Fn(&dyn Any) -> Bytes + Send + Sync;
+
+/// Encode and decode function pair for a custom type.
+#[derive(Clone)]
+pub struct Codec {
+    key: CodecKey,
+    type_id: TypeId,
+    decode: Arc<DecodeFn>,
+    encode: Arc<EncodeFn>,
+}
+
+#[derive(Clone, Debug)]
+enum CodecKey {
+    Oid(Oid),
+    Name(Cow<'static, str>),
+}
+
+impl Codec {
+    /// Create codec for type with known [`Oid`].
+    pub fn with_oid<T, D, E, B>(oid: Oid, decode: D, encode: E) -> Codec
+    where
+        T: Any,
+        D: Fn(&[u8]) -> Result<T, DecodeError> + Send + Sync + 'static,
+        E: Fn(&T) -> B + Send + Sync + 'static,
+        B: Into<Bytes>,
+    {
+        Self::new(CodecKey::Oid(oid), decode, encode)
+    }
+
+    /// Create codec for type with given name, the [`Oid`] is resolved when connecting.
+    pub fn with_type_name<T, D, E, B>(name: impl Into<Cow<'static, str>>, decode: D, encode: E) -> Codec
+    where
+        T: Any,
+        D: Fn(&[u8]) -> Result<T, DecodeError> + Send + Sync + 'static,
+        E: Fn(&T) -> B + Send + Sync + 'static,
+        B: Into<Bytes>,
+    {
+        Self::new(CodecKey::Name(name.into()), decode, encode)
+    }
+
+    fn new<T, D, E, B>(key: CodecKey, decode: D, encode: E) -> Codec
+    where
+        T: Any,
+        D: Fn(&[u8]) -> Result<T, DecodeError> + Send + Sync + 'static,
+        E: Fn(&T) -> B + Send + Sync + 'static,
+        B: Into<Bytes>,
+    {
+        Self {
+            key,
+            type_id: TypeId::of::<T>(),
+            decode: Arc::new(move |value| Ok(Box::new(decode(value)?))),
+            encode: Arc::new(move |value| {
+                encode(value.downcast_ref().expect("codec type missmatch")).into()
+            }),
+        }
+    }
+}
+
+/// Resolved postgres type, see [`Config::resolve_type`][crate::Config::resolve_type].
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct TypeInfo {
+    oid: Oid,
+    array_oid: Oid,
+    name: String,
+    kind: TypeKind,
+}
+
+/// Kind of postgres type, from `pg_type.typtype`.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum TypeKind {
+    /// Base type, e.g: type from extension.
+    Base,
+    /// Enum type with its labels in sort order.
+    Enum(Vec<String>),
+    /// Domain with the [`Oid`] of its base type.
+    Domain(Oid),
+    /// Composite type with its fields name and [`Oid`] in order.
+    Composite(Vec<(String, Oid)>),
+    /// Other type, e.g: range or pseudo type.
+    Other,
+}
+
+impl TypeInfo {
+    /// Returns the type [`Oid`].
+    pub fn oid(&self) -> Oid {
+        self.oid
+    }
+
+    /// Returns the [`Oid`] of the array of this type, or `0` if there is none.
+    pub fn array_oid(&self) -> Oid {
+        self.array_oid
+    }
+
+    /// Returns the type name, schema qualified if it is not in the `search_path`.
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+
+    /// Returns the type [`TypeKind`].
+    pub fn kind(&self) -> &TypeKind {
+        &self.kind
+    }
+}
+
+/// Registered [`Codec`]s and resolved [`TypeInfo`]s of a connection.
+#[derive(Clone, Default)]
+pub struct Codecs {
+    decoders: HashMap<(Oid, TypeId), Arc<DecodeFn>>,
+    encoders: HashMap<TypeId, (Oid, Arc<EncodeFn>)>,
+    types: HashMap<Oid, TypeInfo>,
+    names: HashMap<Cow<'static, str>, Oid>,
+}
+
+impl Codecs {
+    /// Returns `true` if there is no registered codec nor resolved type.
+    pub fn is_empty(&self) -> bool {
+        self.encoders.is_empty() && self.types.is_empty()
+    }
+
+    /// Returns resolved [`TypeInfo`] by the name it is registered with.
+    pub fn type_info(&self, name: &str) -> Option<&TypeInfo> {
+        self.types.get(self.names.get(name)?)
+    }
+
+    /// Returns resolved [`TypeInfo`] by its [`Oid`].
+    pub fn type_info_by_oid(&self, oid: Oid) -> Option<&TypeInfo> {
+        self.types.get(&oid)
+    }
+
+    /// Encode value using registered codec.
+    ///
+    /// Returns [`None`] if there is no codec registered for type `T`.
+    pub fn encode<T: Any>(&self, value: &T) -> Option<Encoded<'static>> {
+        let (oid, encode) = self.encoders.get(&TypeId::of::<T>())?;
+        Some(Encoded::owned(encode(value), *oid))
+    }
+
+    /// Decode value using registered codec.
+    ///
+    /// Returns [`DecodeError::OidMissmatch`] if there is no codec registered
+    /// for type `T` with given [`Oid`].
+    pub fn decode<T: Any>(&self, oid: Oid, value: &[u8]) -> Result<T, DecodeError> {
+        let Some(decode) = self.decoders.get(&(oid, TypeId::of::<T>())) else {
+            return Err(DecodeError::OidMissmatch(Box::new(OidMissmatch {
+                column: ByteStr::default(),
+                rust_type: std::any::type_name::<T>(),
+                expected: None,
+                actual: oid,
+            })));
+        };
+        Ok(*decode(value)?.downcast().expect("codec type missmatch"))
+    }
+
+    fn insert(&mut self, oid: Oid, codec: &Codec) {
+        self.decoders.insert((oid, codec.type_id), codec.decode.clone());
+        self.encoders.insert(codec.type_id, (oid, codec.encode.clone()));
+    }
+}
+
+/// Resolve codecs [`Oid`] and types, and build the registry.
+pub(crate) async fn resolve<IO: PgTransport>(
+    codecs: &[Codec],
+    types: &[Cow<'static, str>],
+    mut io: IO,
+) -> Result<Codecs> {
+    let mut registry = Codecs::default();
+
+    for name in types {
+        registry.resolve(name.clone(), &mut io).await?;
+    }
+
+    for codec in codecs {
+        let oid = match &codec.key {
+            CodecKey::Oid(oid) => *oid,
+            CodecKey::Name(name) => registry.resolve(name.clone(), &mut io).await?,
+        };
+        registry.insert(oid, codec);
+    }
+
+    Ok(registry)
+}
+
+impl Codecs {
+    /// Query `pg_type` for type with given name, returns its [`Oid`].
+    async fn resolve<IO: PgTransport>(&mut self, name: Cow<'static, str>, mut io: IO) -> Result<Oid> {
+        if let Some(oid) = self.names.get(&name) {
+            return Ok(*oid);
+        }
+
+        let row = crate::query(
+            "SELECT oid, oid::regtype::text, typtype::text, typbasetype, typarray, typrelid \
+            FROM pg_type WHERE oid = to_regtype($1)",
+            &mut io,
+        )
+        .bind(name.as_ref())
+        .fetch_optional()
+        .await?;
+
+        let Some(row) = row else {
+            return Err(TypeNotFound { name }.into());
+        };
+
+        let oid = row.try_get::<_, u32>(0)?;
+        let kind = match row.try_get::<_, String>(2)?.as_str() {
+            "b" => TypeKind::Base,
+            "d" => TypeKind::Domain(row.try_get(3)?),
+            "e" => TypeKind::Enum(
+                crate::query_scalar::<_, _, String>(
+                    "SELECT enumlabel::text FROM pg_enum WHERE enumtypid = $1 ORDER BY enumsortorder",
+                    &mut io,
+                )
+                .bind(oid)
+                .fetch_all()
+                .await?,
+            ),
+            "c" => TypeKind::Composite(
+                crate::query_as::<_, _, (String, u32)>(
+                    "SELECT attname::text, atttypid FROM pg_attribute \
+                    WHERE attrelid = $1 AND attnum > 0 AND NOT attisdropped ORDER BY attnum",
+                    &mut io,
+                )
+                .bind(row.try_get::<_, u32>(5)?)
+                .fetch_all()
+                .await?,
+            ),
+            _ => TypeKind::Other,
+        };
+
+        self.types.insert(oid, TypeInfo {
+            oid,
+            array_oid: row.try_get(4)?,
+            name: row.try_get(1)?,
+            kind,
+        });
+        self.names.insert(name, oid);
+        Ok(oid)
+    }
+}
+
+/// Decode value using [`Codec`] registered in the connection.
+#[derive(Debug)]
+pub struct Custom<T>(pub T);
+
+impl<T: Any> Decode for Custom<T> {
+    fn decode(column: Column) -> Result<Self, DecodeError> {
+        let Some(codecs) = column.codecs() else {
+            return Err(column.oid_missmatch::<T>(None));
+        };
+        let Some(value) = column.as_slice() else {
+            return Err(DecodeError::Null);
+        };
+        match codecs.decode(column.oid(), value) {
+            Ok(ok) => Ok(Custom(ok)),
+            Err(DecodeError::OidMissmatch(_)) => Err(column.oid_missmatch::<T>(None)),
+            Err(err) => Err(err),
+        }
+    }
+}
+
+/// An error when type name registered in [`Codec`] or
+/// [`Config::resolve_type`][crate::Config::resolve_type] does not exists.
+pub struct TypeNotFound {
+    name: Cow<'static, str>,
+}
+
+impl std::error::Error for TypeNotFound { }
+
+impl fmt::Display for TypeNotFound {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "type not found: {:?}", self.name)
+    }
+}
+
+impl fmt::Debug for TypeNotFound {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "\"{self}\"")
+    }
+}
+
+impl fmt::Debug for Codec {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("Codec").field("key", &self.key).finish_non_exhaustive()
+    }
+}
+
+impl fmt::Debug for Codecs {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_set()
+            .entries(self.decoders.keys().map(|(oid, _)| oid))
+            .entries(self.types.values().map(|e| &e.name))
+            .finish()
+    }
+}
@@ -2,6 +2,11 @@
 mod bytestr;
 pub use bytestr::ByteStr;
 
+/// Quote sql identifier.
+pub(crate) fn quote_ident(ident: &str) -> String {
+    format!("\"{}\"", ident.replace('"', "\"\""))
+}
+
 /// Create unit type `Error`.
 ///
 /// # Example
@@ -41,6 +46,14 @@ macro_rules! verbose {
     };
 }
 
+/// Warn about protocol resource left unfinished, only in debug build with `log` feature.
+macro_rules! leak {
+    ($($tt:tt)*) => {
+        #[cfg(all(debug_assertions, feature = "log"))]
+        log::warn!($($tt)*);
+    };
+}
+
 /// Create and enter `Span` when `verbose` feature enabled.
 macro_rules! span {
     ($($tt:tt)*) => {
@@ -53,5 +66,6 @@ macro_rules! span {
 
 pub(crate) use unit_error;
 pub(crate) use verbose;
+pub(crate) use leak;
 pub(crate) use span;
 
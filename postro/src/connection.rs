@@ -2,16 +2,19 @@
 use bytes::{Buf, BytesMut};
 use lru::LruCache;
 use std::{
+    collections::VecDeque,
     future::Ready,
     io,
     num::NonZeroUsize,
-    task::{Context, Poll, ready},
+    sync::{Arc, atomic::{AtomicBool, Ordering}},
+    task::{Context, Poll, Waker, ready},
     time::Instant,
 };
 
 use crate::{
     Result,
-    common::{span, verbose},
+    codec::{self, Codecs},
+    common::{ByteStr, span, unit_error, verbose},
     executor::Executor,
     net::Socket,
     phase,
@@ -20,20 +23,31 @@ use crate::{
     },
     statement::StatementName,
     transport::{PgTransport, PgTransportExt},
+    types::PgLsn,
 };
 
+mod auth;
+mod cancel;
 mod config;
+mod memory;
+mod notify;
+mod passfile;
 
-pub use config::{Config, ParseError};
+pub use auth::{AuthError, AuthHandler};
+pub use cancel::CancelHandle;
+pub use config::{Config, ParseError, SslMode, TargetSessionAttrs};
+pub use memory::MemoryUsage;
+pub use notify::Notifications;
 
 const DEFAULT_BUF_CAPACITY: usize = 1024;
-const DEFAULT_PREPARED_STMT_CACHE: NonZeroUsize = NonZeroUsize::new(24).unwrap();
 
 /// Postgres Connection.
 ///
 /// # Features
 ///
-/// Connection cache a prepared statement. To opt out, use [`once`][1] when querying.
+/// Connection cache a prepared statement. To opt out, use [`once`][1] when querying. The
+/// cache capacity is set via [`Config::statement_cache_capacity`], and cached statements
+/// can be closed via [`Connection::clear_cached_statements`], e.g: after DDL changes.
 ///
 /// Connection handle `NoticeResponse` message. If the `log` feature is enabled,
 /// `NoticeResponse` will be logged, otherwise it ignored.
@@ -42,7 +56,12 @@ const DEFAULT_PREPARED_STMT_CACHE: NonZeroUsize = NonZeroUsize::new(24).unwrap()
 /// This is postgres specific and happens transparently, most users
 /// does not need to worry about this.
 ///
-/// Connection will also consume `ParameterStatus` message
+/// Connection will also consume `ParameterStatus` message, reported parameters
+/// can be retrieved via [`Connection::parameter`], or watched for changes via
+/// [`Connection::parameter_updates`].
+///
+/// `NotificationResponse` that arrive while executing queries are buffered,
+/// see [`Connection::listen`].
 ///
 /// # Pending Messages
 ///
@@ -67,14 +86,35 @@ pub struct Connection {
     socket: Socket,
     read_buf: BytesMut,
     write_buf: BytesMut,
+    write_watermark: usize,
+    /// see [`Config::buffer_shrink_threshold`]
+    shrink_threshold: Option<usize>,
 
     // feature
-    stmts: LruCache<u64, StatementName>,
+    /// `None` if statement caching is disabled
+    stmts: Option<LruCache<u64, StatementName>>,
+    codecs: Arc<Codecs>,
+    parameters: Vec<backend::ParameterStatus>,
+    #[cfg(feature = "tokio")]
+    parameter_updates: Option<tokio::sync::watch::Sender<Vec<backend::ParameterStatus>>>,
+    notifications: VecDeque<backend::NotificationResponse>,
 
     // diagnostic
     connected_at: Instant,
     sync_pending: usize,
+    /// `Sync` or `Query` sent which `ReadyForQuery` is not yet received
+    sync_inflight: usize,
+    /// transaction status of the last `ReadyForQuery`
+    tx_status: u8,
+    poisoned: bool,
+    /// see [`PgTransport::abandoned`]
+    abandoned: Arc<AtomicBool>,
+    /// see [`Config::skip_unknown_messages`]
+    skip_unknown: bool,
+    /// see [`Config::normalize_statements`]
+    normalize: bool,
     backend_key: backend::BackendKeyData,
+    addr: (ByteStr, u16),
 }
 
 impl Connection {
@@ -103,34 +143,120 @@ impl Connection {
     /// # Panics
     ///
     /// Panics if `tokio` feature is not enabled.
+    ///
+    /// If multiple hosts are configured, each host is tried in order until one connects
+    /// and matches [`Config::target_session_attrs`], otherwise the last error is returned.
     pub async fn connect_with(config: Config) -> Result<Self> {
-        let socket = if cfg!(unix) && config.host == "localhost" {
-            let socket = Socket::connect_socket(&(format!("/run/postgresql/.s.PGSQL.{}",config.port))).await;
-            match socket {
-                Ok(ok) => ok,
-                Err(_) => Socket::connect_tcp(&config.host, config.port).await?,
+        if config.ssl_mode.requires_tls() {
+            return Err(TlsNotSupported.into());
+        }
+
+        let mut last_err = None;
+
+        for (host, port) in &config.hosts {
+            let connect = Self::connect_host(&config, host, *port);
+            let result = match config.connect_timeout {
+                #[cfg(feature = "tokio")]
+                Some(timeout) => tokio::time::timeout(timeout, connect).await.unwrap_or_else(|_| {
+                    Err(io::Error::new(io::ErrorKind::TimedOut, "timed out connecting to server").into())
+                }),
+                _ => connect.await,
+            };
+            match result {
+                Ok(ok) => return Ok(ok),
+                Err(err) => {
+                    #[cfg(feature = "log")]
+                    if config.hosts.len() > 1 {
+                        log::warn!("failed to connect to {host}:{port}: {err}");
+                    }
+                    last_err = Some(err);
+                },
             }
-        } else {
-            Socket::connect_tcp(&config.host, config.port).await?
-        };
+        }
+
+        Err(last_err.expect("config contains at least one host"))
+    }
+
+    async fn connect_host(config: &Config, host: &ByteStr, port: u16) -> Result<Self> {
+        let socket = connect_socket(host, port).await?;
 
         let mut me = Self {
             socket,
             read_buf: BytesMut::with_capacity(DEFAULT_BUF_CAPACITY),
             write_buf: BytesMut::with_capacity(DEFAULT_BUF_CAPACITY),
-            stmts: LruCache::new(DEFAULT_PREPARED_STMT_CACHE),
+            write_watermark: config.write_watermark,
+            shrink_threshold: config.buffer_shrink_threshold,
+            stmts: NonZeroUsize::new(config.stmt_cache_capacity)
+                .filter(|_| config.stmt_cache)
+                .map(LruCache::new),
+            codecs: Arc::default(),
+            parameters: Vec::new(),
+            #[cfg(feature = "tokio")]
+            parameter_updates: None,
+            notifications: VecDeque::new(),
             connected_at: Instant::now(),
             backend_key: backend::BackendKeyData { process_id: 0, secret_key: 0 },
+            addr: (host.clone(), port),
             sync_pending: 0,
+            sync_inflight: 0,
+            tx_status: b'I',
+            poisoned: false,
+            abandoned: Arc::default(),
+            skip_unknown: config.skip_unknown_messages,
+            normalize: config.normalize_statements,
         };
 
-        let res = phase::startup(&config, &mut me).await?;
+        let mut startup = phase::StartupConfig::from(config);
+        if config.pass.is_empty()
+            && let Some(path) = &config.passfile
+            && let Some(pass) = passfile::lookup(path, host, port, &config.dbname, &config.user)
+        {
+            startup.set_password(pass);
+        }
+        if let Some(auth) = &config.auth_handler {
+            startup.auth_handler = Some(auth.create(host));
+        }
+
+        let res = phase::startup(startup, &mut me).await?;
         me.backend_key = res.backend_key_data;
 
+        if config.strict_encoding {
+            me.check_encoding()?;
+        }
+
+        let target = config.target_session_attrs;
+        if target != TargetSessionAttrs::Any && !me.is_session_attrs(target).await? {
+            let _ = me.close().await;
+            return Err(SessionAttrsMismatch(target).into());
+        }
+
+        if !config.codecs.is_empty() || !config.types.is_empty() {
+            me.codecs = Arc::new(codec::resolve(&config.codecs, &config.types, &mut me).await?);
+        }
+
         Ok(me)
     }
 }
 
+/// Connect via unix socket if host is a directory path.
+///
+/// For localhost, unix socket in `/run/postgresql` is tried first, otherwise via tcp.
+async fn connect_socket(host: &str, port: u16) -> io::Result<Socket> {
+    if host.starts_with('/') {
+        if cfg!(unix) {
+            return Socket::connect_socket(&format!("{host}/.s.PGSQL.{port}")).await;
+        }
+        return Err(io::Error::new(io::ErrorKind::Unsupported, "unix socket is not supported"));
+    }
+    if cfg!(unix) && host == "localhost" {
+        let socket = Socket::connect_socket(&(format!("/run/postgresql/.s.PGSQL.{}",port))).await;
+        if let Ok(ok) = socket {
+            return Ok(ok);
+        }
+    }
+    Socket::connect_tcp(host, port).await
+}
+
 impl Connection {
     /// Get the [`Instant`] value of when the socket is connected to postgres server.
     pub fn connected_at(&self) -> Instant {
@@ -143,6 +269,245 @@ impl Connection {
     pub fn backend_key(&self) -> backend::BackendKeyData {
         self.backend_key
     }
+
+    /// Returns [`CancelHandle`] to cancel query running in this connection from other task.
+    pub fn cancel_handle(&self) -> CancelHandle {
+        CancelHandle {
+            host: self.addr.0.clone(),
+            port: self.addr.1,
+            key: self.backend_key,
+        }
+    }
+
+    /// Returns registered custom type [`Codecs`].
+    pub fn codecs(&self) -> &Codecs {
+        &self.codecs
+    }
+
+    /// Returns `true` if the connection received unexpected message from the server.
+    ///
+    /// A poisoned connection always returns [`ConnectionPoisoned`] error, and is discarded
+    /// by the pool when released.
+    pub fn is_poisoned(&self) -> bool {
+        self.poisoned
+    }
+
+    /// Returns the number of cached prepared statement.
+    pub fn cached_statements(&self) -> usize {
+        self.stmts.as_ref().map_or(0, LruCache::len)
+    }
+
+    /// Close all cached prepared statements.
+    ///
+    /// Cached statements may fail with `cached plan must not change result type` error
+    /// after the schema it depends on is altered. Query outside of transaction is prepared
+    /// again transparently, inside transaction, clearing the cache makes subsequent queries
+    /// prepared again.
+    ///
+    /// `Close` messages are queued and sent on the next operation.
+    pub fn clear_cached_statements(&mut self) {
+        let Some(stmts) = self.stmts.as_mut() else {
+            return;
+        };
+        let names = stmts.iter().map(|(_, name)|name.clone()).collect::<Vec<_>>();
+        stmts.clear();
+
+        if names.is_empty() {
+            return;
+        }
+
+        for name in names {
+            self.send(frontend::Close {
+                variant: b'S',
+                name: name.as_str(),
+            });
+        }
+        self.send(frontend::Sync);
+        self.ready_request();
+    }
+
+    /// Returns run-time parameter reported by the server, e.g: `server_version`.
+    ///
+    /// See [Postgres Documentation][1] for parameters that are reported.
+    ///
+    /// [1]: https://www.postgresql.org/docs/current/protocol-flow.html#PROTOCOL-ASYNC
+    pub fn parameter(&self, name: &str) -> Option<&str> {
+        self.parameters.iter().find(|e|e.name == name).map(|e|e.value.as_ref())
+    }
+
+    /// Returns the reported `server_version`, e.g: `16.2`.
+    ///
+    /// See also [`Connection::server_version_num`].
+    pub fn server_version(&self) -> Option<&str> {
+        self.parameter("server_version")
+    }
+
+    /// Returns the reported `client_encoding`, e.g: `UTF8`.
+    pub fn client_encoding(&self) -> Option<&str> {
+        self.parameter("client_encoding")
+    }
+
+    /// Returns the reported session `TimeZone`, e.g: `Etc/UTC`.
+    ///
+    /// Updated when the session time zone changes, e.g: after `SET TimeZone`.
+    pub fn timezone(&self) -> Option<&str> {
+        self.parameter("TimeZone")
+    }
+
+    /// Returns the server version as integer, e.g: `160002` for version `16.2`.
+    ///
+    /// Returns [`None`] if the server did not report valid `server_version`.
+    pub fn server_version_num(&self) -> Option<u32> {
+        let version = self.parameter("server_version")?;
+        let mut parts = version
+            .split(|e: char|!e.is_ascii_digit())
+            .take_while(|e|!e.is_empty())
+            .map(str::parse::<u32>);
+        let major = parts.next()?.ok()?;
+        let minor = parts.next().and_then(Result::ok).unwrap_or(0);
+
+        // before version 10, version have 3 parts
+        match major >= 10 {
+            true => Some(major * 10000 + minor),
+            false => Some(major * 10000 + minor * 100 + parts.next().and_then(Result::ok).unwrap_or(0)),
+        }
+    }
+
+    /// Returns [`UnsupportedVersion`] error if the server major version is lower than `major`.
+    ///
+    /// See also [`require_version!`][crate::require_version].
+    pub fn require_version(&self, major: u32, feature: &'static str) -> Result<(), UnsupportedVersion> {
+        let version = self.server_version_num().unwrap_or(0);
+        match version >= major * 10000 {
+            true => Ok(()),
+            false => Err(UnsupportedVersion { feature, required: major, version }),
+        }
+    }
+
+    /// Returns [`UnsupportedEncoding`] error if reported encoding may produce non UTF-8 text.
+    fn check_encoding(&self) -> Result<(), UnsupportedEncoding> {
+        check_encoding(self.parameter("client_encoding"), self.parameter("server_encoding"))
+    }
+
+    /// Returns `true` if server session matches the target.
+    async fn is_session_attrs(&mut self, target: TargetSessionAttrs) -> Result<bool> {
+        let read_only = crate::query_scalar::<_, _, String>("SHOW transaction_read_only", &mut *self)
+            .fetch_one()
+            .await?;
+        Ok(match target {
+            TargetSessionAttrs::Any => true,
+            TargetSessionAttrs::ReadWrite => read_only == "off",
+            TargetSessionAttrs::ReadOnly => read_only == "on",
+        })
+    }
+
+    /// Returns a watch receiver of all run-time parameters reported by the server.
+    ///
+    /// The receiver is notified when a parameter changes mid-session, e.g: after
+    /// `SET TimeZone` or server configuration reload. Note that parameter changes are
+    /// only received while the connection is in use.
+    ///
+    /// # Example
+    ///
+    /// ```no_run
+    /// # async fn test(mut conn: postro::Connection) -> postro::Result<()> {
+    /// let mut updates = conn.parameter_updates();
+    ///
+    /// tokio::spawn(async move {
+    ///     while updates.changed().await.is_ok() {
+    ///         let params = updates.borrow_and_update();
+    ///         let timezone = params.iter().find(|e|e.name == "TimeZone");
+    ///         println!("TimeZone: {:?}", timezone.map(|e|&e.value));
+    ///     }
+    /// });
+    /// # Ok(())
+    /// # }
+    /// ```
+    #[cfg(feature = "tokio")]
+    pub fn parameter_updates(&mut self) -> tokio::sync::watch::Receiver<Vec<backend::ParameterStatus>> {
+        match &self.parameter_updates {
+            Some(send) => send.subscribe(),
+            None => {
+                let (send, recv) = tokio::sync::watch::channel(self.parameters.clone());
+                self.parameter_updates = Some(send);
+                recv
+            },
+        }
+    }
+
+    fn set_parameter(&mut self, param: backend::ParameterStatus) {
+        let changed = match self.parameters.iter_mut().find(|e|e.name == param.name) {
+            Some(e) if e.value == param.value => false,
+            Some(e) => {
+                e.value = param.value;
+                true
+            },
+            None => {
+                self.parameters.push(param);
+                true
+            },
+        };
+
+        if changed {
+            #[cfg(feature = "tokio")]
+            if let Some(send) = &self.parameter_updates {
+                send.send_replace(self.parameters.clone());
+            }
+        }
+    }
+
+    /// Describe the statement without executing it.
+    ///
+    /// See [`describe`][crate::describe::describe] for more details.
+    pub async fn describe<SQL: crate::sql::Sql>(&mut self, sql: SQL) -> Result<crate::describe::StatementInfo> {
+        crate::describe::describe(sql, self).await
+    }
+
+    /// Close a portal by name, e.g: [`SuspendedPortal::name`][1].
+    ///
+    /// Closing a nonexistent portal is not an error.
+    ///
+    /// [1]: crate::portal::SuspendedPortal::name
+    pub async fn close_portal(&mut self, name: &str) -> Result<()> {
+        crate::portal::close(b'P', name, self).await
+    }
+
+    /// Close a prepared statement by name, removing it from statement cache.
+    ///
+    /// Closing a nonexistent statement is not an error.
+    pub async fn close_statement(&mut self, name: &str) -> Result<()> {
+        if let Some(stmts) = self.stmts.as_mut()
+            && let Some(id) = stmts.iter().find(|(_, stmt)| stmt.as_str() == name).map(|(id, _)| *id)
+        {
+            stmts.pop(&id);
+        }
+        crate::portal::close(b'S', name, self).await
+    }
+
+    /// Check that the connection is alive with a single round trip.
+    ///
+    /// Sends a `Sync` message and waits for `ReadyForQuery`, which is cheaper than
+    /// executing `SELECT 1`. Pending actions are executed first, see [`Connection::ready`].
+    pub async fn ping(&mut self) -> Result<()> {
+        self.ping_request();
+        self.ready().await
+    }
+
+    /// Queue `Sync` message, its `ReadyForQuery` is awaited in [`Connection::poll_ready`].
+    pub(crate) fn ping_request(&mut self) {
+        self.send(frontend::Sync);
+        self.ready_request();
+    }
+
+    /// Returns the current write-ahead log write location.
+    ///
+    /// The returned [`PgLsn`] can be passed to [`Pool::wait_for_lsn`][1] to
+    /// read your own writes from a standby server.
+    ///
+    /// [1]: crate::Pool::wait_for_lsn
+    pub async fn current_wal_lsn(&mut self) -> Result<PgLsn> {
+        crate::query_scalar("SELECT pg_current_wal_lsn()", self).fetch_one().await
+    }
 }
 
 impl Connection {
@@ -161,6 +526,12 @@ impl Connection {
 }
 
 macro_rules! poll_message {
+    (@read $io:ident, $cx:ident) => {
+        // buffer is reserved, zero read means the server closed the connection
+        if ready!(crate::io::poll_read(&mut $io.socket, &mut $io.read_buf, $cx)?) == 0 {
+            return Poll::Ready(Err(io::Error::from(io::ErrorKind::UnexpectedEof).into()));
+        }
+    };
     (
         poll($io:ident, $cx:ident);
         let $msgtype:ident;
@@ -168,7 +539,7 @@ macro_rules! poll_message {
     ) => {
         let Some(mut header) = $io.read_buf.get(..5) else {
             $io.read_buf.reserve(1024);
-            ready!(crate::io::poll_read(&mut $io.socket, &mut $io.read_buf, $cx)?);
+            poll_message!(@read $io, $cx);
             continue;
         };
 
@@ -177,7 +548,7 @@ macro_rules! poll_message {
 
         if $io.read_buf.len() - 1/*msgtype*/ < len {
             $io.read_buf.reserve(1 + len);
-            ready!(crate::io::poll_read(&mut $io.socket, &mut $io.read_buf, $cx)?);
+            poll_message!(@read $io, $cx);
             continue;
         }
 
@@ -185,7 +556,7 @@ macro_rules! poll_message {
         let $body = $io.read_buf.split_to(len - 4).freeze();
 
         // Message fully acquired
-        verbose!("(B){:?}",backend::BackendMessage::decode($msgtype, $body.clone()).unwrap());
+        verbose!("(B){:?}",backend::BackendMessage::decode($msgtype, $body.clone()));
     };
 }
 
@@ -205,6 +576,12 @@ impl Connection {
     ///
     /// [1]: Connection#pending-messages
     pub(crate) fn poll_ready(&mut self, cx: &mut Context) -> Poll<Result<()>> {
+        if self.poisoned {
+            return Poll::Ready(Err(ConnectionPoisoned.into()));
+        }
+
+        self.resync_abandoned();
+
         if !self.write_buf.is_empty() {
             ready!(self.poll_flush(cx)?)
         }
@@ -219,23 +596,29 @@ impl Connection {
             }
 
             match msgtype {
-                ErrorResponse::MSGTYPE => {
+                // `ReadyForQuery` of the `Sync` in flight is already pending
+                ErrorResponse::MSGTYPE if self.sync_inflight == 0 => {
                     self.send(frontend::Sync);
-                    // NOTE:
-                    // not documented but the `Sync` will get
-                    // eaten by ErrorResponse based on currently happening
                     self.ready_request();
                     #[cfg(feature = "log")]
                     log::error!("{}",ErrorResponse::new(_body));
                 },
+                ErrorResponse::MSGTYPE => {
+                    #[cfg(feature = "log")]
+                    log::error!("{}",ErrorResponse::new(_body));
+                },
                 NoticeResponse::MSGTYPE => {
                     #[cfg(feature = "log")]
                     log::warn!("{}",NoticeResponse::new(_body));
                 },
                 backend::ParameterStatus::MSGTYPE => {
-                    // currently, we dont care about parameter status
+                    self.set_parameter(backend::ParameterStatus::decode(msgtype, _body)?);
+                }
+                backend::NotificationResponse::MSGTYPE => {
+                    self.notifications.push_back(backend::NotificationResponse::decode(msgtype, _body)?);
                 }
                 backend::ReadyForQuery::MSGTYPE => {
+                    self.ready_for_query(&_body);
                     self.sync_pending -= 1;
                 },
                 _ => {} // ignore all messages until `ReadyForQuery` received
@@ -244,6 +627,81 @@ impl Connection {
 
         Poll::Ready(Ok(()))
     }
+
+    /// Discard the remaining responses of an abandoned request, see [`PgTransport::abandoned`].
+    fn resync_abandoned(&mut self) {
+        if !self.abandoned.swap(false, Ordering::Acquire) {
+            return;
+        }
+        // the request did not send `Sync` yet, e.g: paged or waiting for `ParseComplete`
+        if self.sync_inflight <= self.sync_pending {
+            self.send(frontend::Sync);
+        }
+        self.ready_request();
+    }
+
+    /// A `Sync` or `Query` is completed.
+    fn ready_for_query(&mut self, body: &[u8]) {
+        self.sync_inflight = self.sync_inflight.saturating_sub(1);
+        if let Some(status) = body.first() {
+            self.tx_status = *status;
+        }
+        if let Some(threshold) = self.shrink_threshold && self.sync_inflight == 0 {
+            self.shrink_buffers_above(threshold);
+        }
+    }
+
+    /// Attempt to receive buffered or incoming notification.
+    pub(crate) fn poll_notification(&mut self, cx: &mut Context) -> Poll<Result<backend::NotificationResponse>> {
+        loop {
+            if let Some(notification) = self.notifications.pop_front() {
+                return Poll::Ready(Ok(notification));
+            }
+
+            ready!(self.poll_ready(cx)?);
+
+            poll_message! {
+                poll(self, cx);
+                let msgtype;
+                let body;
+            }
+
+            match msgtype {
+                ErrorResponse::MSGTYPE => Err(ErrorResponse::new(body))?,
+                NoticeResponse::MSGTYPE => {
+                    #[cfg(feature = "log")]
+                    log::warn!("{}",NoticeResponse::new(body));
+                },
+                backend::ParameterStatus::MSGTYPE => {
+                    self.set_parameter(backend::ParameterStatus::decode(msgtype, body)?);
+                }
+                backend::NotificationResponse::MSGTYPE => {
+                    return Poll::Ready(Ok(backend::NotificationResponse::decode(msgtype, body)?));
+                }
+                _ if self.skip_unknown && !backend::BackendMessage::is_known(msgtype) => {
+                    #[cfg(feature = "log")]
+                    log::warn!("skipping unknown backend message {:?}", msgtype as char);
+                }
+                _ => {
+                    self.poisoned = true;
+                    let err = backend::BackendMessage::decode(msgtype, body)?.unexpected("waiting notification");
+                    return Poll::Ready(Err(err.into()));
+                },
+            }
+        }
+    }
+
+    /// Write buffered messages without waiting for the socket.
+    ///
+    /// Remaining messages, including any io error, will be handled by the next flush.
+    ///
+    /// If the socket is not writable, the noop waker replaces the waker registered by
+    /// previous flush. This is fine as [`PgTransport::send`] requires a real flush
+    /// afterwards, which register its waker again.
+    fn try_flush(&mut self) {
+        let mut cx = Context::from_waker(Waker::noop());
+        let _ = crate::io::poll_write_all(&mut self.socket, &mut self.write_buf, &mut cx);
+    }
 }
 
 impl PgTransport for Connection {
@@ -261,9 +719,16 @@ impl PgTransport for Connection {
                 let body;
             }
 
+            if msgtype == backend::ReadyForQuery::MSGTYPE {
+                self.ready_for_query(&body);
+            }
+
             match msgtype {
                 ErrorResponse::MSGTYPE => {
-                    self.send(frontend::Sync);
+                    // server discards messages until `Sync`, which may already be sent
+                    if self.sync_inflight == 0 {
+                        self.send(frontend::Sync);
+                    }
                     self.ready_request();
                     Err(ErrorResponse::new(body))?
                 },
@@ -273,9 +738,23 @@ impl PgTransport for Connection {
                     continue;
                 },
                 backend::ParameterStatus::MSGTYPE => {
-                    // currently, we dont care about parameter status
+                    self.set_parameter(backend::ParameterStatus::decode(msgtype, body)?);
+                }
+                backend::NotificationResponse::MSGTYPE => {
+                    self.notifications.push_back(backend::NotificationResponse::decode(msgtype, body)?);
                 }
-                _ => return Poll::Ready(Ok(B::decode(msgtype, body)?)),
+                _ if self.skip_unknown && !backend::BackendMessage::is_known(msgtype) => {
+                    #[cfg(feature = "log")]
+                    log::warn!("skipping unknown backend message {:?}", msgtype as char);
+                    continue;
+                }
+                _ => match B::decode(msgtype, body) {
+                    Ok(ok) => return Poll::Ready(Ok(ok)),
+                    Err(err) => {
+                        self.poisoned = true;
+                        return Poll::Ready(Err(err.into()));
+                    },
+                },
             }
         }
     }
@@ -284,9 +763,21 @@ impl PgTransport for Connection {
         self.sync_pending += 1;
     }
 
+    fn poison(&mut self) {
+        self.poisoned = true;
+    }
+
     fn send<F: FrontendProtocol>(&mut self, message: F) {
+        self.resync_abandoned();
         verbose!(?message,"(F)");
+        if matches!(F::MSGTYPE, frontend::Sync::MSGTYPE | frontend::Query::MSGTYPE) {
+            self.sync_inflight += 1;
+        }
         frontend::write(message, &mut self.write_buf);
+
+        if self.write_buf.len() >= self.write_watermark {
+            self.try_flush();
+        }
     }
 
     fn send_startup(&mut self, startup: frontend::Startup) {
@@ -295,7 +786,7 @@ impl PgTransport for Connection {
     }
 
     fn get_stmt(&mut self, sqlid: u64) -> Option<StatementName> {
-        self.stmts.get(&sqlid).cloned().inspect(|_name|{
+        self.stmts.as_mut()?.get(&sqlid).cloned().inspect(|_name|{
             span!("statement");
             verbose!(name=%_name,"cache hit")
         })
@@ -304,9 +795,18 @@ impl PgTransport for Connection {
     fn add_stmt(&mut self, id: u64, name: StatementName) {
         span!("statement");
 
+        // statement is never named if caching is disabled
+        let Some(stmts) = self.stmts.as_mut() else {
+            return;
+        };
+        // one-off statement
+        if name.is_unnamed() {
+            return;
+        }
+
         verbose!(%name,"added");
 
-        if let Some((_id,name)) = self.stmts.push(id, name) {
+        if let Some((_id,name)) = stmts.push(id, name) {
             verbose!(%name,"removed");
 
             self.send(frontend::Close {
@@ -317,6 +817,47 @@ impl PgTransport for Connection {
             self.ready_request();
         }
     }
+
+    fn remove_stmt(&mut self, id: u64) {
+        span!("statement");
+
+        let Some(name) = self.stmts.as_mut().and_then(|stmts| stmts.pop(&id)) else {
+            return;
+        };
+
+        verbose!(%name,"removed");
+
+        self.send(frontend::Close {
+            variant: b'S',
+            name: name.as_str(),
+        });
+        self.send(frontend::Sync);
+        self.ready_request();
+    }
+
+    fn in_transaction(&self) -> bool {
+        self.tx_status != b'I'
+    }
+
+    fn stmt_cache_enabled(&self) -> bool {
+        self.stmts.is_some()
+    }
+
+    fn normalize_statements(&self) -> bool {
+        self.normalize
+    }
+
+    fn codecs(&self) -> Option<Arc<Codecs>> {
+        (!self.codecs.is_empty()).then(|| self.codecs.clone())
+    }
+
+    fn cancel_handle(&self) -> Option<CancelHandle> {
+        Some(Connection::cancel_handle(self))
+    }
+
+    fn abandoned(&self) -> Option<Arc<AtomicBool>> {
+        Some(self.abandoned.clone())
+    }
 }
 
 impl Executor for Connection {
@@ -329,3 +870,176 @@ impl Executor for Connection {
     }
 }
 
+
+/// Returns early with [`UnsupportedVersion`] error if the server major version is lower than required.
+///
+/// # Example
+///
+/// ```no_run
+/// # async fn test(mut conn: postro::Connection) -> postro::Result<()> {
+/// postro::require_version!(conn, 15, "MERGE support");
+///
+/// postro::query("MERGE INTO post USING ...", &mut conn).await?;
+/// # Ok(())
+/// # }
+/// ```
+#[macro_export]
+macro_rules! require_version {
+    ($conn:expr, $major:expr, $feature:expr $(,)?) => {
+        if let Err(err) = $conn.require_version($major, $feature) {
+            return Err(err.into());
+        }
+    };
+}
+
+unit_error! {
+    /// An error when using a connection that previously received unexpected message.
+    ///
+    /// The message framing state is unknown, a new connection should be used instead.
+    pub struct ConnectionPoisoned("connection is poisoned by previous protocol error");
+}
+
+unit_error! {
+    /// An error when [`Config::ssl_mode`] requires encrypted connection, which is not yet supported.
+    pub struct TlsNotSupported("TLS is not supported, sslmode requires encrypted connection");
+}
+
+fn check_encoding(client: Option<&str>, server: Option<&str>) -> Result<(), UnsupportedEncoding> {
+    let client = client.unwrap_or_default();
+    if !client.eq_ignore_ascii_case("UTF8") {
+        return Err(UnsupportedEncoding { parameter: "client_encoding", value: client.into() });
+    }
+    // with `SQL_ASCII`, server does not perform encoding conversion
+    if let Some(server) = server.filter(|e|e.eq_ignore_ascii_case("SQL_ASCII")) {
+        return Err(UnsupportedEncoding { parameter: "server_encoding", value: server.into() });
+    }
+    Ok(())
+}
+
+/// An error when the server report encoding that may produce non UTF-8 text.
+///
+/// See [`Config::strict_encoding`].
+pub struct UnsupportedEncoding {
+    parameter: &'static str,
+    value: String,
+}
+
+impl UnsupportedEncoding {
+    /// Returns the reported parameter name, `client_encoding` or `server_encoding`.
+    pub fn parameter(&self) -> &'static str {
+        self.parameter
+    }
+
+    /// Returns the reported encoding.
+    pub fn value(&self) -> &str {
+        &self.value
+    }
+}
+
+impl std::error::Error for UnsupportedEncoding { }
+
+impl std::fmt::Display for UnsupportedEncoding {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "unsupported {} {:?}, only UTF8 is supported", self.parameter, self.value)
+    }
+}
+
+impl std::fmt::Debug for UnsupportedEncoding {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "\"{self}\"")
+    }
+}
+
+/// An error when the server does not match [`Config::target_session_attrs`].
+pub struct SessionAttrsMismatch(TargetSessionAttrs);
+
+impl SessionAttrsMismatch {
+    /// Returns the required session attributes.
+    pub fn target(&self) -> TargetSessionAttrs {
+        self.0
+    }
+}
+
+impl std::error::Error for SessionAttrsMismatch { }
+
+impl std::fmt::Display for SessionAttrsMismatch {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "server does not match target_session_attrs {:?}", self.0.as_str())
+    }
+}
+
+impl std::fmt::Debug for SessionAttrsMismatch {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "\"{self}\"")
+    }
+}
+
+/// An error when a feature is not supported by the server version.
+pub struct UnsupportedVersion {
+    feature: &'static str,
+    required: u32,
+    version: u32,
+}
+
+impl UnsupportedVersion {
+    /// Returns the feature name.
+    pub fn feature(&self) -> &'static str {
+        self.feature
+    }
+
+    /// Returns the required major version.
+    pub fn required(&self) -> u32 {
+        self.required
+    }
+
+    /// Returns the server version, see [`Connection::server_version_num`].
+    pub fn version(&self) -> u32 {
+        self.version
+    }
+}
+
+impl std::error::Error for UnsupportedVersion { }
+
+impl std::fmt::Display for UnsupportedVersion {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "{} requires postgres {} or later, server version is {}",
+            self.feature, self.required, self.version,
+        )
+    }
+}
+
+impl std::fmt::Debug for UnsupportedVersion {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "\"{self}\"")
+    }
+}
+
+#[cfg(test)]
+mod test {
+    #[test]
+    fn check_encoding() {
+        use super::check_encoding;
+
+        assert!(check_encoding(Some("UTF8"), Some("UTF8")).is_ok());
+        assert!(check_encoding(Some("UTF8"), Some("LATIN1")).is_ok());
+        assert!(check_encoding(Some("UTF8"), None).is_ok());
+
+        let err = check_encoding(Some("LATIN1"), Some("UTF8")).unwrap_err();
+        assert_eq!(err.parameter(), "client_encoding");
+        assert_eq!(err.value(), "LATIN1");
+        assert!(check_encoding(None, Some("UTF8")).is_err());
+
+        let err = check_encoding(Some("UTF8"), Some("SQL_ASCII")).unwrap_err();
+        assert_eq!(err.parameter(), "server_encoding");
+    }
+    #[test]
+    fn known_message() {
+        use crate::postgres::BackendMessage;
+
+        assert!(BackendMessage::is_known(b'Z'));
+        assert!(BackendMessage::is_known(b'N'));
+        assert!(!BackendMessage::is_known(b'?'));
+    }
+}
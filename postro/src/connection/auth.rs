@@ -0,0 +1,100 @@
+//! Pluggable authentication, see [`Config::auth_handler`][super::Config::auth_handler].
+use std::{fmt, sync::Arc};
+
+use crate::postgres::backend::Authentication;
+
+/// Authentication method that is not built into `postro`, e.g: GSSAPI or SSPI.
+///
+/// Clear-text and MD5 password are handled by `postro`, every other request of the
+/// server is passed to the handler. A handler is created for each connection, so it
+/// can hold the security context across multiple exchanges.
+///
+/// ```no_run
+/// use postro::{Config, connection::{AuthError, AuthHandler}, postgres::backend::Authentication};
+///
+/// /// Kerberos via GSSAPI, e.g: using `libgssapi` crate.
+/// struct Kerberos {
+///     service: String,
+/// }
+///
+/// impl AuthHandler for Kerberos {
+///     fn respond(&mut self, request: &Authentication) -> Result<Option<Vec<u8>>, AuthError> {
+///         match request {
+///             Authentication::GSS => gss_step(&self.service, None),
+///             Authentication::GSSContinue { data } => gss_step(&self.service, Some(data)),
+///             _ => Err(AuthError::new("kerberos only")),
+///         }
+///     }
+/// }
+///
+/// /// Initiate or continue the security context, returns the output token if any.
+/// fn gss_step(service: &str, input: Option<&[u8]>) -> Result<Option<Vec<u8>>, AuthError> {
+///     # todo!()
+/// }
+///
+/// let config = Config::from_env()
+///     .auth_handler(|host| Kerberos { service: format!("postgres@{host}") });
+/// ```
+pub trait AuthHandler: Send + Sync {
+    /// Returns the response to authentication `request`, or `None` if no response is
+    /// needed, e.g: the final GSSAPI step without output token.
+    ///
+    /// The response is sent as is as `GSSResponse` message body. `SASLInitialResponse`
+    /// and `SASLResponse` share the same message type, so SASL mechanism can also be
+    /// implemented by encoding the respective message body.
+    fn respond(&mut self, request: &Authentication) -> Result<Option<Vec<u8>>, AuthError>;
+}
+
+type Factory = dyn Fn(&str) -> Box<dyn AuthHandler> + Send + Sync;
+
+/// Creates [`AuthHandler`] with the host of each connection.
+#[derive(Clone)]
+pub(crate) struct AuthFactory(Arc<Factory>);
+
+impl AuthFactory {
+    pub(crate) fn new<H, F>(f: F) -> Self
+    where
+        H: AuthHandler + 'static,
+        F: Fn(&str) -> H + Send + Sync + 'static,
+    {
+        Self(Arc::new(move |host| Box::new(f(host))))
+    }
+
+    pub(crate) fn create(&self, host: &str) -> Box<dyn AuthHandler> {
+        (self.0)(host)
+    }
+}
+
+impl fmt::Debug for AuthFactory {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str("AuthHandler")
+    }
+}
+
+/// An error returned by [`AuthHandler`].
+pub struct AuthError(Box<dyn std::error::Error + Send + Sync>);
+
+impl AuthError {
+    /// Create new error from the underlying mechanism error or message.
+    pub fn new(err: impl Into<Box<dyn std::error::Error + Send + Sync>>) -> Self {
+        Self(err.into())
+    }
+}
+
+impl std::error::Error for AuthError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        Some(&*self.0)
+    }
+}
+
+impl fmt::Display for AuthError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "authentication failed: {}", self.0)
+    }
+}
+
+impl fmt::Debug for AuthError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "\"{self}\"")
+    }
+}
@@ -0,0 +1,47 @@
+//! Cancel in-progress query.
+use bytes::BytesMut;
+
+use super::connect_socket;
+use crate::{
+    Result,
+    common::ByteStr,
+    postgres::{backend::BackendKeyData, frontend},
+};
+
+/// Handle to request cancellation of a query currently running in a [`Connection`][1].
+///
+/// The handle can be sent to other task, and stay valid for the lifetime of the connection.
+///
+/// [1]: super::Connection
+#[derive(Debug, Clone)]
+pub struct CancelHandle {
+    pub(crate) host: ByteStr,
+    pub(crate) port: u16,
+    pub(crate) key: BackendKeyData,
+}
+
+impl CancelHandle {
+    /// Request the server to cancel the query currently running.
+    ///
+    /// Cancellation is best effort, the server may have finished the query. If the query is
+    /// cancelled, it returns database error with `query_canceled` code (`57014`). If there
+    /// is no query running, nothing happens.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `tokio` feature is not enabled.
+    pub async fn cancel(&self) -> Result<()> {
+        let mut socket = connect_socket(&self.host, self.port).await?;
+
+        let mut buf = BytesMut::with_capacity(16);
+        frontend::CancelRequest {
+            process_id: self.key.process_id,
+            secret_key: self.key.secret_key,
+        }
+        .write(&mut buf);
+
+        std::future::poll_fn(|cx| crate::io::poll_write_all(&mut socket, &mut buf, cx)).await?;
+        socket.shutdown().await?;
+        Ok(())
+    }
+}
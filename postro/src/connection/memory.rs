@@ -0,0 +1,103 @@
+//! Connection memory accounting.
+use bytes::BytesMut;
+use std::ops::{Add, AddAssign};
+
+use super::{Connection, DEFAULT_BUF_CAPACITY};
+use crate::statement::StatementName;
+
+/// Estimated per entry overhead of statement cache, key, value, and the lru links.
+const STMT_ENTRY_SIZE: usize = size_of::<(u64, StatementName)>() + 3 * size_of::<usize>();
+
+/// Memory held by a connection, in bytes.
+///
+/// Buffer sizes are the allocated capacity, while the statement cache size is estimated.
+/// Memory held by the server for cached statements is not included.
+///
+/// Can be added together to aggregate multiple connections, see [`Pool::memory_usage`][1].
+///
+/// [1]: crate::Pool::memory_usage
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct MemoryUsage {
+    /// Capacity of the read buffer.
+    pub read_buffer: usize,
+    /// Capacity of the write buffer.
+    pub write_buffer: usize,
+    /// Estimated size of the statement cache.
+    pub statement_cache: usize,
+    /// Size of buffered notifications and reported parameters.
+    pub messages: usize,
+}
+
+impl MemoryUsage {
+    /// Returns the sum of all memory.
+    pub fn total(&self) -> usize {
+        self.read_buffer + self.write_buffer + self.statement_cache + self.messages
+    }
+}
+
+impl Add for MemoryUsage {
+    type Output = Self;
+
+    fn add(mut self, rhs: Self) -> Self::Output {
+        self += rhs;
+        self
+    }
+}
+
+impl AddAssign for MemoryUsage {
+    fn add_assign(&mut self, rhs: Self) {
+        self.read_buffer += rhs.read_buffer;
+        self.write_buffer += rhs.write_buffer;
+        self.statement_cache += rhs.statement_cache;
+        self.messages += rhs.messages;
+    }
+}
+
+impl std::iter::Sum for MemoryUsage {
+    fn sum<I: Iterator<Item = Self>>(iter: I) -> Self {
+        iter.fold(Self::default(), Add::add)
+    }
+}
+
+impl Connection {
+    /// Returns the memory held by this connection.
+    pub fn memory_usage(&self) -> MemoryUsage {
+        let parameters = self
+            .parameters
+            .iter()
+            .map(|e| e.name.len() + e.value.len())
+            .sum::<usize>();
+        let notifications = self
+            .notifications
+            .iter()
+            .map(|e| e.channel.len() + e.payload.len())
+            .sum::<usize>();
+
+        MemoryUsage {
+            read_buffer: self.read_buf.capacity(),
+            write_buffer: self.write_buf.capacity(),
+            statement_cache: self.stmts.as_ref().map_or(0, |e| e.len() * STMT_ENTRY_SIZE),
+            messages: parameters + notifications,
+        }
+    }
+
+    /// Release buffer capacity that grew beyond the initial capacity, e.g: after
+    /// receiving a large result set.
+    ///
+    /// Only empty buffers are shrunk. To shrink automatically, see
+    /// [`Config::buffer_shrink_threshold`][1].
+    ///
+    /// [1]: super::Config::buffer_shrink_threshold
+    pub fn shrink_buffers(&mut self) {
+        self.shrink_buffers_above(DEFAULT_BUF_CAPACITY);
+    }
+
+    /// Shrink empty buffers with capacity larger than `threshold`.
+    pub(super) fn shrink_buffers_above(&mut self, threshold: usize) {
+        for buf in [&mut self.read_buf, &mut self.write_buf] {
+            if buf.is_empty() && buf.capacity() > threshold {
+                *buf = BytesMut::with_capacity(DEFAULT_BUF_CAPACITY);
+            }
+        }
+    }
+}
@@ -0,0 +1,72 @@
+//! Asynchronous notification via `LISTEN` and `NOTIFY`.
+use futures_core::Stream;
+use std::{
+    pin::Pin,
+    task::{Context, Poll},
+};
+
+use super::Connection;
+use crate::{
+    Result,
+    common::quote_ident,
+    postgres::backend,
+};
+
+impl Connection {
+    /// Listen to notification on `channel`, returns [`Stream`] of notifications.
+    ///
+    /// Notifications that arrive while executing other queries are buffered,
+    /// use [`Connection::notifications`] to continue receiving them.
+    ///
+    /// # Example
+    ///
+    /// ```no_run
+    /// # async fn test(mut conn: postro::Connection) -> postro::Result<()> {
+    /// use futures::StreamExt;
+    ///
+    /// let mut notifications = conn.listen("post_created").await?;
+    ///
+    /// while let Some(notification) = notifications.next().await {
+    ///     println!("{}", notification?.payload);
+    /// }
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub async fn listen(&mut self, channel: &str) -> Result<Notifications<'_>> {
+        self.simple_query(&format!("LISTEN {}", quote_ident(channel))).await?;
+        Ok(self.notifications())
+    }
+
+    /// Stop listening to notification on `channel`.
+    pub async fn unlisten(&mut self, channel: &str) -> Result<()> {
+        self.simple_query(&format!("UNLISTEN {}", quote_ident(channel))).await
+    }
+
+    /// Returns [`Stream`] of notifications from all listened channel.
+    pub fn notifications(&mut self) -> Notifications<'_> {
+        Notifications { conn: self }
+    }
+
+    async fn simple_query(&mut self, sql: &str) -> Result<()> {
+        crate::raw_sql(sql, self).await?;
+        Ok(())
+    }
+}
+
+/// [`Stream`] of notifications returned from [`Connection::listen`].
+///
+/// The stream never ends, it only returns error on io error or when server
+/// terminate the connection.
+#[derive(Debug)]
+#[must_use = "streams do nothing unless polled"]
+pub struct Notifications<'a> {
+    conn: &'a mut Connection,
+}
+
+impl Stream for Notifications<'_> {
+    type Item = Result<backend::NotificationResponse>;
+
+    fn poll_next(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Option<Self::Item>> {
+        self.conn.poll_notification(cx).map(Some)
+    }
+}
@@ -0,0 +1,116 @@
+//! Password file lookup, see [`Config::passfile`][super::Config::passfile].
+use std::path::{Path, PathBuf};
+
+/// Returns `~/.pgpass`, or `%APPDATA%\postgresql\pgpass.conf` on windows.
+pub(super) fn default_path() -> Option<PathBuf> {
+    if cfg!(windows) {
+        let appdata = std::env::var_os("APPDATA")?;
+        Some(PathBuf::from(appdata).join("postgresql").join("pgpass.conf"))
+    } else {
+        let home = std::env::var_os("HOME")?;
+        Some(PathBuf::from(home).join(".pgpass"))
+    }
+}
+
+/// Lookup password for the first matching entry in password file.
+///
+/// Missing file, or file that is accessible by group or others, is ignored.
+pub(super) fn lookup(path: &Path, host: &str, port: u16, dbname: &str, user: &str) -> Option<String> {
+    #[cfg(unix)]
+    {
+        use std::os::unix::fs::PermissionsExt;
+
+        let mode = std::fs::metadata(path).ok()?.permissions().mode();
+        if mode & 0o077 != 0 {
+            #[cfg(feature = "log")]
+            log::warn!("password file {path:?} has group or world access, permissions should be u=rw (0600) or less");
+            return None;
+        }
+    }
+
+    let content = std::fs::read_to_string(path).ok()?;
+    find(&content, host, &port.to_string(), dbname, user)
+}
+
+fn find(content: &str, host: &str, port: &str, dbname: &str, user: &str) -> Option<String> {
+    content
+        .lines()
+        .filter(|line| !line.trim_start().starts_with('#'))
+        .find_map(|line| {
+            let mut fields = Fields { line };
+            let matches = |field: Option<String>, value: &str| {
+                field.is_some_and(|e| e == "*" || e == value)
+            };
+
+            let host_field = fields.next();
+            // unix socket in any directory is matched by `localhost`
+            let host_matches = match host.starts_with('/') {
+                true => host_field.as_deref().is_some_and(|e| matches!(e, "*" | "localhost") || e == host),
+                false => matches(host_field, host),
+            };
+
+            (host_matches
+                && matches(fields.next(), port)
+                && matches(fields.next(), dbname)
+                && matches(fields.next(), user))
+            .then(|| fields.rest())
+        })
+}
+
+/// Colon separated fields, where `\` escape the next character.
+struct Fields<'a> {
+    line: &'a str,
+}
+
+impl Fields<'_> {
+    fn next(&mut self) -> Option<String> {
+        let mut field = String::new();
+        let mut chars = self.line.char_indices();
+
+        while let Some((i, ch)) = chars.next() {
+            match ch {
+                ':' => {
+                    self.line = &self.line[i + 1..];
+                    return Some(field);
+                }
+                '\\' => field.extend(chars.next().map(|e| e.1)),
+                ch => field.push(ch),
+            }
+        }
+
+        // not enough fields
+        None
+    }
+
+    fn rest(self) -> String {
+        let mut field = String::new();
+        let mut chars = self.line.chars();
+        while let Some(ch) = chars.next() {
+            match ch {
+                '\\' => field.extend(chars.next()),
+                ch => field.push(ch),
+            }
+        }
+        field
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::find;
+
+    #[test]
+    fn find_password() {
+        let content = "\
+# comment
+db.local:5432:app:alice:secret
+*:*:*:bob:pa\\:ss\\\\word
+localhost:5433:*:*:socket
+";
+        assert_eq!(find(content, "db.local", "5432", "app", "alice").as_deref(), Some("secret"));
+        assert_eq!(find(content, "db.local", "5432", "other", "alice"), None);
+        assert_eq!(find(content, "any", "1", "any", "bob").as_deref(), Some("pa:ss\\word"));
+        assert_eq!(find(content, "/var/run/postgresql", "5433", "app", "carol").as_deref(), Some("socket"));
+        assert_eq!(find("incomplete:line", "incomplete", "5432", "db", "user"), None);
+    }
+}
@@ -0,0 +1,321 @@
+//! Bulk loading and exporting via `COPY FROM STDIN` and `COPY TO STDOUT`.
+use bytes::Bytes;
+use futures_core::Stream;
+use std::{
+    pin::Pin,
+    task::{Context, Poll, ready},
+};
+
+use crate::{
+    Result,
+    executor::Executor,
+    fetch::{check_message_len, command_complete},
+    postgres::{BackendMessage, backend, frontend},
+    query::MAX_MESSAGE_LEN,
+    transport::{PgTransport, PgTransportExt},
+};
+
+/// Default bytes sent between progress report, 1 MiB.
+const DEFAULT_PROGRESS_INTERVAL: u64 = 1024 * 1024;
+
+/// Start `COPY FROM STDIN` statement, returns [`CopyIn`] to stream the data.
+///
+/// ```no_run
+/// # async fn test(mut conn: postro::Connection) -> postro::Result<()> {
+/// let mut copy = postro::copy::copy_in("COPY post(id, title) FROM STDIN WITH (FORMAT csv)", &mut conn)
+///     .await?
+///     .on_progress(|bytes, rows| eprintln!("sent {bytes} bytes, {rows} rows"));
+///
+/// copy.send("1,first\n2,second\n").await?;
+/// let rows = copy.finish().await?;
+/// assert_eq!(rows, 2);
+/// # Ok(())
+/// # }
+/// ```
+pub async fn copy_in<Exe: Executor>(sql: &str, exe: Exe) -> Result<CopyIn<Exe::Transport>> {
+    check_message_len(sql.len(), [].into_iter())?;
+
+    let commented = crate::sql::comment(sql);
+    let sql = commented.as_ref().map_or(sql, |e|e.0.as_str());
+
+    let mut io = exe.connection().await?;
+    io.send(frontend::Query { sql });
+    io.flush().await?;
+
+    let response = match io.recv().await.map_err(|err| err.with_sql(sql))? {
+        BackendMessage::CopyInResponse(response) => response,
+        f => {
+            io.poison();
+            Err(f.unexpected("copy in"))?
+        },
+    };
+
+    Ok(CopyIn {
+        io,
+        binary: response.format == 1,
+        bytes: 0,
+        rows: 0,
+        progress: None,
+        progress_interval: DEFAULT_PROGRESS_INTERVAL,
+        reported_at: 0,
+        done: false,
+    })
+}
+
+/// Data stream of `COPY FROM STDIN`, created via [`copy_in`].
+///
+/// The statement is completed via [`finish`][CopyIn::finish]. If dropped before
+/// finished, the copy is aborted when the connection is used next.
+pub struct CopyIn<IO: PgTransport> {
+    io: IO,
+    binary: bool,
+    bytes: u64,
+    rows: u64,
+    progress: Option<Box<dyn FnMut(u64, u64) + Send>>,
+    progress_interval: u64,
+    /// bytes sent at the last progress report
+    reported_at: u64,
+    done: bool,
+}
+
+impl<IO: PgTransport> CopyIn<IO> {
+    /// Set the callback to report progress, called with total bytes and rows sent.
+    ///
+    /// The callback is called when at least [`progress_interval`][CopyIn::progress_interval]
+    /// bytes are sent since the last report, and once when finished with the rows copied
+    /// by the server.
+    ///
+    /// Rows are counted by newline in text and csv format, which over count quoted csv
+    /// value that contains newline. In binary format, rows is only reported when finished.
+    pub fn on_progress(mut self, f: impl FnMut(u64, u64) + Send + 'static) -> Self {
+        self.progress = Some(Box::new(f));
+        self
+    }
+
+    /// Set the minimum bytes sent between progress report, default to 1 MiB.
+    pub fn progress_interval(mut self, bytes: u64) -> Self {
+        self.progress_interval = bytes;
+        self
+    }
+
+    /// Returns `true` if the copy is in binary format.
+    pub fn is_binary(&self) -> bool {
+        self.binary
+    }
+
+    /// Returns the total bytes sent.
+    pub fn bytes_sent(&self) -> u64 {
+        self.bytes
+    }
+
+    /// Send data to the server.
+    ///
+    /// Data can be divided arbitrarily, e.g: not aligned with rows. Each call flushes
+    /// the data to the socket, so prefer to send data in large chunks.
+    ///
+    /// Error in the data is only reported when [`finish`][CopyIn::finish]ed.
+    pub async fn send(&mut self, data: impl AsRef<[u8]>) -> Result<()> {
+        let data = data.as_ref();
+
+        for chunk in data.chunks(MAX_MESSAGE_LEN - 4) {
+            self.io.send(frontend::CopyData { data: chunk });
+            self.io.flush().await?;
+        }
+
+        self.bytes += data.len() as u64;
+        if !self.binary {
+            self.rows += data.iter().filter(|e| **e == b'\n').count() as u64;
+        }
+
+        if self.bytes - self.reported_at >= self.progress_interval
+            && let Some(progress) = self.progress.as_mut()
+        {
+            self.reported_at = self.bytes;
+            progress(self.bytes, self.rows);
+        }
+
+        Ok(())
+    }
+
+    /// Complete the copy, returns the number of rows copied.
+    pub async fn finish(mut self) -> Result<u64> {
+        self.done = true;
+        self.io.send(frontend::CopyDone);
+        self.io.flush().await?;
+
+        let cmd = self.io.recv::<backend::CommandComplete>().await?;
+        self.io.recv::<backend::ReadyForQuery>().await?;
+
+        let rows = command_complete(&cmd);
+        if let Some(progress) = self.progress.as_mut() {
+            progress(self.bytes, rows);
+        }
+
+        Ok(rows)
+    }
+
+    /// Abort the copy with error `message`, no rows are copied.
+    pub async fn abort(mut self, message: &str) -> Result<()> {
+        self.done = true;
+        self.io.send(frontend::CopyFail { message });
+        self.io.flush().await?;
+
+        // server responds with `ErrorResponse` reporting the `message`
+        match self.io.recv::<backend::ReadyForQuery>().await {
+            Ok(_) => Ok(()),
+            Err(err) if matches!(err.kind(), crate::error::ErrorKind::Database(_)) => Ok(()),
+            Err(err) => Err(err),
+        }
+    }
+}
+
+impl<IO: PgTransport> Drop for CopyIn<IO> {
+    fn drop(&mut self) {
+        if !self.done {
+            self.io.send(frontend::CopyFail { message: "COPY dropped before finished" });
+            self.io.ready_request();
+        }
+    }
+}
+
+impl<IO: PgTransport> std::fmt::Debug for CopyIn<IO> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("CopyIn")
+            .field("binary", &self.binary)
+            .field("bytes", &self.bytes)
+            .field("rows", &self.rows)
+            .finish_non_exhaustive()
+    }
+}
+
+/// Start `COPY TO STDOUT` statement, returns [`CopyOut`] to stream the data.
+///
+/// Exporting via `COPY` is considerably faster than `SELECT`, especially in binary
+/// format, where values are sent as is without text conversion.
+///
+/// ```no_run
+/// # async fn test(mut conn: postro::Connection) -> postro::Result<()> {
+/// use futures::TryStreamExt;
+///
+/// let mut copy = postro::copy::copy_out("COPY post TO STDOUT WITH (FORMAT binary)", &mut conn).await?;
+/// let mut file = Vec::new();
+///
+/// while let Some(data) = copy.try_next().await? {
+///     file.extend_from_slice(&data);
+/// }
+/// eprintln!("exported {:?} rows", copy.rows());
+/// # Ok(())
+/// # }
+/// ```
+pub async fn copy_out<Exe: Executor>(sql: &str, exe: Exe) -> Result<CopyOut<Exe::Transport>> {
+    check_message_len(sql.len(), [].into_iter())?;
+
+    let commented = crate::sql::comment(sql);
+    let sql = commented.as_ref().map_or(sql, |e|e.0.as_str());
+
+    let mut io = exe.connection().await?;
+    io.send(frontend::Query { sql });
+    io.flush().await?;
+
+    let response = match io.recv().await.map_err(|err| err.with_sql(sql))? {
+        BackendMessage::CopyOutResponse(response) => response,
+        f => {
+            io.poison();
+            Err(f.unexpected("copy out"))?
+        },
+    };
+
+    Ok(CopyOut {
+        io,
+        binary: response.format == 1,
+        bytes: 0,
+        rows: None,
+        done: false,
+    })
+}
+
+/// Data stream of `COPY TO STDOUT`, created via [`copy_out`].
+///
+/// Each item is one `CopyData` message, which in text and csv format is exactly one
+/// row. In binary format, the file header is prepended to the first row, and the file
+/// trailer is sent as the last item.
+///
+/// The stream must be polled until completion. If dropped before completed, the
+/// remaining data is discarded when the connection is used next.
+pub struct CopyOut<IO: PgTransport> {
+    io: IO,
+    binary: bool,
+    bytes: u64,
+    rows: Option<u64>,
+    done: bool,
+}
+
+impl<IO: PgTransport> CopyOut<IO> {
+    /// Returns `true` if the copy is in binary format.
+    pub fn is_binary(&self) -> bool {
+        self.binary
+    }
+
+    /// Returns the total bytes received.
+    pub fn bytes_received(&self) -> u64 {
+        self.bytes
+    }
+
+    /// Returns the number of rows copied, only available once the stream is completed.
+    pub fn rows(&self) -> Option<u64> {
+        self.rows
+    }
+}
+
+impl<IO: PgTransport + Unpin> Stream for CopyOut<IO> {
+    type Item = Result<Bytes>;
+
+    fn poll_next(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Option<Self::Item>> {
+        let me = self.get_mut();
+
+        while !me.done {
+            let message = match ready!(me.io.poll_recv(cx)) {
+                Ok(ok) => ok,
+                Err(err) => {
+                    // the connection already handle the error
+                    me.done = true;
+                    return Poll::Ready(Some(Err(err)));
+                },
+            };
+            match message {
+                BackendMessage::CopyData(copy) => {
+                    me.bytes += copy.data.len() as u64;
+                    return Poll::Ready(Some(Ok(copy.data)));
+                },
+                BackendMessage::CopyDone(_) => { },
+                BackendMessage::CommandComplete(cmd) => me.rows = Some(command_complete(&cmd)),
+                BackendMessage::ReadyForQuery(_) => me.done = true,
+                f => {
+                    me.io.poison();
+                    me.done = true;
+                    return Poll::Ready(Some(Err(f.unexpected("copy out").into())));
+                },
+            }
+        }
+
+        Poll::Ready(None)
+    }
+}
+
+impl<IO: PgTransport> Drop for CopyOut<IO> {
+    fn drop(&mut self) {
+        if !self.done {
+            self.io.ready_request();
+        }
+    }
+}
+
+impl<IO: PgTransport> std::fmt::Debug for CopyOut<IO> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("CopyOut")
+            .field("binary", &self.binary)
+            .field("bytes", &self.bytes)
+            .field("rows", &self.rows)
+            .finish_non_exhaustive()
+    }
+}
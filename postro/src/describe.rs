@@ -0,0 +1,178 @@
+//! Statement description without execution.
+use bytes::{Buf, Bytes};
+
+use crate::{
+    Result,
+    common::ByteStr,
+    executor::Executor,
+    ext::BytesExt,
+    postgres::{BackendMessage, Oid, backend, frontend},
+    sql::Sql,
+    transport::{PgTransport, PgTransportExt},
+};
+
+/// Describe the statement without executing it.
+///
+/// This sends `Parse` and `Describe` for the unnamed statement, so the
+/// statement cache is not affected.
+///
+/// Column nullability is resolved on best-effort basis by looking up `pg_attribute`
+/// for columns that can be identified as a column of a table, see [`ColumnInfo::nullable`].
+pub async fn describe<SQL: Sql, Exe: Executor>(sql: SQL, exe: Exe) -> Result<StatementInfo> {
+    let mut io = exe.connection().await?;
+    let sql = sql.sql().trim();
+    let mut info = describe_statement(sql, &mut io)
+        .await
+        .map_err(|err| err.with_sql(sql))?;
+
+    let mut tables = info
+        .columns
+        .iter()
+        .map(|col| col.table_oid)
+        .filter(|oid| *oid != 0)
+        .collect::<Vec<_>>();
+    tables.sort_unstable();
+    tables.dedup();
+
+    for table in tables {
+        let not_nulls = crate::query_scalar::<_, _, i32>(
+            "SELECT attnum::int4 FROM pg_attribute WHERE attrelid = $1::oid AND attnotnull AND attnum > 0",
+            &mut io,
+        )
+        .bind(table as i32)
+        .fetch_all()
+        .await?;
+
+        for col in info.columns.iter_mut().filter(|col| col.table_oid == table) {
+            col.nullable = Some(!not_nulls.contains(&(col.column_id as i32)));
+        }
+    }
+
+    Ok(info)
+}
+
+/// Perform `Parse` and `Describe` of the unnamed statement.
+pub(crate) async fn describe_statement<IO: PgTransport>(sql: &str, mut io: IO) -> Result<StatementInfo> {
+    crate::fetch::check_message_len(sql.len(), [].into_iter())?;
+
+    io.send(frontend::Parse {
+        prepare_name: "",
+        sql,
+        oids_len: 0,
+        oids: [],
+    });
+    io.send(frontend::Describe {
+        kind: b'S',
+        name: "",
+    });
+    io.send(frontend::Flush);
+    io.flush().await?;
+
+    io.recv::<backend::ParseComplete>().await?;
+
+    let mut params = io.recv::<backend::ParameterDescription>().await?;
+    let params = (0..params.param_len)
+        .map(|_| params.oids.get_u32())
+        .collect();
+
+    let columns = match io.recv().await? {
+        BackendMessage::RowDescription(rd) => ColumnInfo::from_row_description(rd.body)?,
+        BackendMessage::NoData(_) => Vec::new(),
+        f => {
+            io.poison();
+            Err(f.unexpected("statement description"))?
+        },
+    };
+
+    io.send(frontend::Sync);
+    io.flush().await?;
+    io.recv::<backend::ReadyForQuery>().await?;
+
+    Ok(StatementInfo { params, columns })
+}
+
+/// Prepared statement metadata.
+#[derive(Debug, Clone)]
+pub struct StatementInfo {
+    params: Vec<Oid>,
+    columns: Vec<ColumnInfo>,
+}
+
+impl StatementInfo {
+    /// Returns the parameters data type [`Oid`].
+    pub fn params(&self) -> &[Oid] {
+        &self.params
+    }
+
+    /// Returns the result columns description.
+    ///
+    /// Empty if statement does not return any rows.
+    pub fn columns(&self) -> &[ColumnInfo] {
+        &self.columns
+    }
+}
+
+/// Result column metadata.
+#[derive(Debug, Clone)]
+pub struct ColumnInfo {
+    name: ByteStr,
+    table_oid: Oid,
+    column_id: i16,
+    oid: Oid,
+    nullable: Option<bool>,
+}
+
+impl ColumnInfo {
+    /// Parse `RowDescription` message body.
+    pub(crate) fn from_row_description(mut body: Bytes) -> Result<Vec<ColumnInfo>> {
+        let len = body.get_u16();
+        Self::from_fields(body, len)
+    }
+
+    /// Parse `RowDescription` fields after the fields count.
+    pub(crate) fn from_fields(mut body: Bytes, len: u16) -> Result<Vec<ColumnInfo>> {
+        let mut columns = Vec::with_capacity(len as _);
+
+        for _ in 0..len {
+            let name = body.get_nul_bytestr()?;
+            let table_oid = body.get_u32();
+            let column_id = body.get_i16();
+            let oid = body.get_u32();
+            // data type size, type modifier, format code
+            body.advance(size_of::<i16>() + size_of::<i32>() + size_of::<i16>());
+            columns.push(ColumnInfo { name, table_oid, column_id, oid, nullable: None });
+        }
+
+        Ok(columns)
+    }
+
+    /// Returns the column name.
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+
+    /// Returns the column data type [`Oid`].
+    pub fn oid(&self) -> Oid {
+        self.oid
+    }
+
+    /// Returns the table [`Oid`] if column can be identified as a column of a specific table.
+    pub fn table_oid(&self) -> Option<Oid> {
+        (self.table_oid != 0).then_some(self.table_oid)
+    }
+
+    /// Returns the attribute number if column can be identified as a column of a specific table.
+    pub fn column_id(&self) -> Option<i16> {
+        (self.column_id != 0).then_some(self.column_id)
+    }
+
+    /// Returns whether column can be `NULL`.
+    ///
+    /// Returns [`None`] if it cannot be determined, e.g. for computed column.
+    ///
+    /// Note that this only consider the `NOT NULL` constraint of the table column,
+    /// a column from outer join can still be `NULL`.
+    pub fn nullable(&self) -> Option<bool> {
+        self.nullable
+    }
+}
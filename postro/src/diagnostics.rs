@@ -0,0 +1,97 @@
+//! Database diagnostics helper.
+//!
+//! # Example
+//!
+//! ```no_run
+//! # async fn app(pool: postro::Pool) -> postro::Result<()> {
+//! let result = postro::query("UPDATE post SET title = 'foo'", &pool).await;
+//!
+//! if result.is_err() {
+//!     for blocking in postro::diagnostics::blocking_queries(&pool).await? {
+//!         eprintln!(
+//!             "pid {} waiting {:?} for {:?} lock on {:?} held by pid {}: {}",
+//!             blocking.blocked_pid, blocking.waiting, blocking.lock_mode,
+//!             blocking.relation, blocking.blocking_pid, blocking.blocking_query,
+//!         );
+//!     }
+//! }
+//! # Ok(())
+//! # }
+//! ```
+use std::time::Duration;
+
+use crate::{DecodeError, FromRow, Result, Row, executor::Executor};
+
+const BLOCKING_QUERIES: &str = "\
+SELECT blocked.pid, blocked.usename::text, blocked.query, \
+    COALESCE(EXTRACT(EPOCH FROM now() - blocked.query_start), 0)::float8, \
+    l.locktype, l.mode, l.relation::regclass::text, \
+    blocking.pid, blocking.usename::text, blocking.query, blocking.state \
+FROM pg_stat_activity blocked \
+CROSS JOIN LATERAL unnest(pg_blocking_pids(blocked.pid)) AS blocker(pid) \
+JOIN pg_stat_activity blocking ON blocking.pid = blocker.pid \
+LEFT JOIN LATERAL ( \
+    SELECT locktype, mode, relation FROM pg_locks \
+    WHERE pid = blocked.pid AND NOT granted LIMIT 1 \
+) l ON true \
+ORDER BY blocked.query_start, blocked.pid";
+
+/// Query that is waiting for a lock, with the session that blocks it.
+///
+/// Returned from [`blocking_queries`].
+#[derive(Debug, Clone)]
+pub struct BlockingQuery {
+    /// Process id of the waiting session.
+    pub blocked_pid: i32,
+    /// User of the waiting session.
+    pub blocked_user: Option<String>,
+    /// The waiting query.
+    pub blocked_query: String,
+    /// Time elapsed since the waiting query started.
+    pub waiting: Duration,
+    /// Type of the awaited lock, e.g: `relation`, `transactionid` or `tuple`.
+    pub lock_type: Option<String>,
+    /// Mode of the awaited lock, e.g: `RowExclusiveLock`.
+    pub lock_mode: Option<String>,
+    /// Relation of the awaited lock, if it is on a relation.
+    pub relation: Option<String>,
+    /// Process id of the blocking session.
+    pub blocking_pid: i32,
+    /// User of the blocking session.
+    pub blocking_user: Option<String>,
+    /// Current or last query of the blocking session.
+    pub blocking_query: String,
+    /// State of the blocking session, e.g: `idle in transaction`.
+    pub blocking_state: Option<String>,
+}
+
+impl FromRow for BlockingQuery {
+    fn from_row(row: Row) -> Result<Self, DecodeError> {
+        Ok(Self {
+            blocked_pid: row.try_get(0)?,
+            blocked_user: row.try_get(1)?,
+            blocked_query: row.try_get(2)?,
+            waiting: Duration::try_from_secs_f64(row.try_get(3)?).unwrap_or_default(),
+            lock_type: row.try_get(4)?,
+            lock_mode: row.try_get(5)?,
+            relation: row.try_get(6)?,
+            blocking_pid: row.try_get(7)?,
+            blocking_user: row.try_get(8)?,
+            blocking_query: row.try_get(9)?,
+            blocking_state: row.try_get(10)?,
+        })
+    }
+}
+
+/// Returns queries that are waiting for a lock held by other session, e.g: to log who
+/// is blocking whom when hitting `lock_timeout`.
+///
+/// Joins `pg_stat_activity` with `pg_blocking_pids` and `pg_locks`. Query of sessions
+/// of other users is only visible to superuser or role with `pg_read_all_stats`,
+/// otherwise it is `<insufficient privilege>`.
+///
+/// Note that the query is executed with the given executor, which should not be the
+/// connection that is waiting for the lock.
+pub async fn blocking_queries<Exe: Executor>(exe: Exe) -> Result<Vec<BlockingQuery>> {
+    crate::query_as(BLOCKING_QUERIES, exe).fetch_all().await
+}
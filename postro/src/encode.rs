@@ -1,5 +1,6 @@
 //! Query parameter encoding.
 use bytes::{Buf, Bytes};
+use std::borrow::Cow;
 
 use crate::{
     ext::BindParams,
@@ -8,15 +9,21 @@ use crate::{
 };
 
 /// Value that can be encoded to be bound to sql parameter.
+///
+/// Value that cannot be encoded should returns [`Encoded::invalid`] instead of panicking.
 pub trait Encode<'q> {
     /// Encode the value.
     fn encode(self) -> Encoded<'q>;
 }
 
 /// Postgres encoded value.
+#[derive(Clone)]
 pub struct Encoded<'q> {
     value: ValueRef<'q>,
     is_null: bool,
+    redacted: bool,
+    /// reason of failed encoding, reported before the query is sent
+    error: Option<Cow<'static, str>>,
     oid: Oid,
 }
 
@@ -26,6 +33,8 @@ impl<'q> Encoded<'q> {
         Encoded {
             value: ValueRef::Slice(slice),
             is_null: false,
+            redacted: false,
+            error: None,
             oid,
         }
     }
@@ -35,6 +44,8 @@ impl<'q> Encoded<'q> {
         Encoded {
             value: ValueRef::Bytes(Bytes::copy_from_slice(slice)),
             is_null: false,
+            redacted: false,
+            error: None,
             oid,
         }
     }
@@ -44,6 +55,8 @@ impl<'q> Encoded<'q> {
         Encoded {
             value: ValueRef::Bytes(value.into()),
             is_null: false,
+            redacted: false,
+            error: None,
             oid,
         }
     }
@@ -53,10 +66,26 @@ impl<'q> Encoded<'q> {
         Encoded {
             value: ValueRef::Slice(&[]),
             is_null: true,
+            redacted: false,
+            error: None,
             oid: 0,
         }
     }
 
+    /// Create [`Encoded`] that failed to encode into `oid` type.
+    ///
+    /// Instead of panicking in [`Encode::encode`], the query returns [`EncodeError`]
+    /// before anything is sent to the server.
+    pub fn invalid(oid: Oid, reason: impl Into<Cow<'static, str>>) -> Encoded<'static> {
+        Encoded {
+            value: ValueRef::Slice(&[]),
+            is_null: false,
+            redacted: false,
+            error: Some(reason.into()),
+            oid,
+        }
+    }
+
     /// Returns [`Oid`], or `0` if its `NULL`.
     pub fn oid(&self) -> Oid {
         match self.is_null {
@@ -65,9 +94,38 @@ impl<'q> Encoded<'q> {
         }
     }
 
+    /// Override the [`Oid`], e.g: to bind text as postgres enum, or value with type
+    /// resolved via [`Config::resolve_type`][crate::Config::resolve_type].
+    ///
+    /// ```no_run
+    /// # fn test(conn: &postro::Connection) {
+    /// use postro::Encode;
+    ///
+    /// let mood = conn.codecs().type_info("mood").unwrap();
+    /// let value = "happy".encode().with_oid(mood.oid());
+    /// # }
+    /// ```
+    pub fn with_oid(mut self, oid: Oid) -> Self {
+        self.oid = oid;
+        self
+    }
+
+    /// Mask the value in [`Debug`][std::fmt::Debug] output.
+    ///
+    /// See also [`Redacted`].
+    pub fn redact(mut self) -> Self {
+        self.redacted = true;
+        self
+    }
+
     pub(crate) fn value(&self) -> &ValueRef<'q> {
         &self.value
     }
+
+    /// Returns the reason of failed encoding, see [`Encoded::invalid`].
+    pub(crate) fn error(&self) -> Option<&str> {
+        self.error.as_deref()
+    }
 }
 
 impl Buf for Encoded<'_> {
@@ -93,6 +151,12 @@ impl BindParams for Encoded<'_> {
     }
 }
 
+impl<'q> Encode<'q> for Encoded<'q> {
+    fn encode(self) -> Encoded<'q> {
+        self
+    }
+}
+
 macro_rules! encode {
     (<$lf:tt,$ty:ty>$pat:tt => $body:expr) => {
         impl<$lf> Encode<$lf> for &$lf $ty {
@@ -101,6 +165,8 @@ macro_rules! encode {
                     value: $body,
                     oid: <$ty>::OID,
                     is_null: false,
+                    redacted: false,
+                    error: None,
                 }
             }
         }
@@ -112,6 +178,8 @@ macro_rules! encode {
                     value: $body,
                     oid: <$ty>::OID,
                     is_null: false,
+                    redacted: false,
+                    error: None,
                 }
             }
         }
@@ -119,16 +187,102 @@ macro_rules! encode {
 }
 
 encode!(<bool>self => ValueRef::inline(&(self as u8).to_be_bytes()));
+encode!(<i16>self => ValueRef::inline(&self.to_be_bytes()));
 encode!(<i32>self => ValueRef::inline(&self.to_be_bytes()));
+encode!(<i64>self => ValueRef::inline(&self.to_be_bytes()));
+encode!(<u32>self => ValueRef::inline(&self.to_be_bytes()));
+encode!(<f32>self => ValueRef::inline(&self.to_be_bytes()));
+encode!(<f64>self => ValueRef::inline(&self.to_be_bytes()));
 encode!(<'a,str>self => ValueRef::Slice(self.as_bytes()));
 encode!(<'a,String>self => ValueRef::Slice(self.as_bytes()));
+encode!(<'a,[u8]>self => ValueRef::Slice(self));
+encode!(<'a,Vec<u8>>self => ValueRef::Slice(self));
+encode!(<Bytes>self => ValueRef::Bytes(self));
+
+/// Parameter which value is masked in [`Debug`][std::fmt::Debug] output.
+///
+/// This prevent sensitive parameter, e.g: password, from leaking when the query is logged.
+///
+/// ```
+/// use postro::encode::{Encode, Redacted};
+/// let param = Redacted("hunter2").encode();
+/// assert!(!format!("{param:?}").contains("hunter2"));
+/// ```
+#[derive(Clone, Copy)]
+pub struct Redacted<T>(pub T);
+
+impl<T> std::fmt::Debug for Redacted<T> {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        f.write_str("<redacted>")
+    }
+}
+
+impl<'q, T: Encode<'q>> Encode<'q> for Redacted<T> {
+    fn encode(self) -> Encoded<'q> {
+        self.0.encode().redact()
+    }
+}
 
 impl std::fmt::Debug for Encoded<'_> {
     fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
         f.debug_tuple("Encoded")
-            .field(if self.is_null { &"NULL" } else { &self.value })
+            .field(match (self.is_null, self.redacted) {
+                (true, _) => &"NULL",
+                (false, true) => &"<redacted>",
+                (false, false) => &self.value,
+            })
             .field(&self.oid)
             .finish()
     }
 }
 
+
+/// Returns the first parameter that failed to encode.
+pub(crate) fn check_params(params: &[Encoded]) -> Result<(), EncodeError> {
+    match params.iter().enumerate().find(|(_, e)|e.error.is_some()) {
+        Some((i, e)) => Err(EncodeError {
+            index: i + 1,
+            oid: e.oid,
+            reason: e.error.clone().unwrap_or_default(),
+        }),
+        None => Ok(()),
+    }
+}
+
+/// An error when a parameter failed to encode, see [`Encoded::invalid`].
+pub struct EncodeError {
+    index: usize,
+    oid: Oid,
+    reason: Cow<'static, str>,
+}
+
+impl EncodeError {
+    /// Returns the 1-based index of the parameter, the same as its `$n` placeholder.
+    pub fn index(&self) -> usize {
+        self.index
+    }
+
+    /// Returns the target type [`Oid`].
+    pub fn oid(&self) -> Oid {
+        self.oid
+    }
+
+    /// Returns the reason of the failure.
+    pub fn reason(&self) -> &str {
+        &self.reason
+    }
+}
+
+impl std::error::Error for EncodeError { }
+
+impl std::fmt::Display for EncodeError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "failed to encode parameter ${} of oid {}: {}", self.index, self.oid, self.reason)
+    }
+}
+
+impl std::fmt::Debug for EncodeError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "\"{self}\"")
+    }
+}
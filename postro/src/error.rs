@@ -2,13 +2,24 @@
 use std::{backtrace::Backtrace, fmt, io, str::Utf8Error};
 
 use crate::{
-    connection::ParseError,
-    fetch::EmptyQueryError,
+    codec::TypeNotFound,
+    encode::EncodeError,
+    connection::{
+        AuthError, ConnectionPoisoned, ParseError, SessionAttrsMismatch, TlsNotSupported, UnsupportedEncoding,
+        UnsupportedVersion,
+    },
+    fetch::{EmptyQueryError, IoTimeout, QueryTimeout, TooManyRows},
     phase::UnsupportedAuth,
+    portal::PortalNotInTransaction,
+    pool::{InvalidPoolConfig, LsnTimeout, PoolDraining, PoolTimedOut},
+    query::{MessageTooLarge, TooManyParams},
     postgres::{ErrorResponse, ProtocolError},
     row::{DecodeError, RowNotFound},
+    transaction::ScriptError,
 };
 
+pub use crate::postgres::{DatabaseError, SqlState};
+
 /// A specialized [`Result`] type for `postro` operation.
 pub type Result<T, E = Error> = std::result::Result<T, E>;
 
@@ -27,6 +38,14 @@ impl Error {
     pub fn backtrace(&self) -> &Backtrace {
         &self.backtrace
     }
+
+    /// Attach the sql string to database error, used to render error position.
+    pub(crate) fn with_sql(mut self, sql: &str) -> Self {
+        if let ErrorKind::Database(err) = &mut self.kind {
+            err.set_query(sql);
+        }
+        self
+    }
 }
 
 /// All possible error kind from `postro` library.
@@ -34,12 +53,37 @@ pub enum ErrorKind {
     Config(ParseError),
     Protocol(ProtocolError),
     Io(io::Error),
-    Database(ErrorResponse),
+    Database(Box<DatabaseError>),
     Utf8(std::str::Utf8Error),
     RowNotFound(RowNotFound),
     EmptyQuery(EmptyQueryError),
     UnsupportedAuth(UnsupportedAuth),
+    Auth(AuthError),
     Decode(DecodeError),
+    Encode(EncodeError),
+    LsnTimeout(LsnTimeout),
+    TypeNotFound(TypeNotFound),
+    TooManyParams(TooManyParams),
+    PoolDraining(PoolDraining),
+    PoolTimedOut(PoolTimedOut),
+    InvalidPoolConfig(InvalidPoolConfig),
+    UnsupportedVersion(UnsupportedVersion),
+    IoTimeout(IoTimeout),
+    QueryTimeout(QueryTimeout),
+    TooManyRows(TooManyRows),
+    MessageTooLarge(MessageTooLarge),
+    UnsupportedEncoding(UnsupportedEncoding),
+    SessionAttrsMismatch(SessionAttrsMismatch),
+    TlsNotSupported(TlsNotSupported),
+    Script(ScriptError),
+    ConnectionPoisoned(ConnectionPoisoned),
+    PortalNotInTransaction(PortalNotInTransaction),
+    #[cfg(feature = "sea-query")]
+    UnsupportedValue(crate::types::UnsupportedValue),
+    #[cfg(feature = "migration")]
+    Migrate(crate::migrate::MigrateError),
+    #[cfg(feature = "tokio-util")]
+    Cancelled(crate::fetch::Cancelled),
 }
 
 macro_rules! from {
@@ -57,13 +101,39 @@ from!(<ErrorKind>e => e);
 from!(<ParseError>e => ErrorKind::Config(e));
 from!(<ProtocolError>e => ErrorKind::Protocol(e));
 from!(<std::io::Error>e => ErrorKind::Io(e));
-from!(<ErrorResponse>e => ErrorKind::Database(e));
+from!(<ErrorResponse>e => ErrorKind::Database(Box::new(e.into())));
+from!(<DatabaseError>e => ErrorKind::Database(Box::new(e)));
 from!(<Utf8Error>e => ErrorKind::Utf8(e));
 from!(<RowNotFound>e => ErrorKind::RowNotFound(e));
 from!(<EmptyQueryError>e => ErrorKind::EmptyQuery(e));
 from!(<UnsupportedAuth>e => ErrorKind::UnsupportedAuth(e));
+from!(<AuthError>e => ErrorKind::Auth(e));
 
 from!(<DecodeError>e => ErrorKind::Decode(e));
+from!(<EncodeError>e => ErrorKind::Encode(e));
+from!(<LsnTimeout>e => ErrorKind::LsnTimeout(e));
+from!(<TypeNotFound>e => ErrorKind::TypeNotFound(e));
+from!(<TooManyParams>e => ErrorKind::TooManyParams(e));
+from!(<PoolDraining>e => ErrorKind::PoolDraining(e));
+from!(<PoolTimedOut>e => ErrorKind::PoolTimedOut(e));
+from!(<InvalidPoolConfig>e => ErrorKind::InvalidPoolConfig(e));
+from!(<UnsupportedVersion>e => ErrorKind::UnsupportedVersion(e));
+from!(<IoTimeout>e => ErrorKind::IoTimeout(e));
+from!(<QueryTimeout>e => ErrorKind::QueryTimeout(e));
+from!(<TooManyRows>e => ErrorKind::TooManyRows(e));
+from!(<MessageTooLarge>e => ErrorKind::MessageTooLarge(e));
+from!(<UnsupportedEncoding>e => ErrorKind::UnsupportedEncoding(e));
+from!(<SessionAttrsMismatch>e => ErrorKind::SessionAttrsMismatch(e));
+from!(<TlsNotSupported>e => ErrorKind::TlsNotSupported(e));
+from!(<ScriptError>e => ErrorKind::Script(e));
+from!(<ConnectionPoisoned>e => ErrorKind::ConnectionPoisoned(e));
+from!(<PortalNotInTransaction>e => ErrorKind::PortalNotInTransaction(e));
+#[cfg(feature = "sea-query")]
+from!(<crate::types::UnsupportedValue>e => ErrorKind::UnsupportedValue(e));
+#[cfg(feature = "migration")]
+from!(<crate::migrate::MigrateError>e => ErrorKind::Migrate(e));
+#[cfg(feature = "tokio-util")]
+from!(<crate::fetch::Cancelled>e => ErrorKind::Cancelled(e));
 
 impl std::error::Error for Error { }
 
@@ -103,10 +173,35 @@ impl fmt::Display for ErrorKind {
             Self::Io(e) => e.fmt(f),
             Self::Database(e) => e.fmt(f),
             Self::UnsupportedAuth(e) => e.fmt(f),
+            Self::Auth(e) => e.fmt(f),
             Self::RowNotFound(e) => e.fmt(f),
             Self::EmptyQuery(e) => e.fmt(f),
             Self::Decode(e) => e.fmt(f),
-            Self::Utf8(e) => e.fmt(f)
+            Self::Encode(e) => e.fmt(f),
+            Self::Utf8(e) => e.fmt(f),
+            Self::LsnTimeout(e) => e.fmt(f),
+            Self::TypeNotFound(e) => e.fmt(f),
+            Self::TooManyParams(e) => e.fmt(f),
+            Self::PoolDraining(e) => e.fmt(f),
+            Self::PoolTimedOut(e) => e.fmt(f),
+            Self::InvalidPoolConfig(e) => e.fmt(f),
+            Self::UnsupportedVersion(e) => e.fmt(f),
+            Self::IoTimeout(e) => e.fmt(f),
+            Self::QueryTimeout(e) => e.fmt(f),
+            Self::TooManyRows(e) => e.fmt(f),
+            Self::MessageTooLarge(e) => e.fmt(f),
+            Self::UnsupportedEncoding(e) => e.fmt(f),
+            Self::SessionAttrsMismatch(e) => e.fmt(f),
+            Self::TlsNotSupported(e) => e.fmt(f),
+            Self::Script(e) => e.fmt(f),
+            Self::ConnectionPoisoned(e) => e.fmt(f),
+            Self::PortalNotInTransaction(e) => e.fmt(f),
+            #[cfg(feature = "sea-query")]
+            Self::UnsupportedValue(e) => e.fmt(f),
+            #[cfg(feature = "migration")]
+            Self::Migrate(e) => e.fmt(f),
+            #[cfg(feature = "tokio-util")]
+            Self::Cancelled(e) => e.fmt(f),
         }
     }
 }
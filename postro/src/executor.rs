@@ -1,7 +1,7 @@
 //! The [`Executor`] trait.
 use std::future::Ready;
 
-use crate::{transport::PgTransport, Result};
+use crate::{describe::StatementInfo, sql::Sql, transport::PgTransport, Result};
 
 /// A type that can returns a [`PgTransport`].
 pub trait Executor: Unpin {
@@ -13,6 +13,16 @@ pub trait Executor: Unpin {
 
     /// Acquire the transport.
     fn connection(self) -> Self::Future;
+
+    /// Describe the statement without executing it.
+    ///
+    /// See [`describe`][crate::describe::describe] for more details.
+    fn describe<SQL: Sql>(self, sql: SQL) -> impl Future<Output = Result<StatementInfo>>
+    where
+        Self: Sized,
+    {
+        crate::describe::describe(sql, self)
+    }
 }
 
 impl<T: PgTransport> Executor for &mut T {
@@ -41,5 +51,16 @@ mod test {
         let _ = query("", &mut e).fetch_all().await;
         let _ = query("", &mut e).fetch_all().await;
     }
+
+    #[allow(unused, reason = "type assertion")]
+    async fn assert_transaction(conn: &mut crate::Connection) {
+        let mut tx = crate::begin(conn).await.unwrap();
+        assert_type(&mut tx).await;
+        assert_type(tx.as_executor()).await;
+        let mut nested = tx.begin().await.unwrap();
+        assert_type(&mut nested).await;
+        assert_type(nested).await;
+        assert_type(tx).await;
+    }
 }
 
@@ -1,9 +1,10 @@
 use futures_core::Stream;
 use std::{
-    hash::{DefaultHasher, Hash, Hasher},
     marker::PhantomData,
     mem,
     pin::Pin,
+    sync::{Arc, atomic::{AtomicBool, Ordering}},
+    time::Duration,
     task::{
         Context,
         Poll::{self, *},
@@ -13,11 +14,14 @@ use std::{
 
 use crate::{
     Result, Row,
-    common::unit_error,
-    encode::Encoded,
+    describe::ColumnInfo,
+    common::{leak, unit_error},
+    error::{Error, ErrorKind},
+    encode::{Encoded, check_params},
     ext::UsizeExt,
+    query::{MAX_MESSAGE_LEN, MAX_PARAMS, MessageTooLarge, ParamBuffer, TooManyParams},
     postgres::{PgFormat, backend, frontend},
-    sql::Sql,
+    sql::{Sql, sql_id},
     statement::{PortalName, StatementName},
     transport::PgTransport,
 };
@@ -29,6 +33,24 @@ pub struct PrepareData {
     pub cache_hit: bool,
     /// this field intended to be edited by called for `portal` params.
     pub max_row: u32,
+    /// this field intended to be edited by called for `portal` params.
+    ///
+    /// If `true`, `Sync` is not sent after `Execute`, so the portal can be resumed.
+    pub paged: bool,
+}
+
+/// Validate bound parameters, prepending parameters carried by `sql`.
+pub(crate) fn prepare_params(sql: &mut impl Sql, params: &mut Vec<Encoded>) -> Result<()> {
+    if params.len() > MAX_PARAMS {
+        return Err(TooManyParams(params.len()).into());
+    }
+    let sql_params = sql.params()?;
+    if !sql_params.is_empty() {
+        drop(params.splice(..0, sql_params));
+    }
+    check_params(params)?;
+    check_message_len(sql.sql().len(), params.iter().map(|e| e.value().len()))?;
+    Ok(())
 }
 
 /// Write Prepare statement to `io`.
@@ -39,24 +61,28 @@ pub struct PrepareData {
 /// - `ParseComplete` from `Parse`
 ///
 /// Also caller might want to cache the returned statement.
-fn prepare(
+pub(crate) fn prepare(
     sql: &impl Sql,
     params: &[Encoded],
+    no_cache: bool,
     mut io: impl PgTransport,
 ) -> PrepareData {
-    let persist = sql.persistent();
-    let sql = sql.sql().trim();
-
-    let sqlid = {
-        let mut buf = DefaultHasher::new();
-        sql.hash(&mut buf);
-        buf.finish()
+    let commented = crate::sql::comment(sql.sql());
+    let (persist, sqlid, sql) = match &commented {
+        Some((commented, persistent)) => (*persistent && sql.persistent(), sql_id(commented), commented.as_str()),
+        None => {
+            let sqlid = sql.id().unwrap_or_else(|| match io.normalize_statements() {
+                true => sql_id(&crate::sql::normalize(sql.sql())),
+                false => sql_id(sql.sql()),
+            });
+            (sql.persistent(), sqlid, sql.sql())
+        },
     };
+    let persist = persist && !no_cache && io.stmt_cache_enabled();
+    let sql = sql.trim();
 
-    if persist {
-        if let Some(stmt) = io.get_stmt(sqlid) {
-            return PrepareData { sqlid, stmt, cache_hit: true, max_row: 0 };
-        }
+    if persist && let Some(stmt) = io.get_stmt(sqlid) {
+        return PrepareData { sqlid, stmt, cache_hit: true, max_row: 0, paged: false };
     }
 
     let stmt = match persist {
@@ -72,7 +98,30 @@ fn prepare(
     });
     io.send(frontend::Flush);
 
-    PrepareData { sqlid, stmt, cache_hit: false, max_row: 0 }
+    PrepareData { sqlid, stmt, cache_hit: false, max_row: 0, paged: false }
+}
+
+/// Check that `Parse` and `Bind` message would not exceed [`MAX_MESSAGE_LEN`].
+///
+/// Encoding oversized message would overflow the message length.
+pub(crate) fn check_message_len(
+    sql_len: usize,
+    param_lens: impl ExactSizeIterator<Item = usize>,
+) -> Result<(), MessageTooLarge> {
+    // message header, statement and portal name, and counts
+    const OVERHEAD: usize = 128;
+
+    let parse = OVERHEAD
+        .saturating_add(sql_len)
+        .saturating_add(param_lens.len() * size_of::<u32>());
+    let bind = param_lens.fold(OVERHEAD, |acc, len| {
+        acc.saturating_add(len).saturating_add(size_of::<i32>())
+    });
+
+    match parse.max(bind) {
+        len if len > MAX_MESSAGE_LEN => Err(MessageTooLarge(len)),
+        _ => Ok(()),
+    }
 }
 
 /// Write Prepare statement to `io`.
@@ -88,38 +137,80 @@ fn prepare(
 ///   - `EmptyQueryResponse`
 ///   - `ErrorResponse`
 ///   - `PortalSuspended`
-/// - `ReadyForQuery` from `Sync`
-fn portal(data: &PrepareData, params: &mut Vec<Encoded>, mut io: impl PgTransport) {
+/// - `ReadyForQuery` from `Sync`, if not paged
+fn portal(data: &PrepareData, params: &mut Vec<Encoded>, formats: &[PgFormat], mut io: impl PgTransport) {
     let portal = PortalName::unnamed();
+    bind(&portal, &data.stmt, params, formats, &mut io);
+    io.send(frontend::Execute {
+        portal_name: portal.as_str(),
+        max_row: data.max_row,
+    });
+    match data.paged {
+        true => io.send(frontend::Flush),
+        false => io.send(frontend::Sync),
+    }
+}
+
+/// Write `Bind` and `Describe` of `portal` to `io`.
+///
+/// Responses possible:
+/// - `BindComplete` from `Bind`
+/// - `RowDescription` or `NoData` from `Describe`
+pub(crate) fn bind(
+    portal: &PortalName,
+    stmt: &StatementName,
+    params: &mut Vec<Encoded>,
+    formats: &[PgFormat],
+    mut io: impl PgTransport,
+) {
+    // one format code applies to all columns
+    let formats = match formats.iter().all(|e|*e == PgFormat::Binary) {
+        true => &[PgFormat::Binary],
+        false => formats,
+    };
 
     io.send(frontend::Bind {
         portal_name: portal.as_str(),
-        stmt_name: data.stmt.as_str(),
+        stmt_name: stmt.as_str(),
         param_formats_len: 1,
         param_formats: [PgFormat::Binary],
         params_len: params.len().to_u16(),
         params_size_hint: params
             .iter()
             .fold(0, |acc, n| acc + 4 + n.value().len().to_u32()),
-        params: mem::take(params).into_iter(),
-        result_formats_len: 1,
-        result_formats: [PgFormat::Binary],
+        params: params.drain(..),
+        result_formats_len: formats.len().to_u16(),
+        result_formats: formats.iter().copied(),
     });
     io.send(frontend::Describe {
         kind: b'P',
         name: portal.as_str(),
     });
+}
+
+/// Resume portal execution after `PortalSuspended`.
+///
+/// Flushing is required after call.
+fn resume_portal(data: &PrepareData, mut io: impl PgTransport) {
     io.send(frontend::Execute {
-        portal_name: portal.as_str(),
+        portal_name: PortalName::unnamed().as_str(),
         max_row: data.max_row,
     });
-    io.send(frontend::Sync);
+    io.send(frontend::Flush);
+}
+
+/// Returns `true` if a cached statement fails because it is invalidated.
+fn is_stale_statement(err: &Error) -> bool {
+    match err.kind() {
+        ErrorKind::Database(err) => err.is_stale_statement(),
+        _ => false,
+    }
 }
 
 /// Decode information from [`CommandComplete`][1] message.
 ///
 /// [1]: backend::CommandComplete
-pub(crate) fn command_complete(cmd: backend::CommandComplete) -> u64 {
+pub(crate) fn command_complete(cmd: &backend::CommandComplete) -> u64 {
     let mut whs = cmd.tag.split_whitespace();
     let Some(tag) = whs.next() else {
         return 0;
@@ -153,7 +244,26 @@ pub struct FetchStream<'val, SQL, ExeFut, IO, M> {
     phase: Phase<ExeFut>,
     params: Vec<Encoded<'val>>,
     max_row: u32,
+    paged: bool,
+    /// maximum rows before aborting with [`TooManyRows`]
+    row_limit: Option<u32>,
+    rows: u32,
+    /// disable statement caching
+    no_cache: bool,
+    /// parameters kept to retry a cached statement once
+    retry: Option<Vec<Encoded<'val>>>,
+    /// allocation reused for `retry`
+    spare: Vec<Encoded<'val>>,
+    /// allocations are returned when completed
+    buffer: Option<ParamBuffer>,
+    io_timer: Timer,
+    timeout: Timer,
+    #[cfg(feature = "tokio-util")]
+    cancel: Cancel,
     cmd: Option<backend::CommandComplete>,
+    /// `RowDescription` of the result set
+    desc: Option<Row>,
+    unfinished: Unfinished,
     _p: PhantomData<M>,
 }
 
@@ -166,7 +276,7 @@ enum Phase<ExeFut> {
     BindComplete,
     Complete,
     RowDescription,
-    DataRow(Row),
+    DataRow,
     ReadyForQuery,
 }
 
@@ -184,10 +294,89 @@ impl<'val, SQL, ExeFut, IO, M> FetchStream<'val, SQL, ExeFut, IO, M> {
             phase: Phase::Connect { f: exe },
             params,
             max_row,
+            paged: false,
+            row_limit: None,
+            rows: 0,
+            no_cache: false,
+            retry: None,
+            spare: Vec::new(),
+            buffer: None,
+            io_timer: Timer::default(),
+            timeout: Timer::default(),
+            #[cfg(feature = "tokio-util")]
+            cancel: Cancel::default(),
             cmd: None,
+            desc: None,
+            unfinished: Unfinished::default(),
             _p: PhantomData,
         }
     }
+
+    pub(crate) fn with_options(mut self, opts: FetchOptions) -> Self {
+        self.io_timer = Timer::new(opts.io_timeout);
+        self.timeout = Timer::new(opts.timeout);
+        self.row_limit = opts.max_rows;
+        self.no_cache = opts.no_cache;
+        #[cfg(feature = "tokio-util")]
+        {
+            self.cancel = Cancel { cancelled: opts.cancel.map(|e| Box::pin(e.cancelled_owned())) };
+        }
+        self
+    }
+
+    pub(crate) fn with_buffer(mut self, buffer: Option<ParamBuffer>) -> Self {
+        if let Some(buffer) = &buffer {
+            self.spare = buffer.take_retry();
+        }
+        self.buffer = buffer;
+        self
+    }
+
+    /// Fetch rows from the server in pages of `rows` size.
+    ///
+    /// Instead of sending all rows at once, server will suspend the execution
+    /// after `rows` number of rows is sent, and only resume when the next page
+    /// is requested, which is when all rows in the current page are consumed.
+    ///
+    /// Note that unless inside a transaction, the whole query will be executed
+    /// in one implicit transaction, which is held until the stream is completed.
+    ///
+    /// Zero `rows` disables paging.
+    ///
+    /// # Panics
+    ///
+    /// Panics if stream is already polled.
+    pub fn page_size(mut self, rows: u32) -> Self {
+        assert!(matches!(self.phase, Phase::Connect { .. }), "stream already polled");
+        self.max_row = rows;
+        self.paged = rows != 0;
+        self
+    }
+
+    /// Returns the columns metadata of the result set.
+    ///
+    /// The metadata is available once the first row is returned or the stream is
+    /// completed, even when no rows are returned, e.g: to render header of an empty
+    /// result. Returns [`None`] before that, or if the statement returns no result set,
+    /// e.g: `INSERT` without `RETURNING`.
+    ///
+    /// ```no_run
+    /// # async fn test(mut conn: postro::Connection) -> postro::Result<()> {
+    /// use futures::TryStreamExt;
+    ///
+    /// let mut stream = postro::query("SELECT * FROM post WHERE false", &mut conn).fetch();
+    /// while let Some(row) = stream.try_next().await? {
+    ///     // ...
+    /// }
+    /// for column in stream.columns().unwrap_or_default() {
+    ///     println!("{}: {}", column.name(), column.oid());
+    /// }
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn columns(&self) -> Option<&[ColumnInfo]> {
+        self.desc.as_ref().map(Row::columns)
+    }
 }
 
 impl<SQL, ExeFut, IO, M> Stream for FetchStream<'_, SQL, ExeFut, IO, M>
@@ -201,16 +390,101 @@ where
 
     fn poll_next(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Option<Self::Item>> {
         let me = self.get_mut();
+        let poll = me.poll_stream(cx);
+        if let Ready(Some(Err(_))) = poll {
+            // the connection already handle the error
+            me.phase = Phase::Complete;
+        }
+        me.unfinished.active = !matches!(me.phase, Phase::Connect { .. } | Phase::Prepare | Phase::Complete);
+        if matches!(me.phase, Phase::Complete) && let Some(buffer) = me.buffer.take() {
+            let retry = me.retry.take().unwrap_or_else(|| mem::take(&mut me.spare));
+            buffer.put(mem::take(&mut me.params), retry);
+        }
+        poll
+    }
+}
+
+impl<SQL, ExeFut, IO, M> FetchStream<'_, SQL, ExeFut, IO, M>
+where
+    SQL: Sql + Unpin,
+    ExeFut: Future<Output = Result<IO>> + Unpin,
+    IO: PgTransport + Unpin,
+    M: StreamMap + Unpin,
+{
+    fn poll_stream(&mut self, cx: &mut Context<'_>) -> Poll<Option<Result<M::Output>>> {
+        let me = self;
+        #[cfg(feature = "tokio-util")]
+        if me.cancel.poll_cancelled(cx).is_ready() {
+            match me.phase {
+                // statement is not sent yet
+                Phase::Connect { .. } | Phase::Prepare => {
+                    me.phase = Phase::Complete;
+                    return Ready(Some(Err(Cancelled.into())));
+                },
+                Phase::Complete => { },
+                _ => request_cancel(me.io.as_ref()),
+            }
+        }
+        if !matches!(me.phase, Phase::Complete) && me.timeout.poll_elapsed(cx).is_ready() {
+            if !matches!(me.phase, Phase::Connect { .. } | Phase::Prepare) {
+                // statement is running, the connection is still busy
+                let io = me.io.as_mut().unwrap();
+                request_cancel(Some(&*io));
+                io.poison();
+            }
+            me.phase = Phase::Complete;
+            return Ready(Some(Err(QueryTimeout.into())));
+        }
+        let poll = match me.poll_fetch(cx) {
+            // waiting for connection is not bounded by io timeout
+            Pending if matches!(me.phase, Phase::Connect { .. }) => return Pending,
+            Pending => {
+                ready!(me.io_timer.poll_elapsed(cx));
+                if !matches!(me.phase, Phase::Prepare) {
+                    // replies of the statement are still pending in the socket
+                    me.io.as_mut().unwrap().poison();
+                }
+                me.phase = Phase::Complete;
+                Ready(Some(Err(IoTimeout.into())))
+            },
+            poll => poll,
+        };
+        me.io_timer.reset();
+        match poll {
+            // error position is relative to the trimmed sql sent in `prepare`
+            Ready(Some(Err(err))) => Ready(Some(Err(err.with_sql(me.sql.sql().trim())))),
+            poll => poll,
+        }
+    }
+}
+
+impl<SQL, ExeFut, IO, M> FetchStream<'_, SQL, ExeFut, IO, M>
+where
+    SQL: Sql + Unpin,
+    ExeFut: Future<Output = Result<IO>> + Unpin,
+    IO: PgTransport + Unpin,
+    M: StreamMap + Unpin,
+{
+    fn poll_fetch(&mut self, cx: &mut Context<'_>) -> Poll<Option<Result<M::Output>>> {
+        let me = self;
 
         loop {
             match &mut me.phase {
                 Phase::Connect { f } => {
+                    if let Err(err) = prepare_params(&mut me.sql, &mut me.params) {
+                        me.phase = Phase::Complete;
+                        return Ready(Some(Err(err)));
+                    }
                     let io = ready!(Pin::new(f).poll(cx)?);
                     me.io = Some(io);
                     me.phase = Phase::Prepare;
                 },
                 Phase::Prepare => {
-                    me.data = Some(prepare(&me.sql, &me.params, me.io.as_mut().unwrap()));
+                    let io = me.io.as_mut().unwrap();
+                    if me.unfinished.abandoned.is_none() {
+                        me.unfinished.abandoned = io.abandoned();
+                    }
+                    me.data = Some(prepare(&me.sql, &me.params, me.no_cache, io));
                     me.phase = match me.data.as_ref().unwrap().cache_hit {
                         true => Phase::Portal,
                         false => Phase::PrepareComplete,
@@ -225,13 +499,38 @@ where
                 },
                 Phase::Portal => {
                     let data = me.data.as_mut().unwrap();
-                    data.max_row = me.max_row;
-                    portal(data, &mut me.params, me.io.as_mut().unwrap());
+                    data.max_row = match me.row_limit {
+                        // server stops right after the limit is exceeded
+                        Some(limit) if !me.paged => limit.saturating_add(1),
+                        _ => me.max_row,
+                    };
+                    data.paged = me.paged;
+                    if data.cache_hit {
+                        let mut retry = mem::take(&mut me.spare);
+                        retry.clone_from(&me.params);
+                        me.retry = Some(retry);
+                    }
+                    portal(data, &mut me.params, M::FORMATS, me.io.as_mut().unwrap());
                     me.phase = Phase::BindComplete;
                 },
                 Phase::BindComplete => {
-                    ready!(me.io.as_mut().unwrap().poll_recv::<backend::BindComplete>(cx)?);
-                    me.phase = Phase::RowDescription;
+                    let io = me.io.as_mut().unwrap();
+                    match ready!(io.poll_recv::<backend::BindComplete>(cx)) {
+                        Ok(_) => {
+                            if let Some(mut retry) = me.retry.take() {
+                                retry.clear();
+                                me.spare = retry;
+                            }
+                            me.phase = Phase::RowDescription;
+                        },
+                        // cached statement invalidated by schema change, prepare it again once
+                        Err(err) if me.retry.is_some() && is_stale_statement(&err) && !io.in_transaction() => {
+                            io.remove_stmt(me.data.as_ref().unwrap().sqlid);
+                            me.spare = mem::replace(&mut me.params, me.retry.take().unwrap());
+                            me.phase = Phase::Prepare;
+                        },
+                        Err(err) => return Ready(Some(Err(err))),
+                    }
                 }
                 Phase::RowDescription => {
                     use backend::BackendMessage::*;
@@ -244,23 +543,33 @@ where
                         },
 
                         RowDescription(rd) => {
-                            me.phase = Phase::DataRow(Row::new(rd.body));
+                            let row = Row::new(rd.body).with_codecs(me.io.as_ref().unwrap().codecs());
+                            me.desc = Some(row);
+                            me.phase = Phase::DataRow;
                         },
                         f => {
                             let err = f.unexpected("description recv");
+                            me.io.as_mut().unwrap().poison();
                             me.phase = Phase::Complete;
                             return Ready(Some(Err(err.into())));
                         },
                     }
                 },
-                Phase::DataRow(row) => {
+                Phase::DataRow => {
                     use backend::BackendMessage::*;
                     match ready!(me.io.as_mut().unwrap().poll_recv(cx)?) {
                         DataRow(dr) => {
-                            let row = row.inner_clone(dr.body);
-                            let result = M::map(row);
+                            me.rows += 1;
+                            let result = match me.row_limit {
+                                Some(limit) if me.rows > limit => Err(TooManyRows(limit).into()),
+                                _ => M::map(me.desc.as_ref().unwrap().inner_clone(dr.body)),
+                            };
                             if result.is_err() {
-                                me.io.as_mut().unwrap().ready_request();
+                                let io = me.io.as_mut().unwrap();
+                                if me.paged {
+                                    io.send(frontend::Sync);
+                                }
+                                io.ready_request();
                                 me.phase = Phase::Complete;
                             }
                             return Ready(Some(result));
@@ -269,14 +578,27 @@ where
                         // `Execute` phase terminations:
                         CommandComplete(cmd) => {
                             me.cmd = Some(cmd);
+                            if me.paged {
+                                me.io.as_mut().unwrap().send(frontend::Sync);
+                            }
+                        },
+                        PortalSuspended(_) if me.paged => {
+                            resume_portal(me.data.as_ref().unwrap(), me.io.as_mut().unwrap());
+                            continue;
                         },
                         PortalSuspended(_) => { },
                         EmptyQueryResponse(_) => {
+                            if me.paged {
+                                let io = me.io.as_mut().unwrap();
+                                io.send(frontend::Sync);
+                                io.ready_request();
+                            }
                             me.phase = Phase::Complete;
                             return Ready(Some(Err(EmptyQueryError.into())));
                         },
                         f => {
                             let err = f.unexpected("fetching data rows");
+                            me.io.as_mut().unwrap().poison();
                             me.phase = Phase::Complete;
                             return Ready(Some(Err(err.into())));
                         },
@@ -302,6 +624,16 @@ pub struct Fetch<'val, SQL, ExeFut, IO, M, C> {
 }
 
 impl<'val, SQL, ExeFut, IO, M, C> Fetch<'val, SQL, ExeFut, IO, M, C> {
+    pub(crate) fn with_options(mut self, opts: FetchOptions) -> Self {
+        self.fetch = self.fetch.with_options(opts);
+        self
+    }
+
+    pub(crate) fn with_buffer(mut self, buffer: Option<ParamBuffer>) -> Self {
+        self.fetch = self.fetch.with_buffer(buffer);
+        self
+    }
+
     pub(crate) fn new(
         sql: SQL,
         exe: ExeFut,
@@ -337,11 +669,75 @@ where
     }
 }
 
+/// Stream of rows in chunks, created via [`Query::fetch_chunked`][1].
+///
+/// [1]: crate::query::Query::fetch_chunked
+#[derive(Debug)]
+#[must_use = "streams do nothing unless polled"]
+pub struct FetchChunks<'val, SQL, ExeFut, IO, M: StreamMap> {
+    fetch: FetchStream<'val, SQL, ExeFut, IO, M>,
+    chunk: Vec<M::Output>,
+    size: usize,
+}
+
+impl<'val, SQL, ExeFut, IO, M: StreamMap> FetchChunks<'val, SQL, ExeFut, IO, M> {
+    pub(crate) fn new(fetch: FetchStream<'val, SQL, ExeFut, IO, M>, size: u32) -> Self {
+        Self {
+            fetch: fetch.page_size(size),
+            chunk: Vec::new(),
+            size: size as usize,
+        }
+    }
+
+    /// Returns the columns metadata of the result set, see [`FetchStream::columns`].
+    pub fn columns(&self) -> Option<&[ColumnInfo]> {
+        self.fetch.columns()
+    }
+}
+
+impl<SQL, ExeFut, IO, M> Stream for FetchChunks<'_, SQL, ExeFut, IO, M>
+where
+    SQL: Sql + Unpin,
+    ExeFut: Future<Output = Result<IO>> + Unpin,
+    IO: PgTransport + Unpin,
+    M: StreamMap + Unpin,
+    M::Output: Unpin,
+{
+    type Item = Result<Vec<M::Output>>;
+
+    fn poll_next(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Option<Self::Item>> {
+        let me = self.get_mut();
+
+        loop {
+            match ready!(Pin::new(&mut me.fetch).poll_next(cx)) {
+                Some(Ok(row)) => {
+                    if me.chunk.is_empty() {
+                        me.chunk.reserve(me.size);
+                    }
+                    me.chunk.push(row);
+                    if me.chunk.len() == me.size {
+                        return Ready(Some(Ok(std::mem::take(&mut me.chunk))));
+                    }
+                },
+                Some(Err(err)) => {
+                    me.chunk.clear();
+                    return Ready(Some(Err(err)));
+                },
+                None if me.chunk.is_empty() => return Ready(None),
+                None => return Ready(Some(Ok(std::mem::take(&mut me.chunk)))),
+            }
+        }
+    }
+}
+
 /// Adapter to process a [`Row`].
 pub trait StreamMap {
     /// Processed row.
     type Output;
 
+    /// Preferred result format of each column, see [`FromRow::FORMATS`][crate::FromRow::FORMATS].
+    const FORMATS: &'static [PgFormat] = &[];
+
     /// Process row.
     fn map(row: Row) -> Result<Self::Output>;
 }
@@ -363,3 +759,287 @@ unit_error! {
     pub struct EmptyQueryError("empty query string");
 }
 
+unit_error! {
+    /// An error when a single socket read or write wait exceeds [`Query::io_timeout`][1].
+    ///
+    /// [1]: crate::query::Query::io_timeout
+    pub struct IoTimeout("timed out waiting for socket io");
+}
+
+/// An error when the query returns more rows than [`Query::max_rows`][1].
+///
+/// [1]: crate::query::Query::max_rows
+pub struct TooManyRows(pub u32);
+
+impl std::error::Error for TooManyRows { }
+
+impl std::fmt::Display for TooManyRows {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "query returns more than {} rows", self.0)
+    }
+}
+
+impl std::fmt::Debug for TooManyRows {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "\"{self}\"")
+    }
+}
+
+unit_error! {
+    /// An error when the whole query exceeds [`Query::timeout`][1].
+    ///
+    /// [1]: crate::query::Query::timeout
+    pub struct QueryTimeout("query timed out");
+}
+
+/// Options that configured via [`Query`][crate::query::Query].
+#[derive(Debug, Default)]
+pub(crate) struct FetchOptions {
+    pub io_timeout: Option<Duration>,
+    pub timeout: Option<Duration>,
+    pub max_rows: Option<u32>,
+    pub no_cache: bool,
+    #[cfg(feature = "tokio-util")]
+    pub cancel: Option<tokio_util::sync::CancellationToken>,
+}
+
+#[cfg(feature = "tokio-util")]
+unit_error! {
+    /// An error when query is cancelled via [`Query::cancel_on`][1] before the statement is sent.
+    ///
+    /// [1]: crate::query::Query::cancel_on
+    pub struct Cancelled("query cancelled");
+}
+
+/// Cooperative query cancellation.
+#[cfg(feature = "tokio-util")]
+#[derive(Debug, Default)]
+struct Cancel {
+    cancelled: Option<Pin<Box<tokio_util::sync::WaitForCancellationFutureOwned>>>,
+}
+
+#[cfg(feature = "tokio-util")]
+impl Cancel {
+    /// Returns [`Ready`] once when cancelled, never if no token is set.
+    fn poll_cancelled(&mut self, cx: &mut Context) -> Poll<()> {
+        if let Some(cancelled) = self.cancelled.as_mut() {
+            ready!(cancelled.as_mut().poll(cx));
+            self.cancelled = None;
+            return Ready(());
+        }
+        Pending
+    }
+}
+
+/// Send cancel request for statement that is already sent, if supported by transport.
+fn request_cancel<IO: PgTransport>(io: Option<&IO>) {
+    #[cfg(feature = "tokio")]
+    if let Some(handle) = io.and_then(PgTransport::cancel_handle) {
+        tokio::spawn(async move {
+            if let Err(_err) = handle.cancel().await {
+                #[cfg(feature = "log")]
+                log::error!("failed to send cancel request: {_err:#}");
+            }
+        });
+    }
+    #[cfg(not(feature = "tokio"))]
+    let _ = io;
+}
+
+/// Mark the connection as abandoned on drop if the statement is sent but the stream is not
+/// completed, so the remaining responses are discarded before the next request.
+///
+/// Separated from [`FetchStream`], so the stream does not borrow the connection until dropped.
+#[derive(Debug, Default)]
+struct Unfinished {
+    active: bool,
+    /// see [`PgTransport::abandoned`]
+    abandoned: Option<Arc<AtomicBool>>,
+}
+
+impl Drop for Unfinished {
+    fn drop(&mut self) {
+        if !self.active {
+            return;
+        }
+        leak!("query stream dropped before completion, the remaining responses are discarded");
+        if let Some(abandoned) = &self.abandoned {
+            abandoned.store(true, Ordering::Release);
+        }
+    }
+}
+
+/// Timer started on the first wait, e.g: bounding a single wait for socket io.
+#[derive(Debug, Default)]
+struct Timer {
+    #[cfg(feature = "tokio")]
+    duration: Option<Duration>,
+    #[cfg(feature = "tokio")]
+    sleep: Option<Pin<Box<tokio::time::Sleep>>>,
+}
+
+impl Timer {
+    fn new(duration: Option<Duration>) -> Self {
+        #[cfg(not(feature = "tokio"))]
+        let _ = duration;
+        Self {
+            #[cfg(feature = "tokio")]
+            duration,
+            #[cfg(feature = "tokio")]
+            sleep: None,
+        }
+    }
+
+    /// Progress is made, the next wait start a new timer.
+    fn reset(&mut self) {
+        #[cfg(feature = "tokio")]
+        {
+            self.sleep = None;
+        }
+    }
+
+    /// Returns [`Ready`] if timeout elapsed, never if no timeout is set.
+    fn poll_elapsed(&mut self, cx: &mut Context) -> Poll<()> {
+        #[cfg(feature = "tokio")]
+        if let Some(duration) = self.duration {
+            let sleep = self.sleep.get_or_insert_with(|| Box::pin(tokio::time::sleep(duration)));
+            return sleep.as_mut().poll(cx);
+        }
+        #[cfg(not(feature = "tokio"))]
+        let _ = cx;
+        Pending
+    }
+}
+
+
+#[cfg(test)]
+mod test {
+    use super::command_complete;
+    use crate::postgres::backend::CommandComplete;
+
+    #[test]
+    fn command_tag() {
+        let rows = |tag: &'static str| command_complete(&CommandComplete { tag: tag.into() });
+        assert_eq!(rows("INSERT 0 4"), 4);
+        assert_eq!(rows("SELECT 2"), 2);
+        assert_eq!(rows("UPDATE 0"), 0);
+        assert_eq!(rows("MERGE 3"), 3);
+        assert_eq!(rows("CREATE TABLE"), 0);
+        assert_eq!(rows("BEGIN"), 0);
+    }
+
+    #[test]
+    fn message_len() {
+        use super::check_message_len;
+        use crate::query::MAX_MESSAGE_LEN;
+
+        let sql = |len: usize| check_message_len(len, [].into_iter());
+        assert!(sql(0).is_ok());
+        assert!(sql(MAX_MESSAGE_LEN - 128).is_ok());
+        assert_eq!(sql(MAX_MESSAGE_LEN - 127).unwrap_err().0, MAX_MESSAGE_LEN + 1);
+        assert!(sql(usize::MAX).is_err());
+
+        let params = |lens: &[usize]| check_message_len(8, lens.iter().copied());
+        assert!(params(&[MAX_MESSAGE_LEN - 128 - 4]).is_ok());
+        assert!(params(&[MAX_MESSAGE_LEN / 2, MAX_MESSAGE_LEN / 2]).is_err());
+        assert!(params(&[usize::MAX, 1]).is_err());
+    }
+
+    #[cfg(feature = "tokio")]
+    #[tokio::test]
+    async fn io_timeout() {
+        use std::{io, task::{Context, Poll}, time::Duration};
+        use crate::{
+            Result, error::ErrorKind, query::query,
+            postgres::{BackendProtocol, FrontendProtocol, frontend},
+            statement::StatementName, transport::PgTransport,
+        };
+
+        /// Half-open connection, which never receive any message.
+        struct Stalled;
+
+        impl PgTransport for Stalled {
+            fn poll_flush(&mut self, _: &mut Context) -> Poll<io::Result<()>> { Poll::Ready(Ok(())) }
+            fn poll_recv<B: BackendProtocol>(&mut self, _: &mut Context) -> Poll<Result<B>> { Poll::Pending }
+            fn ready_request(&mut self) { }
+            fn send<F: FrontendProtocol>(&mut self, _: F) { }
+            fn send_startup(&mut self, _: frontend::Startup) { }
+            fn get_stmt(&mut self, _: u64) -> Option<StatementName> { None }
+            fn add_stmt(&mut self, _: u64, _: StatementName) { }
+        }
+
+        let err = query("SELECT 1", &mut Stalled)
+            .io_timeout(Duration::from_millis(10))
+            .await
+            .unwrap_err();
+        assert!(matches!(err.kind(), ErrorKind::IoTimeout(_)));
+
+        let err = query("SELECT 1", &mut Stalled)
+            .timeout(Duration::from_millis(10))
+            .await
+            .unwrap_err();
+        assert!(matches!(err.kind(), ErrorKind::QueryTimeout(_)));
+    }
+
+    #[cfg(feature = "tokio")]
+    #[tokio::test]
+    async fn unexpected_poison() {
+        use std::{io, task::{Context, Poll}};
+        use bytes::Bytes;
+        use crate::{
+            Result, error::ErrorKind, query::query,
+            postgres::{BackendProtocol, FrontendProtocol, frontend},
+            statement::StatementName, transport::PgTransport,
+        };
+
+        /// Replay messages, then never receive any.
+        struct Replay(Vec<u8>, bool);
+
+        impl PgTransport for Replay {
+            fn poll_flush(&mut self, _: &mut Context) -> Poll<io::Result<()>> { Poll::Ready(Ok(())) }
+            fn poll_recv<B: BackendProtocol>(&mut self, _: &mut Context) -> Poll<Result<B>> {
+                match self.0.is_empty() {
+                    true => Poll::Pending,
+                    false => Poll::Ready(Ok(B::decode(self.0.remove(0), Bytes::new())?)),
+                }
+            }
+            fn ready_request(&mut self) { }
+            fn poison(&mut self) { self.1 = true; }
+            fn send<F: FrontendProtocol>(&mut self, _: F) { }
+            fn send_startup(&mut self, _: frontend::Startup) { }
+            fn get_stmt(&mut self, _: u64) -> Option<StatementName> { None }
+            fn add_stmt(&mut self, _: u64, _: StatementName) { }
+        }
+
+        // ParseComplete, BindComplete, then PortalSuspended instead of row description
+        let mut io = Replay(b"12s".to_vec(), false);
+        let err = query("SELECT 1", &mut io).await.unwrap_err();
+        assert!(matches!(err.kind(), ErrorKind::Protocol(_)));
+        assert!(io.1);
+    }
+
+    #[cfg(feature = "tokio-util")]
+    #[tokio::test]
+    async fn cancel_on() {
+        use std::future::pending;
+        use tokio_util::sync::CancellationToken;
+        use crate::{Result, error::ErrorKind, query::query};
+
+        /// Executor waiting for connection forever, e.g: exhausted pool.
+        struct Acquiring;
+
+        impl crate::executor::Executor for Acquiring {
+            type Transport = crate::Connection;
+            type Future = std::pin::Pin<Box<dyn Future<Output = Result<crate::Connection>> + Send>>;
+
+            fn connection(self) -> Self::Future {
+                Box::pin(pending())
+            }
+        }
+
+        let token = CancellationToken::new();
+        token.cancel();
+        let err = query("SELECT 1", Acquiring).cancel_on(token).await.unwrap_err();
+        assert!(matches!(err.kind(), ErrorKind::Cancelled(_)));
+    }
+}
@@ -10,7 +10,7 @@
 //! # async fn app() -> postro::Result<()> {
 //! let mut conn = Connection::connect_env().await?;
 //!
-//! let res = postro::query::<_, _, (i32,String)>("SELECT 420,$1", &mut conn)
+//! let res = postro::query_as::<_, _, (i32,String)>("SELECT 420,$1", &mut conn)
 //!     .bind("Foo")
 //!     .fetch_one()
 //!     .await?;
@@ -34,7 +34,7 @@
 //! for i in 0..14 {
 //!     let mut pool = pool.clone();
 //!     let t = tokio::spawn(async move {
-//!         postro::execute("INSERT INTO foo(id) VALUES($1)", &mut pool)
+//!         postro::query("INSERT INTO foo(id) VALUES($1)", &mut pool)
 //!             .bind(i)
 //!             .execute()
 //!             .await
@@ -46,7 +46,7 @@
 //!     h.await.unwrap();
 //! }
 //!
-//! let foos = postro::query::<_, _, (i32,)>("SELECT * FROM foo", &mut pool)
+//! let foos = postro::query_as::<_, _, (i32,)>("SELECT * FROM foo", &mut pool)
 //!     .fetch_all()
 //!     .await?;
 //!
@@ -66,7 +66,7 @@
 //!
 //! let mut tx = postro::begin(&mut conn).await?;
 //!
-//! let _res = postro::query::<_, _, (i32,String)>("INSERT INTO foo(id) VALUES($1)", &mut tx)
+//! let _res = postro::query("INSERT INTO foo(id) VALUES($1)", &mut tx)
 //!     .bind(14)
 //!     .execute()
 //!     .await?;
@@ -92,6 +92,7 @@ pub mod postgres;
 // Encoding
 mod value;
 pub mod encode;
+pub mod codec;
 
 // Component
 mod statement;
@@ -103,6 +104,12 @@ pub mod transport;
 pub mod executor;
 pub mod query;
 pub mod transaction;
+pub mod pipeline;
+pub mod portal;
+pub mod copy;
+pub mod describe;
+pub mod rls;
+pub mod diagnostics;
 mod phase;
 mod fetch;
 
@@ -115,6 +122,15 @@ pub mod types;
 
 pub mod error;
 
+#[cfg(feature = "testing")]
+pub mod testing;
+
+#[cfg(feature = "migration")]
+pub mod migrate;
+
+#[cfg(feature = "replication")]
+pub mod replication;
+
 
 #[doc(inline)]
 pub use encode::Encode;
@@ -131,12 +147,22 @@ pub use connection::{Connection, Config};
 #[doc(inline)]
 pub use pool::{Pool, PoolConfig};
 #[doc(inline)]
-pub use query::{query, query_as, query_scalar};
+pub use query::{query, query_as, query_scalar, raw_sql};
 #[doc(inline)]
 pub use phase::{startup, begin};
 #[doc(inline)]
+pub use transaction::begin_with;
+#[doc(inline)]
+pub use pipeline::pipeline;
+#[doc(inline)]
 pub use error::{Error, Result};
 
 #[cfg(feature = "macros")]
-pub use postro_macros::{FromRow, Table, Decode, Encode};
+pub use postro_macros::{FromRow, Table, Decode, Encode, include_sql};
+
+
+#[cfg(all(feature = "macros", feature = "migration"))]
+pub use postro_macros::embed_migrations;
 
+#[cfg(all(feature = "macros", feature = "testing", feature = "tokio"))]
+pub use postro_macros::test;
@@ -0,0 +1,266 @@
+//! Sql migrations, requires `migration` feature.
+//!
+//! Migrations are sql files named `<version>_<name>.sql`, e.g: `1_create_users.sql`.
+//! Applied versions are tracked in `_postro_migrations` table, along with the checksum
+//! of its sql, so modified migration that is already applied is detected.
+//!
+//! # Example
+//!
+//! ```ignore
+//! // embedded at compile time, requires `macros` feature
+//! let migrator = postro::embed_migrations!("migrations");
+//!
+//! // or read at runtime
+//! let migrator = postro::migrate::Migrator::from_dir("migrations")?;
+//!
+//! migrator.run(&mut conn).await?;
+//! ```
+use std::{borrow::Cow, fmt, path::Path};
+
+use crate::{
+    Result,
+    executor::Executor,
+    query::{query, query_as, raw_sql},
+    transport::PgTransport,
+};
+
+const CREATE_TABLE: &str = "CREATE TABLE IF NOT EXISTS _postro_migrations (\
+    version int8 PRIMARY KEY, \
+    name text NOT NULL, \
+    checksum text NOT NULL, \
+    applied_at timestamptz NOT NULL DEFAULT now()\
+)";
+
+/// Advisory lock key, prevent concurrent migration from multiple instances.
+const LOCK_KEY: i64 = 0x706f_7374_726f;
+
+/// A single sql migration.
+#[derive(Debug, Clone)]
+pub struct Migration {
+    version: i64,
+    name: Cow<'static, str>,
+    sql: Cow<'static, str>,
+    checksum: String,
+}
+
+impl Migration {
+    /// Create new migration.
+    pub fn new(version: i64, name: impl Into<Cow<'static, str>>, sql: impl Into<Cow<'static, str>>) -> Self {
+        let sql = sql.into();
+        Self {
+            version,
+            name: name.into(),
+            checksum: format!("{:x}", md5::compute(sql.as_bytes())),
+            sql,
+        }
+    }
+
+    /// Returns the migration version.
+    pub fn version(&self) -> i64 {
+        self.version
+    }
+
+    /// Returns the migration name.
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+
+    /// Returns the migration sql.
+    pub fn sql(&self) -> &str {
+        &self.sql
+    }
+
+    /// Returns the hex encoded md5 checksum of the sql.
+    pub fn checksum(&self) -> &str {
+        &self.checksum
+    }
+}
+
+/// Apply pending [`Migration`]s.
+#[derive(Debug, Clone, Default)]
+pub struct Migrator {
+    migrations: Vec<Migration>,
+}
+
+impl Migrator {
+    /// Create new migrator, migrations are ordered by its version.
+    ///
+    /// Intended for statically known migrations, e.g: via `embed_migrations!` which
+    /// already reject duplicate version at compile time.
+    ///
+    /// # Panics
+    ///
+    /// Panics if there is duplicate version.
+    pub fn new(migrations: impl IntoIterator<Item = Migration>) -> Self {
+        match Self::sorted(migrations.into_iter().collect()) {
+            Ok(ok) => ok,
+            Err(err) => panic!("{err}"),
+        }
+    }
+
+    /// Order migrations by its version, returns error if there is duplicate version.
+    fn sorted(mut migrations: Vec<Migration>) -> Result<Self, MigrateError> {
+        migrations.sort_by_key(Migration::version);
+        if let Some(dup) = migrations.windows(2).find(|e|e[0].version == e[1].version) {
+            return Err(MigrateError::DuplicateVersion(dup[0].version));
+        }
+        Ok(Self { migrations })
+    }
+
+    /// Read migrations from `.sql` files in `dir`, other files are ignored.
+    ///
+    /// Returns [`MigrateError::InvalidName`] if file name is not `<version>_<name>.sql`,
+    /// or [`MigrateError::DuplicateVersion`] if there is duplicate version.
+    pub fn from_dir(dir: impl AsRef<Path>) -> Result<Self> {
+        let mut migrations = vec![];
+
+        for entry in std::fs::read_dir(dir)? {
+            let path = entry?.path();
+            if path.extension().is_none_or(|e|e != "sql") {
+                continue;
+            }
+            let file = path.file_name().unwrap_or_default().to_string_lossy();
+            let Some((version, name)) = parse_name(&file) else {
+                return Err(MigrateError::InvalidName(file.into_owned()).into());
+            };
+            migrations.push(Migration::new(version, name.to_owned(), std::fs::read_to_string(&path)?));
+        }
+
+        Ok(Self::sorted(migrations)?)
+    }
+
+    /// Returns all migrations, ordered by its version.
+    pub fn migrations(&self) -> &[Migration] {
+        &self.migrations
+    }
+
+    /// Apply pending migrations, returns the applied versions.
+    ///
+    /// Each migration is executed using the simple query protocol in its own transaction,
+    /// thus it should not contains transaction control or statement that cannot run
+    /// inside transaction, e.g: `CREATE INDEX CONCURRENTLY`.
+    ///
+    /// An advisory lock is held while migrating, so concurrent migrator wait for each other.
+    pub async fn run<Exe: Executor>(&self, exe: Exe) -> Result<Vec<i64>> {
+        let mut io = exe.connection().await?;
+
+        raw_sql(CREATE_TABLE, &mut io).await?;
+        raw_sql(&format!("SELECT pg_advisory_lock({LOCK_KEY})"), &mut io).await?;
+
+        let result = self.apply(&mut io).await;
+        let unlock = raw_sql(&format!("SELECT pg_advisory_unlock({LOCK_KEY})"), &mut io).await;
+
+        let applied = result?;
+        unlock?;
+        Ok(applied)
+    }
+
+    async fn apply<IO: PgTransport>(&self, io: &mut IO) -> Result<Vec<i64>> {
+        let applied = query_as::<_, _, (i64, String)>(
+            "SELECT version, checksum FROM _postro_migrations ORDER BY version",
+            &mut *io,
+        )
+        .fetch_all()
+        .await?;
+
+        for (version, checksum) in &applied {
+            match self.migrations.iter().find(|e|e.version == *version) {
+                Some(migration) if migration.checksum != *checksum => {
+                    return Err(MigrateError::ChecksumMismatch(*version).into());
+                },
+                Some(_) => {},
+                None => return Err(MigrateError::VersionMissing(*version).into()),
+            }
+        }
+
+        let mut versions = vec![];
+
+        for migration in &self.migrations {
+            if applied.iter().any(|e|e.0 == migration.version) {
+                continue;
+            }
+
+            let mut tx = crate::begin(&mut *io).await?;
+            raw_sql(&migration.sql, &mut tx).await?;
+            query("INSERT INTO _postro_migrations(version, name, checksum) VALUES ($1, $2, $3)", &mut tx)
+                .bind(migration.version)
+                .bind(migration.name())
+                .bind(migration.checksum())
+                .execute()
+                .await?;
+            tx.commit().await?;
+
+            #[cfg(feature = "log")]
+            log::info!("applied migration {}_{}", migration.version, migration.name);
+            versions.push(migration.version);
+        }
+
+        Ok(versions)
+    }
+}
+
+/// Parse `<version>_<name>.sql` file name.
+fn parse_name(file: &str) -> Option<(i64, &str)> {
+    let (version, name) = file.strip_suffix(".sql")?.split_once('_')?;
+    Some((version.parse().ok()?, name))
+}
+
+/// An error when running migration.
+pub enum MigrateError {
+    /// Applied migration sql is modified.
+    ChecksumMismatch(i64),
+    /// Applied migration is not found in the migrator.
+    VersionMissing(i64),
+    /// Migration file name is not `<version>_<name>.sql`.
+    InvalidName(String),
+    /// Multiple migrations have the same version.
+    DuplicateVersion(i64),
+}
+
+impl std::error::Error for MigrateError { }
+
+impl fmt::Display for MigrateError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::ChecksumMismatch(v) => write!(f, "migration {v} is modified after applied"),
+            Self::VersionMissing(v) => write!(f, "migration {v} is applied but not found"),
+            Self::InvalidName(name) => write!(f, "invalid migration file name {name:?}, expected `<version>_<name>.sql`"),
+            Self::DuplicateVersion(v) => write!(f, "duplicate migration version {v}"),
+        }
+    }
+}
+
+impl fmt::Debug for MigrateError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "\"{self}\"")
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn migrator() {
+        assert_eq!(parse_name("1_init.sql"), Some((1, "init")));
+        assert_eq!(parse_name("20250101_add_user_email.sql"), Some((20250101, "add_user_email")));
+        assert_eq!(parse_name("init.sql"), None);
+        assert_eq!(parse_name("v1_init.sql"), None);
+
+        let migrator = Migrator::new([
+            Migration::new(2, "post", "CREATE TABLE post()"),
+            Migration::new(1, "users", "CREATE TABLE users()"),
+        ]);
+        let versions = migrator.migrations().iter().map(Migration::version).collect::<Vec<_>>();
+        assert_eq!(versions, [1, 2]);
+        assert_eq!(migrator.migrations()[0].checksum(), format!("{:x}", md5::compute("CREATE TABLE users()")));
+
+        let dir = std::env::temp_dir().join(format!("postro_migrate_{}", std::process::id()));
+        std::fs::create_dir_all(&dir).unwrap();
+        std::fs::write(dir.join("1_a.sql"), "SELECT 1").unwrap();
+        std::fs::write(dir.join("1_b.sql"), "SELECT 2").unwrap();
+        let err = Migrator::from_dir(&dir).unwrap_err();
+        std::fs::remove_dir_all(&dir).unwrap();
+        assert!(matches!(err.kind(), crate::error::ErrorKind::Migrate(MigrateError::DuplicateVersion(1))), "{err}");
+    }
+}
@@ -3,6 +3,7 @@ use std::borrow::Cow;
 use crate::{
     Result,
     common::unit_error,
+    connection::AuthHandler,
     executor::Executor,
     postgres::{BackendMessage, backend, frontend},
     transaction::Transaction,
@@ -17,6 +18,10 @@ pub struct StartupConfig<'a> {
     pub(crate) database: Option<Cow<'a,str>>,
     pub(crate) password: Option<Cow<'a,str>>,
     pub(crate) replication: Option<Cow<'a,str>>,
+    pub(crate) client_encoding: Option<Cow<'a,str>>,
+    pub(crate) application_name: Option<Cow<'a,str>>,
+    pub(crate) params: Vec<(Cow<'a,str>,Cow<'a,str>)>,
+    pub(crate) auth_handler: Option<Box<dyn AuthHandler + 'a>>,
 }
 
 /// Startup phase successful response.
@@ -40,7 +45,7 @@ pub async fn startup<'a, IO: PgTransport>(
     mut io: IO,
 ) -> Result<StartupResponse> {
 
-    let opt: StartupConfig = opt.into();
+    let mut opt: StartupConfig = opt.into();
 
     // To begin a session, a frontend opens a connection to the server and sends a startup message.
 
@@ -50,6 +55,9 @@ pub async fn startup<'a, IO: PgTransport>(
         user: opt.user(),
         database: opt.database(),
         replication: opt.replication(),
+        client_encoding: opt.client_encoding(),
+        application_name: opt.application_name(),
+        params: opt.params().collect(),
     });
     io.flush().await?;
 
@@ -70,8 +78,21 @@ pub async fn startup<'a, IO: PgTransport>(
                 io.send(frontend::PasswordMessage { password: opt.password().unwrap_or_default() });
                 io.flush().await?;
             },
-            // TODO: support more authentication method
-            _ => return Err(UnsupportedAuth.into())
+            // The frontend must now send a PasswordMessage containing the password
+            // (with user name) encrypted via MD5, then encrypted again using the 4-byte random salt
+            MD5Password { salt } => {
+                let password = md5_password(opt.user(), opt.password().unwrap_or_default(), salt);
+                io.send(frontend::PasswordMessage { password: &password });
+                io.flush().await?;
+            },
+            // GSSAPI, SSPI, SASL, or any future method is handled by user provided handler
+            request => match opt.auth_handler.as_mut() {
+                Some(handler) => if let Some(data) = handler.respond(&request)? {
+                    io.send(frontend::GSSResponse { data: &data });
+                    io.flush().await?;
+                },
+                None => return Err(UnsupportedAuth.into()),
+            },
         }
     }
 
@@ -93,7 +114,10 @@ pub async fn startup<'a, IO: PgTransport>(
             ReadyForQuery(_) => break,
             BackendKeyData(new_key_data) => key_data = Some(new_key_data),
             // NOTE: ParameterStatus will get eaten by the IO
-            f => Err(f.unexpected("startup phase"))?,
+            f => {
+                io.poison();
+                Err(f.unexpected("startup phase"))?
+            },
         }
     }
 
@@ -102,10 +126,23 @@ pub async fn startup<'a, IO: PgTransport>(
     })
 }
 
+/// `concat('md5', md5(concat(md5(concat(password, username)), random-salt)))`
+fn md5_password(user: &str, password: &str, salt: [u8; 4]) -> String {
+    let inner = format!("{:x}", md5::compute([password.as_bytes(), user.as_bytes()].concat()));
+    let outer = md5::compute([inner.as_bytes(), &salt].concat());
+    format!("md5{outer:x}")
+}
+
 /// Begin transaction with given executor.
+///
+/// To specify isolation level or access mode, use [`begin_with`][crate::transaction::begin_with].
 pub async fn begin<Exec: Executor>(exec: Exec) -> Result<Transaction<Exec::Transport>> {
+    begin_sql(exec, "BEGIN").await
+}
+
+pub(crate) async fn begin_sql<Exec: Executor>(exec: Exec, sql: &str) -> Result<Transaction<Exec::Transport>> {
     let mut io = exec.connection().await?;
-    io.send(frontend::Query { sql: "BEGIN" });
+    io.send(frontend::Query { sql });
     io.flush().await?;
     io.recv::<backend::CommandComplete>().await?;
     let r = io.recv::<backend::ReadyForQuery>().await?;
@@ -116,7 +153,16 @@ pub async fn begin<Exec: Executor>(exec: Exec) -> Result<Transaction<Exec::Trans
 impl<'a> StartupConfig<'a> {
     /// Create new config, the database user name is required.
     pub fn new(user: impl Into<Cow<'a, str>>) -> Self {
-        Self { user: user.into(), database: None, password: None, replication: None  }
+        Self {
+            user: user.into(),
+            database: None,
+            password: None,
+            replication: None,
+            client_encoding: None,
+            application_name: None,
+            params: Vec::new(),
+            auth_handler: None,
+        }
     }
 
     /// The database user name to connect as.
@@ -163,4 +209,133 @@ impl<'a> StartupConfig<'a> {
     pub fn set_replication(&mut self, replication: impl Into<Cow<'a,str>>) {
         self.replication = Some(replication.into());
     }
+
+    /// Sets the client-side encoding, the default is the database encoding.
+    pub fn client_encoding(&self) -> Option<&str> {
+        self.client_encoding.as_ref().map(<_>::as_ref)
+    }
+
+    /// Sets the client-side encoding, the default is the database encoding.
+    pub fn set_client_encoding(&mut self, encoding: impl Into<Cow<'a,str>>) {
+        self.client_encoding = Some(encoding.into());
+    }
+
+    /// Sets the application name reported in `pg_stat_activity`.
+    pub fn application_name(&self) -> Option<&str> {
+        self.application_name.as_ref().map(<_>::as_ref)
+    }
+
+    /// Sets the application name reported in `pg_stat_activity`.
+    pub fn set_application_name(&mut self, name: impl Into<Cow<'a,str>>) {
+        self.application_name = Some(name.into());
+    }
+
+    /// Additional run-time parameters to be set at backend start time.
+    pub fn params(&self) -> impl Iterator<Item = (&str, &str)> {
+        self.params.iter().map(|(k,v)|(k.as_ref(),v.as_ref()))
+    }
+
+    /// Add run-time parameter to be set at backend start time, e.g: `search_path`.
+    pub fn add_param(&mut self, name: impl Into<Cow<'a,str>>, value: impl Into<Cow<'a,str>>) {
+        self.params.push((name.into(),value.into()));
+    }
+
+    /// Set the handler of authentication method that is not built in, e.g: GSSAPI or SSPI.
+    pub fn set_auth_handler(&mut self, handler: impl AuthHandler + 'a) {
+        self.auth_handler = Some(Box::new(handler));
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use bytes::{BufMut, Bytes, BytesMut};
+    use std::{io, task::{Context, Poll}};
+
+    use super::{StartupConfig, startup};
+    use crate::{
+        Result,
+        connection::{AuthError, AuthHandler},
+        error::ErrorKind,
+        postgres::{BackendProtocol, FrontendProtocol, backend::Authentication, frontend},
+        statement::StatementName,
+        transport::PgTransport,
+    };
+
+    /// Replay backend messages, and record frontend messages.
+    struct Script(Vec<(u8, Bytes)>, Vec<(u8, Bytes)>);
+
+    impl PgTransport for Script {
+        fn poll_flush(&mut self, _: &mut Context) -> Poll<io::Result<()>> { Poll::Ready(Ok(())) }
+        fn poll_recv<B: BackendProtocol>(&mut self, _: &mut Context) -> Poll<Result<B>> {
+            let (msgtype, body) = self.0.remove(0);
+            Poll::Ready(Ok(B::decode(msgtype, body)?))
+        }
+        fn ready_request(&mut self) { }
+        fn send<F: FrontendProtocol>(&mut self, message: F) {
+            let mut buf = BytesMut::new();
+            message.encode(&mut buf);
+            self.1.push((F::MSGTYPE, buf.freeze()));
+        }
+        fn send_startup(&mut self, _: frontend::Startup) { }
+        fn get_stmt(&mut self, _: u64) -> Option<StatementName> { None }
+        fn add_stmt(&mut self, _: u64, _: StatementName) { }
+    }
+
+    fn auth(code: u32, data: &[u8]) -> (u8, Bytes) {
+        let mut body = BytesMut::new();
+        body.put_u32(code);
+        body.put_slice(data);
+        (b'R', body.freeze())
+    }
+
+    struct Gss;
+
+    impl AuthHandler for Gss {
+        fn respond(&mut self, request: &Authentication) -> Result<Option<Vec<u8>>, AuthError> {
+            match request {
+                Authentication::GSS => Ok(Some(b"init".to_vec())),
+                Authentication::GSSContinue { data } if &data[..] == b"more" => Ok(Some(b"next".to_vec())),
+                Authentication::GSSContinue { .. } => Ok(None),
+                _ => Err(AuthError::new("gss only")),
+            }
+        }
+    }
+
+    #[tokio::test]
+    async fn auth_handler() {
+        let messages = || vec![
+            auth(7, b""),
+            auth(8, b"more"),
+            auth(8, b"done"),
+            auth(0, b""),
+            (b'K', Bytes::from_static(&[0, 0, 0, 1, 0, 0, 0, 2])),
+            (b'Z', Bytes::from_static(b"I")),
+        ];
+
+        let mut config = StartupConfig::new("postgres");
+        config.set_auth_handler(Gss);
+        let mut io = Script(messages(), vec![]);
+        let res = startup(config, &mut io).await.unwrap();
+        assert_eq!(res.backend_key_data.process_id, 1);
+        assert_eq!(io.1, [(b'p', Bytes::from_static(b"init")), (b'p', Bytes::from_static(b"next"))]);
+
+        let mut io = Script(messages(), vec![]);
+        let Err(err) = startup(StartupConfig::new("postgres"), &mut io).await else { panic!() };
+        assert!(matches!(err.kind(), ErrorKind::UnsupportedAuth(_)));
+
+        let mut config = StartupConfig::new("postgres");
+        config.set_auth_handler(Gss);
+        let mut io = Script(vec![auth(9, b"")], vec![]);
+        let Err(err) = startup(config, &mut io).await else { panic!() };
+        assert!(matches!(err.kind(), ErrorKind::Auth(_)));
+    }
+
+    #[test]
+    fn md5_password() {
+        // SELECT 'md5' || md5(md5('secret' || 'postgres') || 'salt')
+        assert_eq!(
+            super::md5_password("postgres", "secret", *b"salt"),
+            "md584c038d2ecb3d1025e697333e1660011",
+        );
+    }
 }
@@ -0,0 +1,165 @@
+//! Pipelined execution of multiple statements.
+use crate::{
+    Error, Result,
+    common::verbose,
+    encode::{Encode, Encoded, check_params},
+    executor::Executor,
+    ext::UsizeExt,
+    fetch::{check_message_len, command_complete},
+    postgres::{BackendMessage, PgFormat, backend, frontend},
+    query::{MAX_PARAMS, TooManyParams},
+    statement::{PortalName, StatementName},
+    transaction::ScriptError,
+    transport::{PgTransport, PgTransportExt},
+};
+
+/// Entrypoint of the pipeline API.
+///
+/// All statements with its parameters are sent before a single `Sync`, so the whole pipeline
+/// takes one round trip.
+///
+/// # Example
+///
+/// ```no_run
+/// # async fn test(mut conn: postro::Connection) -> postro::Result<()> {
+/// let rows = postro::pipeline(&mut conn)
+///     .push("INSERT INTO orders(id) VALUES($1)")
+///     .bind(1)
+///     .push("UPDATE stock SET qty = qty - $1 WHERE item = $2")
+///     .bind(2)
+///     .bind("Foo")
+///     .run()
+///     .await?;
+///
+/// assert_eq!(rows.len(), 2);
+/// # Ok(())
+/// # }
+/// ```
+pub fn pipeline<'val, Exe>(exe: Exe) -> Pipeline<'val, Exe> {
+    Pipeline { exe, statements: Vec::new() }
+}
+
+/// Multiple statements executed in one round trip, created via [`pipeline`].
+#[derive(Debug)]
+#[must_use = "pipeline do nothing unless `run`"]
+pub struct Pipeline<'val, Exe> {
+    exe: Exe,
+    statements: Vec<(&'val str, Vec<Encoded<'val>>)>,
+}
+
+impl<'val, Exe> Pipeline<'val, Exe> {
+    /// Push a statement to the pipeline.
+    pub fn push(mut self, sql: &'val str) -> Self {
+        self.statements.push((sql, Vec::new()));
+        self
+    }
+
+    /// Bind parameter of the last pushed statement.
+    ///
+    /// # Panics
+    ///
+    /// Panics if no statement is pushed.
+    pub fn bind<'v: 'val, V: Encode<'v>>(mut self, value: V) -> Self {
+        let (_, params) = self.statements.last_mut().expect("bind before any statement is pushed");
+        params.push(value.encode());
+        self
+    }
+
+    /// Run all statements in order, returning the number of rows affected of each statement.
+    ///
+    /// Unless inside a transaction, all statements are executed in one implicit transaction,
+    /// so an error rollback all statements. Statements after the first failed one are not
+    /// executed, the failed statement index is available from [`ScriptError::index`].
+    pub async fn run(self) -> Result<Vec<u64>>
+    where
+        Exe: Executor,
+    {
+        let mut io = self.exe.connection().await?;
+        run(&mut io, self.statements).await
+    }
+}
+
+/// Send all statements before a single `Sync`, and receive its results.
+pub(crate) async fn run<'s, 'q, IO: PgTransport>(
+    io: &mut IO,
+    statements: Vec<(&'s str, Vec<Encoded<'q>>)>,
+) -> Result<Vec<u64>> {
+    // nothing is sent if any statement is invalid
+    for (index, (sql, params)) in statements.iter().enumerate() {
+        let result = match params.len() > MAX_PARAMS {
+            true => Err(TooManyParams(params.len()).into()),
+            false => check_params(params).map_err(Error::from).and_then(|_|{
+                check_message_len(sql.len(), params.iter().map(|e|e.value().len())).map_err(Error::from)
+            }),
+        };
+        if let Err(err) = result {
+            return Err(ScriptError { index, source: Box::new(err) }.into());
+        }
+    }
+
+    let len = statements.len();
+    let portal = PortalName::unnamed();
+    let stmt = StatementName::unnamed();
+
+    for (sql, params) in statements {
+        let commented = crate::sql::comment(sql);
+        io.send(frontend::Parse {
+            prepare_name: stmt.as_str(),
+            sql: commented.as_ref().map_or(sql, |e|e.0.as_str()),
+            oids_len: params.len().to_u16(),
+            oids: params.iter().map(Encoded::oid),
+        });
+        io.send(frontend::Bind {
+            portal_name: portal.as_str(),
+            stmt_name: stmt.as_str(),
+            param_formats_len: 1,
+            param_formats: [PgFormat::Binary],
+            params_len: params.len().to_u16(),
+            params_size_hint: params
+                .iter()
+                .fold(0, |acc, n| acc + 4 + n.value().len().to_u32()),
+            params: params.into_iter(),
+            result_formats_len: 1,
+            result_formats: [PgFormat::Binary],
+        });
+        io.send(frontend::Execute {
+            portal_name: portal.as_str(),
+            max_row: 0,
+        });
+    }
+
+    // on error, the server skip the remaining statements until `Sync`, and the connection
+    // discard the remaining responses until `ReadyForQuery`
+    io.send(frontend::Sync);
+    io.flush().await?;
+
+    let mut rows = Vec::with_capacity(len);
+    for index in 0..len {
+        match recv_result(io).await {
+            Ok(n) => rows.push(n),
+            Err(err) => return Err(ScriptError { index, source: Box::new(err) }.into()),
+        }
+    }
+
+    io.recv::<backend::ReadyForQuery>().await?;
+
+    verbose!(statements = len, "pipeline");
+
+    Ok(rows)
+}
+
+/// Receive responses of one statement.
+async fn recv_result<IO: PgTransport>(io: &mut IO) -> Result<u64> {
+    use BackendMessage::*;
+    loop {
+        match io.recv().await? {
+            ParseComplete(_) | BindComplete(_) | DataRow(_) => {},
+            CommandComplete(cmd) => return Ok(command_complete(&cmd)),
+            EmptyQueryResponse(_) => return Ok(0),
+            f => {
+                io.poison();
+                return Err(f.unexpected("running pipeline").into());
+            },
+        }
+    }
+}
@@ -1,14 +1,55 @@
 //! Database connection pooling.
-use crate::{Connection, Result, executor::Executor, transport::PgTransport};
+use crate::{
+    Connection, Result, common::unit_error, executor::Executor, transaction::Transaction,
+    transport::PgTransport,
+};
 
 mod config;
+#[cfg(feature = "tokio")]
+mod shard;
 
+#[cfg(feature = "adaptive-pool")]
+mod adaptive;
+#[cfg(feature = "blocking")]
+mod blocking;
+#[cfg(feature = "tokio")]
+mod clock;
 #[cfg(feature = "tokio")]
 mod worker;
 
-pub use config::PoolConfig;
+pub use config::{InvalidPoolConfig, PoolConfig};
+#[cfg(feature = "tokio")]
+pub use shard::ConnectStats;
+#[cfg(feature = "adaptive-pool")]
+pub use adaptive::AdaptiveConfig;
 
 /// Database connection pool.
+///
+/// # Application state
+///
+/// [`Pool`] is cheap to clone, all clones share the same connections. [`Executor`] is
+/// implemented for `&Pool`, `Arc<Pool>` and `&Arc<Pool>`, so the pool can be kept in
+/// application state as is, e.g: axum `State<Arc<Pool>>` or actix `web::Data<Pool>`
+/// via `Data::into_inner`.
+///
+/// Transaction can also be started from shared reference, the connection is held by
+/// the [`Transaction`] until it is commited or dropped.
+///
+/// ```no_run
+/// use std::sync::Arc;
+/// use postro::Pool;
+///
+/// async fn handler(pool: Arc<Pool>) -> postro::Result<i32> {
+///     postro::query_scalar::<_, _, i32>("SELECT 1", &pool).fetch_one().await
+/// }
+///
+/// async fn transfer(pool: &Pool) -> postro::Result<()> {
+///     let mut tx = postro::begin(pool).await?;
+///     postro::query("UPDATE account SET balance = balance - 10 WHERE id = 1", &mut tx).await?;
+///     postro::query("UPDATE account SET balance = balance + 10 WHERE id = 2", &mut tx).await?;
+///     tx.commit().await
+/// }
+/// ```
 #[derive(Debug)]
 #[clippy::has_significant_drop]
 pub struct Pool {
@@ -57,7 +98,11 @@ impl Pool {
     }
 
     /// Create [`Pool`] and try to create one connection.
+    ///
+    /// Returns [`InvalidPoolConfig`] error if [`PoolConfig::validate`] fails.
     pub async fn connect_with(config: PoolConfig) -> Result<Self> {
+        config.validate()?;
+
         #[cfg(feature = "tokio")]
         {
             let (handle,worker) = worker::WorkerHandle::new(config);
@@ -73,12 +118,29 @@ impl Pool {
     }
 
     /// Create [`Pool`] without trying to create connection.
+    ///
+    /// # Panics
+    ///
+    /// Panics if [`PoolConfig::validate`] fails, use [`Pool::try_connect_lazy_with`] to
+    /// handle the error.
     pub fn connect_lazy_with(config: PoolConfig) -> Self {
+        match Self::try_connect_lazy_with(config) {
+            Ok(ok) => ok,
+            Err(err) => panic!("{err}"),
+        }
+    }
+
+    /// Create [`Pool`] without trying to create connection.
+    ///
+    /// Returns [`InvalidPoolConfig`] error if [`PoolConfig::validate`] fails.
+    pub fn try_connect_lazy_with(config: PoolConfig) -> Result<Self, InvalidPoolConfig> {
+        config.validate()?;
+
         #[cfg(feature = "tokio")]
         {
             let (handle,worker) = worker::WorkerHandle::new(config);
             tokio::spawn(worker);
-            Self { conn: None, handle }
+            Ok(Self { conn: None, handle })
         }
 
         #[cfg(not(feature = "tokio"))]
@@ -88,6 +150,143 @@ impl Pool {
         }
     }
 
+    /// Acquire a dedicated connection from the pool.
+    ///
+    /// The returned [`PoolConnection`] derefs to [`Connection`], allowing session level
+    /// work such as advisory locks, `SET` or `LISTEN`. The connection is returned to the
+    /// pool when dropped.
+    ///
+    /// Note that session state is not reset when the connection is returned, prefer
+    /// transaction scoped state such as `SET LOCAL`, or reset it before dropping.
+    ///
+    /// # Example
+    ///
+    /// ```no_run
+    /// # async fn test(pool: postro::Pool) -> postro::Result<()> {
+    /// let mut conn = pool.acquire().await?;
+    ///
+    /// postro::query("SELECT pg_advisory_lock(42)", &mut conn).execute().await?;
+    /// // ...
+    /// postro::query("SELECT pg_advisory_unlock(42)", &mut conn).execute().await?;
+    ///
+    /// // `Connection` method is available via deref
+    /// conn.listen("jobs").await?;
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn acquire(&self) -> PoolConnect<'static> {
+        self.clone().connection()
+    }
+
+    /// Begin a transaction on a connection checked out from the pool.
+    ///
+    /// The returned transaction owns the connection and does not borrow the pool, so it
+    /// can be stored or moved across tasks. The connection is returned to the pool when
+    /// the transaction is committed or dropped.
+    ///
+    /// # Example
+    ///
+    /// ```no_run
+    /// # async fn test(pool: postro::Pool) -> postro::Result<()> {
+    /// let mut tx = pool.begin_owned().await?;
+    ///
+    /// tokio::spawn(async move {
+    ///     postro::query("DELETE FROM post", &mut tx).execute().await?;
+    ///     tx.commit().await
+    /// });
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub async fn begin_owned(&self) -> Result<Transaction<PoolConnection<'static>>> {
+        crate::begin(self.clone()).await
+    }
+
+    /// Wait until the server has replayed the write-ahead log up to `lsn`.
+    ///
+    /// This is used for read-your-writes consistency when the pool points to a standby
+    /// server, where `lsn` is retrieved from [`Connection::current_wal_lsn`] on the primary.
+    ///
+    /// If the server is not in recovery, it returns immediately.
+    ///
+    /// Returns [`LsnTimeout`] error if the location is not replayed before `timeout` elapsed.
+    #[cfg(feature = "tokio")]
+    pub async fn wait_for_lsn(&self, lsn: crate::types::PgLsn, timeout: std::time::Duration) -> Result<()> {
+        use crate::types::PgLsn;
+        use std::time::Duration;
+        use tokio::time::{Instant, sleep};
+
+        const POLL_INTERVAL: Duration = Duration::from_millis(10);
+
+        let deadline = Instant::now() + timeout;
+
+        loop {
+            let replayed = crate::query_scalar::<_, _, Option<PgLsn>>("SELECT pg_last_wal_replay_lsn()", self)
+                .fetch_one()
+                .await?;
+
+            match replayed {
+                // not a standby
+                None => return Ok(()),
+                Some(replayed) if replayed >= lsn => return Ok(()),
+                Some(_) => {}
+            }
+
+            let now = Instant::now();
+            if now >= deadline {
+                return Err(LsnTimeout.into());
+            }
+
+            sleep(POLL_INTERVAL.min(deadline - now)).await;
+        }
+    }
+
+    /// Stop giving out connections and wait until all connections are closed.
+    ///
+    /// Acquiring connection after this call returns [`PoolDraining`] error, while
+    /// connections that are currently in use, e.g: in flight transaction, are allowed
+    /// to finish and closed when released.
+    ///
+    /// Note that other [`Pool`] handle may hold a connection until it is used or dropped.
+    #[cfg(feature = "tokio")]
+    pub async fn drain(&mut self) {
+        if let Some(conn) = self.conn.take() {
+            self.handle.release(conn);
+        }
+        self.handle.drain().await;
+    }
+
+    /// Returns the connection attempt statistic of each connection config.
+    ///
+    /// The order is the same as [`PoolConfig::connections`], statistic is reset
+    /// when the config is updated.
+    #[cfg(feature = "tokio")]
+    pub async fn connect_stats(&self) -> Vec<ConnectStats> {
+        self.handle.connect_stats().await
+    }
+
+    /// Returns the memory held by idle connections in the pool.
+    ///
+    /// Connections that are currently in use are not included, see [`Connection::memory_usage`].
+    #[cfg(feature = "tokio")]
+    pub async fn memory_usage(&self) -> crate::connection::MemoryUsage {
+        self.handle.memory_usage().await
+    }
+
+    /// Update pool configuration at runtime.
+    ///
+    /// New connections will use the new config. Idle connections created with
+    /// the previous config are closed, and connections that are currently in use
+    /// are closed when released. This allow credential rotation without
+    /// restarting the pool.
+    ///
+    /// Returns [`InvalidPoolConfig`] error if [`PoolConfig::validate`] fails, in which
+    /// case the pool keeps the current config.
+    pub fn update_config(&self, config: PoolConfig) -> Result<(), InvalidPoolConfig> {
+        config.validate()?;
+        self.handle.update_config(config);
+        Ok(())
+    }
+
     fn poll_connection(&mut self, cx: &mut std::task::Context) -> std::task::Poll<Result<Connection>> {
         self.handle.poll_acquire(cx)
     }
@@ -113,6 +312,26 @@ impl Executor for &Pool {
     }
 }
 
+impl Executor for std::sync::Arc<Pool> {
+    type Transport = PoolConnection<'static>;
+
+    type Future = PoolConnect<'static>;
+
+    fn connection(self) -> Self::Future {
+        PoolConnect { pool: Some(PoolCow::Owned(Pool::clone(&self))) }
+    }
+}
+
+impl Executor for &std::sync::Arc<Pool> {
+    type Transport = PoolConnection<'static>;
+
+    type Future = PoolConnect<'static>;
+
+    fn connection(self) -> Self::Future {
+        PoolConnect { pool: Some(PoolCow::Owned(Pool::clone(self))) }
+    }
+}
+
 impl<'a> Executor for &'a mut Pool {
     type Transport = PoolConnection<'a>;
 
@@ -134,6 +353,13 @@ impl<'a> Future for PoolConnect<'a> {
 
     fn poll(mut self: std::pin::Pin<&mut Self>, cx: &mut std::task::Context<'_>) -> std::task::Poll<Self::Output> {
         use std::task::Poll::*;
+        let pool = self.pool.as_mut().unwrap().as_mut();
+        if pool.handle.is_draining() {
+            if let Some(conn) = pool.conn.take() {
+                pool.handle.release(conn);
+            }
+            return Ready(Err(PoolDraining.into()));
+        }
         if let Some(conn) = self.pool.as_mut().unwrap().as_mut().conn.take() {
             return Ready(Ok(PoolConnection { conn: Some(conn), pool: self.pool.take().unwrap() }))
         }
@@ -151,6 +377,7 @@ pub struct PoolConnection<'a> {
 }
 
 #[derive(Debug)]
+#[allow(clippy::large_enum_variant, reason = "owned pool is the common case")]
 enum PoolCow<'a> {
     Borrow(&'a mut Pool),
     Owned(Pool),
@@ -185,6 +412,21 @@ impl PoolConnection<'_> {
     }
 }
 
+impl std::ops::Deref for PoolConnection<'_> {
+    type Target = Connection;
+
+    fn deref(&self) -> &Connection {
+        // `conn` only `None` on drop
+        self.conn.as_ref().unwrap()
+    }
+}
+
+impl std::ops::DerefMut for PoolConnection<'_> {
+    fn deref_mut(&mut self) -> &mut Connection {
+        self.connection()
+    }
+}
+
 impl Drop for PoolConnection<'_> {
     fn drop(&mut self) {
         self.pool.as_mut().conn = self.conn.take();
@@ -204,6 +446,10 @@ impl PgTransport for PoolConnection<'_> {
         self.connection().ready_request();
     }
 
+    fn poison(&mut self) {
+        self.connection().poison();
+    }
+
     fn send<F: crate::postgres::FrontendProtocol>(&mut self, message: F) {
         self.connection().send(message);
     }
@@ -219,6 +465,49 @@ impl PgTransport for PoolConnection<'_> {
     fn add_stmt(&mut self, sql: u64, id: crate::statement::StatementName) {
         self.connection().add_stmt(sql, id);
     }
+
+    fn remove_stmt(&mut self, sql: u64) {
+        self.connection().remove_stmt(sql);
+    }
+
+    fn in_transaction(&self) -> bool {
+        self.conn.as_ref().is_none_or(PgTransport::in_transaction)
+    }
+
+    fn stmt_cache_enabled(&self) -> bool {
+        self.conn.as_ref().is_none_or(PgTransport::stmt_cache_enabled)
+    }
+
+    fn normalize_statements(&self) -> bool {
+        self.conn.as_ref().is_some_and(PgTransport::normalize_statements)
+    }
+
+    fn codecs(&self) -> Option<std::sync::Arc<crate::codec::Codecs>> {
+        self.conn.as_ref().and_then(PgTransport::codecs)
+    }
+
+    fn cancel_handle(&self) -> Option<crate::connection::CancelHandle> {
+        self.conn.as_ref().and_then(PgTransport::cancel_handle)
+    }
+
+    fn abandoned(&self) -> Option<std::sync::Arc<std::sync::atomic::AtomicBool>> {
+        self.conn.as_ref().and_then(PgTransport::abandoned)
+    }
+}
+
+unit_error! {
+    /// An error when [`Pool::wait_for_lsn`] timed out.
+    pub struct LsnTimeout("timed out waiting for wal replay");
+}
+
+unit_error! {
+    /// An error when acquiring connection from draining [`Pool`].
+    pub struct PoolDraining("pool is draining");
+}
+
+unit_error! {
+    /// An error when acquiring connection exceed [`PoolConfig::acquire_timeout`].
+    pub struct PoolTimedOut("timed out acquiring connection from pool");
 }
 
 #[cfg(not(feature = "tokio"))]
@@ -238,6 +527,14 @@ mod mock_handle {
         pub fn release(&self, _: Connection) {
             unreachable!()
         }
+
+        pub fn update_config(&self, _: super::PoolConfig) {
+            unreachable!()
+        }
+
+        pub fn is_draining(&self) -> bool {
+            unreachable!()
+        }
     }
 }
 
@@ -0,0 +1,200 @@
+//! Adaptive pool sizing, requires `adaptive-pool` feature.
+use std::time::Duration;
+use tokio::time::Instant;
+
+/// Adaptive pool sizing configuration, see [`PoolConfig::adaptive`][1].
+///
+/// The pool starts with target connection count of `min_connection`, which is evaluated
+/// at the end of each window:
+/// - grow by half when average acquire wait exceeds `max_wait`, up to
+///   [`max_connection`][2]
+/// - shrink by one when peak connections in use is below `low_utilization` of the target,
+///   down to `min_connection`
+///
+/// Shrinking is skipped for one window after growing, so a spike does not cause the
+/// target to oscillate.
+///
+/// [1]: super::PoolConfig::adaptive
+/// [2]: super::PoolConfig::max_connection
+#[derive(Debug, Clone)]
+pub struct AdaptiveConfig {
+    pub(crate) min_conn: usize,
+    pub(crate) window: Duration,
+    pub(crate) max_wait: Duration,
+    pub(crate) low_utilization: f64,
+}
+
+impl Default for AdaptiveConfig {
+    fn default() -> Self {
+        Self {
+            min_conn: 1,
+            window: Duration::from_secs(10),
+            max_wait: Duration::from_millis(50),
+            low_utilization: 0.5,
+        }
+    }
+}
+
+impl AdaptiveConfig {
+    /// Create default configuration.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Set min target connection count, default to 1.
+    pub fn min_connection(mut self, value: usize) -> Self {
+        self.min_conn = value.max(1);
+        self
+    }
+
+    /// Set the duration of observation window, default to 10 seconds.
+    pub fn window(mut self, value: Duration) -> Self {
+        self.window = value;
+        self
+    }
+
+    /// Set average acquire wait that grow the target, default to 50 milliseconds.
+    pub fn max_wait(mut self, value: Duration) -> Self {
+        self.max_wait = value;
+        self
+    }
+
+    /// Set utilization ratio below which the target shrink, default to `0.5`.
+    pub fn low_utilization(mut self, value: f64) -> Self {
+        self.low_utilization = value.clamp(0.0, 1.0);
+        self
+    }
+}
+
+/// Target connection count driven by observation over a sliding window.
+#[derive(Debug)]
+pub(crate) struct Adaptive {
+    config: AdaptiveConfig,
+    target: usize,
+    window_start: Instant,
+    waits: u32,
+    wait_total: Duration,
+    /// peak connections in use
+    peak: usize,
+    /// target grew in the previous window
+    grown: bool,
+}
+
+impl Adaptive {
+    pub fn new(config: AdaptiveConfig, now: Instant) -> Self {
+        Self {
+            target: config.min_conn,
+            config,
+            window_start: now,
+            waits: 0,
+            wait_total: Duration::ZERO,
+            peak: 0,
+            grown: false,
+        }
+    }
+
+    pub fn target(&self) -> usize {
+        self.target
+    }
+
+    /// Returns the end of current window.
+    pub fn window_end(&self) -> Instant {
+        self.window_start + self.config.window
+    }
+
+    /// Record the duration an acquire request waited for connection.
+    pub fn record_wait(&mut self, wait: Duration) {
+        self.waits = self.waits.saturating_add(1);
+        self.wait_total = self.wait_total.saturating_add(wait);
+    }
+
+    /// Record the number of connections currently in use.
+    pub fn record_in_use(&mut self, in_use: usize) {
+        self.peak = self.peak.max(in_use);
+    }
+
+    /// Evaluate the target if the window elapsed, returns `true` if target changed.
+    pub fn tick(&mut self, now: Instant, max: usize) -> bool {
+        if now < self.window_end() {
+            return false;
+        }
+
+        let prev = self.target;
+        let max = max.max(self.config.min_conn);
+        let avg_wait = match self.waits {
+            0 => Duration::ZERO,
+            waits => self.wait_total / waits,
+        };
+
+        if avg_wait > self.config.max_wait && self.target < max {
+            self.target += (self.target / 2).max(1);
+            self.grown = true;
+        } else if std::mem::take(&mut self.grown) {
+            // cool down after growing
+        } else if (self.peak as f64) < self.target as f64 * self.config.low_utilization {
+            self.target -= 1;
+        }
+        self.target = self.target.clamp(self.config.min_conn, max);
+
+        self.window_start = now;
+        self.waits = 0;
+        self.wait_total = Duration::ZERO;
+        self.peak = 0;
+
+        self.target != prev
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn adaptive_target() {
+        let config = AdaptiveConfig::new()
+            .min_connection(2)
+            .window(Duration::from_secs(1))
+            .max_wait(Duration::from_millis(10));
+        let mut now = Instant::now();
+        let mut adaptive = Adaptive::new(config, now);
+        assert_eq!(adaptive.target(), 2);
+
+        // window is not elapsed
+        adaptive.record_wait(Duration::from_millis(100));
+        assert!(!adaptive.tick(now, 10));
+
+        // grow by half, at least one
+        now += Duration::from_secs(1);
+        assert!(adaptive.tick(now, 10));
+        assert_eq!(adaptive.target(), 3);
+
+        adaptive.record_wait(Duration::from_millis(100));
+        adaptive.record_wait(Duration::ZERO);
+        now += Duration::from_secs(1);
+        assert!(adaptive.tick(now, 10));
+        assert_eq!(adaptive.target(), 4);
+
+        // bounded by max
+        adaptive.record_wait(Duration::from_millis(100));
+        now += Duration::from_secs(1);
+        assert!(adaptive.tick(now, 5));
+        assert_eq!(adaptive.target(), 5);
+
+        // idle, but cool down after growing
+        now += Duration::from_secs(1);
+        assert!(!adaptive.tick(now, 5));
+
+        // utilization within hysteresis band
+        adaptive.record_in_use(3);
+        now += Duration::from_secs(1);
+        assert!(!adaptive.tick(now, 5));
+
+        // shrink by one, bounded by min
+        for target in [4, 3, 2, 2] {
+            adaptive.record_in_use(1);
+            now += Duration::from_secs(1);
+            adaptive.tick(now, 5);
+            assert_eq!(adaptive.target(), target);
+        }
+    }
+}
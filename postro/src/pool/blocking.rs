@@ -0,0 +1,86 @@
+//! Blocking [`Pool`] operation.
+use tokio::runtime::{Handle, RuntimeFlavor};
+
+use super::{Pool, PoolConnection};
+use crate::Result;
+
+impl Pool {
+    /// Run a future to completion on the runtime where the pool is created.
+    ///
+    /// This is intended for synchronous code, e.g: rayon workers, to use the same pool.
+    ///
+    /// When called within multi thread runtime, the current worker thread is
+    /// transitioned to blocking thread via [`block_in_place`][1].
+    ///
+    /// Note that if the pool is created in current thread runtime, the runtime must
+    /// be driven by [`Runtime::block_on`][2] in another thread.
+    ///
+    /// # Panics
+    ///
+    /// Panics if called within current thread runtime, because it will block the
+    /// runtime which may also be required to complete the future.
+    ///
+    /// [1]: tokio::task::block_in_place
+    /// [2]: tokio::runtime::Runtime::block_on
+    pub fn block_on<F: Future>(&self, f: F) -> F::Output {
+        let runtime = self.handle.runtime();
+        match Handle::try_current() {
+            Err(_) => runtime.block_on(f),
+            Ok(current) => match current.runtime_flavor() {
+                RuntimeFlavor::MultiThread => tokio::task::block_in_place(|| runtime.block_on(f)),
+                _ => panic!("blocking pool operation can not be used in current thread runtime"),
+            },
+        }
+    }
+
+    /// Acquire a connection in blocking manner.
+    ///
+    /// See [`Pool::block_on`] for more details.
+    pub fn blocking_acquire(&self) -> Result<PoolConnection<'static>> {
+        self.block_on(self.acquire())
+    }
+
+    /// Acquire a connection and run queries in blocking manner.
+    ///
+    /// See [`Pool::block_on`] for more details.
+    ///
+    /// # Example
+    ///
+    /// ```no_run
+    /// # fn test(pool: postro::Pool) -> postro::Result<()> {
+    /// let names = pool.blocking_query(async |conn| {
+    ///     postro::query_scalar::<_, _, String>("SELECT name FROM post", conn)
+    ///         .fetch_all()
+    ///         .await
+    /// })?;
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn blocking_query<F, T>(&self, f: F) -> Result<T>
+    where
+        F: AsyncFnOnce(&mut PoolConnection<'static>) -> Result<T>,
+    {
+        self.block_on(async {
+            let mut conn = self.acquire().await?;
+            f(&mut conn).await
+        })
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use crate::{Config, Pool, PoolConfig};
+
+    #[test]
+    fn block_on_outside_runtime() {
+        let rt = tokio::runtime::Builder::new_multi_thread().enable_all().build().unwrap();
+        let pool = rt.block_on(async {
+            let config = PoolConfig::from_env()
+                .with_connection(Config::parse("postgres://postgres:@127.0.0.1:1/postgres").unwrap());
+            Pool::connect_lazy_with(config)
+        });
+
+        assert_eq!(pool.block_on(async { 420 }), 420);
+        assert_eq!(rt.block_on(async { pool.block_on(async { 69 }) }), 69);
+    }
+}
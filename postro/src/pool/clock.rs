@@ -0,0 +1,51 @@
+//! Time source of the pool worker.
+use std::pin::Pin;
+use tokio::time::{Instant, Sleep};
+
+/// A source of time used by the pool worker.
+///
+/// Healthcheck interval and connect retry delay are driven by this trait, so tests can
+/// exercise them deterministically instead of waiting for real time to pass.
+pub trait Clock: Send + Sync + Unpin + 'static {
+    /// Future returned by [`Clock::sleep_until`].
+    type Sleep: Future<Output = ()> + Send + Sync;
+
+    /// Returns the current instant.
+    fn now(&self) -> Instant;
+
+    /// Returns a future that resolve when `deadline` is reached.
+    fn sleep_until(&self, deadline: Instant) -> Self::Sleep;
+
+    /// Reset the sleep future to resolve at new `deadline`.
+    fn reset(&self, sleep: Pin<&mut Self::Sleep>, deadline: Instant);
+
+    /// Returns the instant at which the sleep future will resolve.
+    #[allow(unused, reason = "used in verbose tracing")]
+    fn deadline(&self, sleep: &Self::Sleep) -> Instant;
+}
+
+/// The default [`Clock`], which use the tokio timer.
+///
+/// This also works with `tokio::time::pause`.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct TokioClock;
+
+impl Clock for TokioClock {
+    type Sleep = Sleep;
+
+    fn now(&self) -> Instant {
+        Instant::now()
+    }
+
+    fn sleep_until(&self, deadline: Instant) -> Self::Sleep {
+        tokio::time::sleep_until(deadline)
+    }
+
+    fn reset(&self, sleep: Pin<&mut Self::Sleep>, deadline: Instant) {
+        sleep.reset(deadline);
+    }
+
+    fn deadline(&self, sleep: &Self::Sleep) -> Instant {
+        sleep.deadline()
+    }
+}
@@ -1,40 +1,209 @@
-use std::time::Duration;
+use std::{fmt, time::Duration};
 
 use crate::{Config, Result};
 
 use super::Pool;
 
 /// Pool configuration builder.
+///
+/// | option | default |
+/// | --- | --- |
+/// | [`max_connection`][PoolConfig::max_connection] | 10 |
+/// | [`min_idle`][PoolConfig::min_idle] | 1 |
+/// | [`max_idle`][PoolConfig::max_idle] | unbounded |
+/// | [`acquire_timeout`][PoolConfig::acquire_timeout] | 30 seconds |
+/// | [`connect_retry`][PoolConfig::connect_retry] | 3 retries, 5 seconds delay |
+/// | [`healthcheck_interval`][PoolConfig::healthcheck_interval] | 60 seconds |
+/// | [`healthcheck_concurrency`][PoolConfig::healthcheck_concurrency] | 4 |
+/// | [`healthcheck_jitter`][PoolConfig::healthcheck_jitter] | 1 second |
+///
+/// The configuration is checked via [`validate`][PoolConfig::validate] when the pool
+/// is created.
 pub struct PoolConfig {
-    pub(crate) conn: Config,
+    /// connection config with its weight, never empty
+    pub(crate) conns: Vec<(Config, u32)>,
     pub(crate) max_conn: usize,
     pub(crate) retry_delay: Duration,
     pub(crate) max_retry: usize,
     pub(crate) interval: Duration,
+    pub(crate) healthcheck_concurrency: usize,
+    pub(crate) healthcheck_jitter: Duration,
+    pub(crate) keepalive: bool,
+    pub(crate) stmt_cache: bool,
+    pub(crate) acquire_timeout: Duration,
+    pub(crate) min_idle: usize,
+    pub(crate) max_idle: usize,
+    #[cfg(feature = "adaptive-pool")]
+    pub(crate) adaptive: Option<super::AdaptiveConfig>,
 }
 
 impl PoolConfig {
     pub fn from_env() -> PoolConfig {
         Self {
-            conn: Config::from_env(),
+            conns: vec![(Config::from_env(), 1)],
             max_conn: 10,
             retry_delay: Duration::from_secs(5),
             max_retry: 3,
             interval: Duration::from_secs(60),
+            healthcheck_concurrency: 4,
+            healthcheck_jitter: Duration::from_secs(1),
+            keepalive: false,
+            stmt_cache: true,
+            acquire_timeout: Duration::from_secs(30),
+            min_idle: 1,
+            max_idle: usize::MAX,
+            #[cfg(feature = "adaptive-pool")]
+            adaptive: None,
         }
     }
 
     /// Get connection config.
+    ///
+    /// If multiple connection config is set, returns the first one.
     pub fn connection(&self) -> &Config {
-        &self.conn
+        &self.conns[0].0
     }
 
-    /// Set max connection.
+    /// Get all connection config with its weight.
+    pub fn connections(&self) -> &[(Config, u32)] {
+        &self.conns
+    }
+
+    /// Set connection config.
+    pub fn with_connection(mut self, conn: Config) -> Self {
+        self.conns = vec![(conn, 1)];
+        self
+    }
+
+    /// Set multiple connection config with its weight.
+    ///
+    /// New connections are distributed across configs using weighted round-robin,
+    /// e.g: different users for different privilege levels, or different endpoints.
+    /// Connection attempt statistic of each config is available via
+    /// [`Pool::connect_stats`].
+    ///
+    /// # Panics
+    ///
+    /// Panics if `conns` is empty or total weight is zero.
+    pub fn with_connections(mut self, conns: impl IntoIterator<Item = (Config, u32)>) -> Self {
+        let conns = conns.into_iter().collect::<Vec<_>>();
+        assert!(
+            conns.iter().any(|(_, weight)| *weight != 0),
+            "pool requires at least one connection config with non zero weight",
+        );
+        self.conns = conns;
+        self
+    }
+
+    /// Set max connection, default to 10.
+    ///
+    /// Must be non zero and not less than [`min_idle`][PoolConfig::min_idle].
     pub fn max_connection(mut self, value: usize) -> Self {
         self.max_conn = value;
         self
     }
 
+    /// Set max connect attempt retried on failure and the delay between them, default
+    /// to 3 retries with 5 seconds delay.
+    ///
+    /// `delay` must be non zero if `max_retry` is non zero, to not hammer the server
+    /// that is down.
+    pub fn connect_retry(mut self, max_retry: usize, delay: Duration) -> Self {
+        self.max_retry = max_retry;
+        self.retry_delay = delay;
+        self
+    }
+
+    /// Set interval of idle connection healthcheck and idle connection shrinking,
+    /// default to 60 seconds.
+    ///
+    /// Must be non zero.
+    pub fn healthcheck_interval(mut self, value: Duration) -> Self {
+        self.interval = value;
+        self
+    }
+
+    /// Set max number of connection healthcheck in progress at once, default to 4.
+    ///
+    /// Due idle connections exceeding the limit are checked on the next worker iteration.
+    pub fn healthcheck_concurrency(mut self, value: usize) -> Self {
+        self.healthcheck_concurrency = value;
+        self
+    }
+
+    /// Set max random delay added to each connection healthcheck schedule, default to 1 second.
+    ///
+    /// This spread healthchecks of connections created at the same time, e.g: on startup
+    /// or after traffic spike, to avoid load spike against the server.
+    pub fn healthcheck_jitter(mut self, value: Duration) -> Self {
+        self.healthcheck_jitter = value;
+        self
+    }
+
+    /// Set whether healthcheck of idle connection sends a `Sync` message and waits for
+    /// `ReadyForQuery`, default to `false`.
+    ///
+    /// Without it, healthcheck only drains pending responses, and dead socket is only
+    /// detected when the connection is acquired. The `Sync` round trip is the cheapest
+    /// possible, no statement is executed, see [`Connection::ping`][crate::Connection::ping].
+    pub fn keepalive(mut self, value: bool) -> Self {
+        self.keepalive = value;
+        self
+    }
+
+    /// Set whether prepared statement is cached, default to `true`.
+    ///
+    /// `false` disables statement cache of all connections regardless of their config,
+    /// e.g: behind pgbouncer in transaction pooling mode, see
+    /// [`Config::statement_cache`][crate::Config::statement_cache].
+    pub fn statement_cache(mut self, enabled: bool) -> Self {
+        self.stmt_cache = enabled;
+        self
+    }
+
+    /// Set min number of idle connection the pool try to keep open, default to 1.
+    ///
+    /// Other connections are only created on demand.
+    pub fn min_idle(mut self, value: usize) -> Self {
+        self.min_idle = value;
+        self
+    }
+
+    /// Set max number of idle connection, default to unbounded.
+    ///
+    /// When traffic drops, surplus idle connections are closed gradually, one for each
+    /// [`interval`][PoolConfig::interval], down to `max(min_idle, max_idle)` connections.
+    /// This is useful for database that is billed per connection time.
+    pub fn max_idle(mut self, value: usize) -> Self {
+        self.max_idle = value;
+        self
+    }
+
+    /// Set max duration to wait for a connection, default to 30 seconds.
+    ///
+    /// Must be non zero.
+    ///
+    /// Acquiring connection returns [`PoolTimedOut`][1] error after `value` elapsed, e.g:
+    /// when all connections are in use, or the database is down and connect retries
+    /// are still in progress.
+    ///
+    /// [1]: super::PoolTimedOut
+    pub fn acquire_timeout(mut self, value: Duration) -> Self {
+        self.acquire_timeout = value;
+        self
+    }
+
+    /// Enable adaptive pool sizing, experimental.
+    ///
+    /// Instead of creating connections up to [`max_connection`][PoolConfig::max_connection]
+    /// on demand, the pool adjust the target connection count based on observed acquire
+    /// wait and utilization, see [`AdaptiveConfig`][super::AdaptiveConfig].
+    #[cfg(feature = "adaptive-pool")]
+    pub fn adaptive(mut self, config: super::AdaptiveConfig) -> Self {
+        self.adaptive = Some(config);
+        self
+    }
+
     /// Get retry delay.
     pub fn retry_delay(&self) -> Duration {
         self.retry_delay
@@ -49,19 +218,110 @@ impl PoolConfig {
     pub fn interval(&self) -> Duration {
         self.interval
     }
+
+    /// Check for invalid combination of options.
+    ///
+    /// This is called by [`Pool::connect_with`] and friends, invalid configuration would
+    /// otherwise make the pool worker misbehave silently, e.g: never create connection
+    /// or spin without delay.
+    pub fn validate(&self) -> Result<(), InvalidPoolConfig> {
+        let invalid = |option, reason| Err(InvalidPoolConfig { option, reason });
+
+        if self.max_conn == 0 {
+            return invalid("max_connection", "must be non zero".into());
+        }
+        if self.min_idle > self.max_conn {
+            return invalid(
+                "min_idle",
+                format!("{} exceeds max_connection {}", self.min_idle, self.max_conn),
+            );
+        }
+        if self.max_retry != 0 && self.retry_delay.is_zero() {
+            return invalid(
+                "connect_retry",
+                format!("delay must be non zero for {} retries", self.max_retry),
+            );
+        }
+        if self.interval.is_zero() {
+            return invalid("healthcheck_interval", "must be non zero".into());
+        }
+        if self.healthcheck_concurrency == 0 {
+            return invalid("healthcheck_concurrency", "must be non zero".into());
+        }
+        if self.acquire_timeout.is_zero() {
+            return invalid("acquire_timeout", "must be non zero".into());
+        }
+        Ok(())
+    }
 }
 
 impl PoolConfig {
     pub async fn connect(mut self, url: &str) -> Result<Pool> {
         let conn = Config::parse(url)?;
-        self.conn = conn;
+        self.conns = vec![(conn, 1)];
         Pool::connect_with(self).await
     }
 
     pub fn connect_lazy(mut self, url: &str) -> Result<Pool> {
         let conn = Config::parse(url)?;
-        self.conn = conn;
-        Ok(Pool::connect_lazy_with(self))
+        self.conns = vec![(conn, 1)];
+        Ok(Pool::try_connect_lazy_with(self)?)
+    }
+}
+
+/// An error when [`PoolConfig`] contains invalid combination of options.
+pub struct InvalidPoolConfig {
+    option: &'static str,
+    reason: String,
+}
+
+impl InvalidPoolConfig {
+    /// Returns the name of the invalid option.
+    pub fn option(&self) -> &'static str {
+        self.option
+    }
+
+    /// Returns the reason the option is invalid.
+    pub fn reason(&self) -> &str {
+        &self.reason
+    }
+}
+
+impl std::error::Error for InvalidPoolConfig { }
+
+impl fmt::Display for InvalidPoolConfig {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "invalid pool config `{}`: {}", self.option, self.reason)
+    }
+}
+
+impl fmt::Debug for InvalidPoolConfig {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "\"{self}\"")
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use std::time::Duration;
+
+    use super::PoolConfig;
+
+    #[test]
+    fn validate() {
+        let option = |config: PoolConfig| config.validate().unwrap_err().option();
+
+        assert!(PoolConfig::from_env().validate().is_ok());
+        assert!(PoolConfig::from_env().connect_retry(0, Duration::ZERO).validate().is_ok());
+        assert_eq!(option(PoolConfig::from_env().max_connection(0)), "max_connection");
+        assert_eq!(option(PoolConfig::from_env().max_connection(2).min_idle(3)), "min_idle");
+        assert_eq!(option(PoolConfig::from_env().connect_retry(100, Duration::ZERO)), "connect_retry");
+        assert_eq!(option(PoolConfig::from_env().healthcheck_interval(Duration::ZERO)), "healthcheck_interval");
+        assert_eq!(option(PoolConfig::from_env().healthcheck_concurrency(0)), "healthcheck_concurrency");
+        assert_eq!(option(PoolConfig::from_env().acquire_timeout(Duration::ZERO)), "acquire_timeout");
+
+        let err = PoolConfig::from_env().max_connection(2).min_idle(3).validate().unwrap_err();
+        assert_eq!(err.to_string(), "invalid pool config `min_idle`: 3 exceeds max_connection 2");
     }
 }
 
@@ -0,0 +1,96 @@
+//! Connection config selection for pool with multiple connection configs.
+use super::PoolConfig;
+
+/// Connection attempt statistic of a connection config.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct ConnectStats {
+    /// Number of connection attempt.
+    pub attempts: u64,
+    /// Number of failed connection attempt.
+    pub failures: u64,
+}
+
+impl ConnectStats {
+    /// Returns the ratio of failed connection attempt, `0.0` if there is no attempt.
+    pub fn failure_rate(&self) -> f64 {
+        match self.attempts {
+            0 => 0.0,
+            attempts => self.failures as f64 / attempts as f64,
+        }
+    }
+}
+
+/// Smooth weighted round-robin across connection configs.
+///
+/// <https://github.com/phusion/nginx/commit/27e94984486058d73157038f7950a0a36ecc6e35>
+#[derive(Debug)]
+pub(crate) struct Shards {
+    current: Vec<i64>,
+    stats: Vec<ConnectStats>,
+}
+
+impl Shards {
+    pub fn new(config: &PoolConfig) -> Self {
+        Self {
+            current: vec![0; config.conns.len()],
+            stats: vec![ConnectStats::default(); config.conns.len()],
+        }
+    }
+
+    /// Returns the index of connection config to use for the next connection.
+    pub fn next(&mut self, config: &PoolConfig) -> usize {
+        let mut total = 0;
+        let mut selected = 0;
+
+        for (i, (_, weight)) in config.conns.iter().enumerate() {
+            let weight = i64::from(*weight);
+            total += weight;
+            self.current[i] += weight;
+            if self.current[i] > self.current[selected] {
+                selected = i;
+            }
+        }
+
+        self.current[selected] -= total;
+        selected
+    }
+
+    /// Record connection attempt result.
+    pub fn record(&mut self, shard: usize, ok: bool) {
+        let stats = &mut self.stats[shard];
+        stats.attempts += 1;
+        if !ok {
+            stats.failures += 1;
+        }
+    }
+
+    pub fn stats(&self) -> Vec<ConnectStats> {
+        self.stats.clone()
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::Shards;
+    use crate::{Config, PoolConfig};
+
+    #[test]
+    fn weighted_round_robin() {
+        let config = |user: &str| Config::parse(&format!("postgres://{user}:@127.0.0.1:5432/postgres")).unwrap();
+        let config = PoolConfig::from_env().with_connections([
+            (config("a"), 5),
+            (config("b"), 1),
+            (config("c"), 1),
+            (config("d"), 0),
+        ]);
+
+        let mut shards = Shards::new(&config);
+        let picks = (0..7).map(|_| shards.next(&config)).collect::<Vec<_>>();
+        assert_eq!(picks, [0, 0, 1, 0, 2, 0, 0]);
+
+        shards.record(1, false);
+        shards.record(1, true);
+        assert_eq!(shards.stats()[1].failure_rate(), 0.5);
+        assert_eq!(shards.stats()[0].failure_rate(), 0.0);
+    }
+}
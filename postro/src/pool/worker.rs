@@ -1,6 +1,10 @@
 use std::{
     collections::VecDeque,
     pin::Pin,
+    sync::{
+        Arc,
+        atomic::{AtomicBool, Ordering},
+    },
     task::{
         Context,
         Poll::{self, *},
@@ -13,13 +17,20 @@ use tokio::{
         mpsc::{self, UnboundedReceiver, UnboundedSender},
         oneshot,
     },
-    time::{Instant, Sleep, sleep},
+    time::Instant,
 };
 
-use super::PoolConfig;
+#[cfg(feature = "adaptive-pool")]
+use super::adaptive::Adaptive;
+use super::{
+    PoolConfig, PoolDraining, PoolTimedOut,
+    clock::{Clock, TokioClock},
+    shard::{ConnectStats, Shards},
+};
 use crate::{
     Connection, Result,
     common::{span, verbose},
+    connection::MemoryUsage,
 };
 
 const HALF_MINUTE: Duration = Duration::from_secs(3);
@@ -27,6 +38,10 @@ const HALF_MINUTE: Duration = Duration::from_secs(3);
 pub struct WorkerHandle {
     send: UnboundedSender<WorkerMessage>,
     state: State,
+    draining: Arc<AtomicBool>,
+    /// runtime where the worker is spawned
+    #[cfg(feature = "blocking")]
+    runtime: tokio::runtime::Handle,
 }
 
 enum State {
@@ -36,11 +51,21 @@ enum State {
 
 impl WorkerHandle {
     pub fn new(config: PoolConfig) -> (Self, WorkerFutureV2) {
+        Self::with_clock(config, TokioClock)
+    }
+
+    pub fn with_clock<C: Clock>(config: PoolConfig, clock: C) -> (Self, WorkerFutureV2<C>) {
         let (send, recv) = mpsc::unbounded_channel();
         (
-            Self { send, state: State::Idle },
+            Self {
+                send,
+                state: State::Idle,
+                draining: <_>::default(),
+                #[cfg(feature = "blocking")]
+                runtime: tokio::runtime::Handle::current(),
+            },
             WorkerFutureV2 {
-                started: Instant::now(),
+                started: clock.now(),
                 #[cfg(feature = "verbose")]
                 iter_n: 0,
                 connect_retry: 0,
@@ -53,11 +78,19 @@ impl WorkerHandle {
 
                 connect_delay: None,
                 connecting: None,
-                healthcheck: None,
+                connecting_shard: 0,
+                shards: Shards::new(&config),
+                healthchecks: Vec::new(),
+                shrunk_at: clock.now(),
+                #[cfg(feature = "adaptive-pool")]
+                adaptive: config.adaptive.clone().map(|e|Adaptive::new(e, clock.now())),
                 closing: None,
-                sleep: Box::pin(sleep(config.interval)),
+                sleep: Box::pin(clock.sleep_until(clock.now() + config.interval)),
 
                 config,
+                updated_at: None,
+                drain: None,
+                clock,
             },
         )
     }
@@ -83,6 +116,38 @@ impl WorkerHandle {
     pub fn release(&self, conn: Connection) {
         self.send.send(WorkerMessage::Release(conn)).expect("worker task closed");
     }
+
+    pub fn update_config(&self, config: PoolConfig) {
+        self.send.send(WorkerMessage::UpdateConfig(config)).expect("worker task closed");
+    }
+
+    pub async fn drain(&self) {
+        let (tx,rx) = oneshot::channel();
+        self.draining.store(true, Ordering::Relaxed);
+        self.send.send(WorkerMessage::Drain(tx)).expect("worker task closed");
+        rx.await.expect("worker pool closed")
+    }
+
+    pub async fn connect_stats(&self) -> Vec<ConnectStats> {
+        let (tx,rx) = oneshot::channel();
+        self.send.send(WorkerMessage::Stats(tx)).expect("worker task closed");
+        rx.await.expect("worker pool closed")
+    }
+
+    pub async fn memory_usage(&self) -> MemoryUsage {
+        let (tx,rx) = oneshot::channel();
+        self.send.send(WorkerMessage::Memory(tx)).expect("worker task closed");
+        rx.await.expect("worker pool closed")
+    }
+
+    pub fn is_draining(&self) -> bool {
+        self.draining.load(Ordering::Relaxed)
+    }
+
+    #[cfg(feature = "blocking")]
+    pub fn runtime(&self) -> &tokio::runtime::Handle {
+        &self.runtime
+    }
 }
 
 impl Clone for WorkerHandle {
@@ -90,6 +155,9 @@ impl Clone for WorkerHandle {
         Self {
             send: self.send.clone(),
             state: State::Idle,
+            draining: self.draining.clone(),
+            #[cfg(feature = "blocking")]
+            runtime: self.runtime.clone(),
         }
     }
 }
@@ -102,6 +170,8 @@ impl std::fmt::Debug for WorkerHandle {
 
 struct PoolConnection {
     last_hc: Instant,
+    /// random delay added to healthcheck schedule
+    jitter: Duration,
     conn: Connection,
 }
 
@@ -109,42 +179,68 @@ impl PoolConnection {
     fn new(conn: Connection, instant: Instant) -> Self {
         Self {
             last_hc: instant,
+            jitter: Duration::ZERO,
             conn
         }
     }
 
-    fn now(conn: Connection) -> Self {
-        Self {
-            last_hc: Instant::now(),
-            conn
-        }
+    fn should_healthcheck(&self, now: Instant) -> bool {
+        now.saturating_duration_since(self.last_hc) > HALF_MINUTE + self.jitter
     }
 
-    fn should_healthcheck(&self) -> bool {
-        self.last_hc.elapsed() > HALF_MINUTE
-    }
-
-    fn poll_healthcheck(&mut self, cx: &mut Context) -> Poll<Result<()>> {
+    fn poll_healthcheck(&mut self, now: Instant, max_jitter: Duration, cx: &mut Context) -> Poll<Result<()>> {
         let result = ready!(self.conn.poll_ready(cx));
         if result.is_ok() {
-            self.last_hc = Instant::now();
+            self.last_hc = now;
+            self.jitter = jitter(max_jitter);
         }
         Poll::Ready(result)
     }
 }
 
+/// Returns random duration in `0..max`.
+fn jitter(max: Duration) -> Duration {
+    use std::hash::{BuildHasher, RandomState};
+
+    if max.is_zero() {
+        return Duration::ZERO;
+    }
+    // each `RandomState` is seeded differently
+    let random = RandomState::new().hash_one(());
+    Duration::from_nanos(random % max.as_nanos().min(u64::MAX as u128) as u64)
+}
+
 type AcquireSend = oneshot::Sender<Result<Connection>>;
 type AcquireRecv = oneshot::Receiver<Result<Connection>>;
 
+/// Backpressured acquire request.
+struct Acquire {
+    send: AcquireSend,
+    /// `None` if the deadline overflow
+    deadline: Option<Instant>,
+    #[cfg(feature = "adaptive-pool")]
+    queued_at: Instant,
+}
+
+#[allow(clippy::large_enum_variant, reason = "connection is moved right away")]
 enum WorkerMessage {
     Acquire(AcquireSend),
     Release(Connection),
+    UpdateConfig(PoolConfig),
+    Drain(oneshot::Sender<()>),
+    Stats(oneshot::Sender<Vec<ConnectStats>>),
+    Memory(oneshot::Sender<MemoryUsage>),
 }
 
 type ConnectFuture = Pin<Box<dyn Future<Output = Result<Connection>> + Send + Sync + 'static>>;
 
-pub struct WorkerFutureV2 {
+pub struct WorkerFutureV2<C: Clock = TokioClock> {
     config: PoolConfig,
+    /// connections created before this instant are using previous config
+    updated_at: Option<std::time::Instant>,
+    /// pool is draining, notified when all connections are closed
+    drain: Option<Vec<oneshot::Sender<()>>>,
+    clock: C,
     started: Instant,
     #[cfg(feature = "verbose")]
     iter_n: u8,
@@ -158,18 +254,26 @@ pub struct WorkerFutureV2 {
     ///
     /// front queue is the most fresh connection
     conns: VecDeque<PoolConnection>,
-    acquires: VecDeque<AcquireSend>,
+    acquires: VecDeque<Acquire>,
     recv: UnboundedReceiver<WorkerMessage>,
 
     connect_retry: usize,
-    connect_delay: Option<Pin<Box<Sleep>>>,
+    connect_delay: Option<Pin<Box<C::Sleep>>>,
     connecting: Option<ConnectFuture>,
-    healthcheck: Option<PoolConnection>,
+    /// index of connection config used by `connecting`
+    connecting_shard: usize,
+    shards: Shards,
+    /// in progress healthcheck, bounded by `config.healthcheck_concurrency`
+    healthchecks: Vec<PoolConnection>,
+    /// last time surplus idle connection is closed
+    shrunk_at: Instant,
+    #[cfg(feature = "adaptive-pool")]
+    adaptive: Option<Adaptive>,
     closing: Option<Connection>,
-    sleep: Pin<Box<Sleep>>,
+    sleep: Pin<Box<C::Sleep>>,
 }
 
-impl Future for WorkerFutureV2 {
+impl<C: Clock> Future for WorkerFutureV2<C> {
     type Output = ();
 
     fn poll(mut self: Pin<&mut Self>, cx: &mut Context) -> Poll<Self::Output> {
@@ -198,7 +302,7 @@ impl Future for WorkerFutureV2 {
             }
         }
 
-        if let Ready(result) = self.poll_connecting(cx) {
+        if self.should_connect() && let Ready(result) = self.poll_connecting(cx) {
             span!("connect-queue");
             self.send_acquire_queue(result);
             while !self.acquires.is_empty() {
@@ -210,16 +314,27 @@ impl Future for WorkerFutureV2 {
             }
         }
 
-        if let Some(conn) = self.healthcheck.take() {
+        for conn in std::mem::take(&mut self.healthchecks) {
             self.poll_healthcheck(conn, cx);
-            while self.healthcheck.is_none() {
-                match self.conns.iter().rev().position(PoolConnection::should_healthcheck) {
-                    Some(i) => {
-                        let conn = self.conns.swap_remove_back(i).unwrap();
-                        self.poll_healthcheck(conn, cx);
+        }
+
+        // at most `healthcheck_concurrency` idle connections are checked per iteration,
+        // the rest is checked when woken up by the next release or interval
+        for _ in 0..self.config.healthcheck_concurrency {
+            if self.healthchecks.len() >= self.config.healthcheck_concurrency {
+                break;
+            }
+            let now = self.clock.now();
+            match self.conns.iter().rev().position(|conn|conn.should_healthcheck(now)) {
+                Some(i) => {
+                    let i = self.conns.len() - 1 - i;
+                    let mut conn = self.conns.remove(i).unwrap();
+                    if self.config.keepalive {
+                        conn.conn.ping_request();
                     }
-                    None => break,
+                    self.poll_healthcheck(conn, cx);
                 }
+                None => break,
             }
         }
 
@@ -227,16 +342,31 @@ impl Future for WorkerFutureV2 {
             self.poll_close(conn, cx);
         }
 
+        self.expire_acquires();
+
         if let Poll::Ready(()) = self.sleep.as_mut().poll(cx) {
             verbose!("Interval");
+            self.shrink_idle(cx);
             self.reset_interval();
+            // register waker for the new deadline
+            let _ = self.sleep.as_mut().poll(cx);
+        }
+
+        #[cfg(feature = "adaptive-pool")]
+        self.adapt(cx);
+
+        if self.actives == 0 && let Some(drain) = self.drain.as_mut() {
+            verbose!("drained");
+            for send in drain.drain(..) {
+                send.send(()).unwrap_or(());
+            }
         }
 
         verbose!(
             actives=self.actives,
             idle=self.conns.len(),
-            hc=self.healthcheck.is_some() as u8,
-            interval=?{self.sleep.deadline() - Instant::now()}.as_secs(),
+            hc=self.healthchecks.len(),
+            interval=?{self.clock.deadline(&self.sleep) - self.clock.now()}.as_secs(),
             backpressured=self.acquires.len(),
             "polled"
         );
@@ -245,7 +375,7 @@ impl Future for WorkerFutureV2 {
     }
 }
 
-impl WorkerFutureV2 {
+impl<C: Clock> WorkerFutureV2<C> {
     fn poll_incoming_message(&mut self, cx: &mut Context) -> Poll<()> {
         while let Poll::Ready(msg) = self.recv.poll_recv(cx) {
             let Some(msg) = msg else {
@@ -253,15 +383,22 @@ impl WorkerFutureV2 {
             };
 
             match msg {
+                WorkerMessage::Acquire(send) if self.drain.is_some() => {
+                    send.send(Err(PoolDraining.into())).unwrap_or(());
+                },
                 WorkerMessage::Acquire(send) => {
                     span!("acquire");
                     verbose!("Acquire");
 
                     match self.pop_connection(cx) {
-                        Poll::Pending => self.acquires.push_back(send),
-                        Poll::Ready(Ok(PoolConnection { last_hc, conn })) => {
+                        Poll::Pending => self.push_acquire(send),
+                        Poll::Ready(Ok(PoolConnection { last_hc, jitter, conn })) => {
+                            #[cfg(feature = "adaptive-pool")]
+                            if let Some(adaptive) = self.adaptive.as_mut() {
+                                adaptive.record_wait(Duration::ZERO);
+                            }
                             if let Err(Ok(conn)) = send.send(Ok(conn)) {
-                                self.conns.push_back(PoolConnection::new(conn, last_hc));
+                                self.conns.push_back(PoolConnection { last_hc, jitter, conn });
                             }
                         },
                         Poll::Ready(Err(err)) => send.send(Err(err)).unwrap_or(()),
@@ -271,7 +408,30 @@ impl WorkerFutureV2 {
                     span!("release");
                     verbose!("Release");
 
-                    self.healthcheck(conn, cx);
+                    if self.drain.is_some() || conn.is_poisoned() || self.is_stale(&conn) || self.actives > self.max_conn() {
+                        self.close(conn, cx);
+                    } else {
+                        self.healthcheck(conn, cx);
+                    }
+                }
+                WorkerMessage::UpdateConfig(config) => {
+                    span!("update-config");
+                    verbose!("UpdateConfig");
+
+                    self.update_config(config, cx);
+                }
+                WorkerMessage::Drain(send) => {
+                    span!("drain");
+                    verbose!("Drain");
+
+                    self.drain(send, cx);
+                }
+                WorkerMessage::Stats(send) => {
+                    send.send(self.shards.stats()).unwrap_or(());
+                }
+                WorkerMessage::Memory(send) => {
+                    let usage = self.conns.iter().chain(&self.healthchecks).map(|e|e.conn.memory_usage()).sum();
+                    send.send(usage).unwrap_or(());
                 }
             }
         }
@@ -280,10 +440,54 @@ impl WorkerFutureV2 {
     }
 
     fn pop_connection(&mut self, cx: &mut Context) -> Poll<Result<PoolConnection>>{
-        match self.conns.pop_front() {
-            Some(ok) => Poll::Ready(Ok(ok)),
-            None => self.poll_connecting(cx),
+        while let Some(conn) = self.conns.pop_front() {
+            if !self.is_stale(&conn.conn) {
+                return Poll::Ready(Ok(conn));
+            }
+            self.close(conn.conn, cx);
         }
+        self.poll_connecting(cx)
+    }
+
+    fn update_config(&mut self, config: PoolConfig, cx: &mut Context) {
+        self.shards = Shards::new(&config);
+        #[cfg(feature = "adaptive-pool")]
+        {
+            self.adaptive = config.adaptive.clone().map(|e|Adaptive::new(e, self.clock.now()));
+        }
+        self.config = config;
+        self.updated_at = Some(std::time::Instant::now());
+
+        // in flight connect is using previous config
+        self.connecting = None;
+        self.connect_delay = None;
+        self.connect_retry = 0;
+
+        for conn in std::mem::take(&mut self.conns) {
+            self.close(conn.conn, cx);
+        }
+
+        self.reset_interval();
+    }
+
+    fn drain(&mut self, send: oneshot::Sender<()>, cx: &mut Context) {
+        self.drain.get_or_insert_default().push(send);
+
+        self.connecting = None;
+        self.connect_delay = None;
+        self.connect_retry = 0;
+
+        for Acquire { send, .. } in std::mem::take(&mut self.acquires) {
+            send.send(Err(PoolDraining.into())).unwrap_or(());
+        }
+
+        for conn in std::mem::take(&mut self.conns) {
+            self.close(conn.conn, cx);
+        }
+    }
+
+    fn is_stale(&self, conn: &Connection) -> bool {
+        self.updated_at.is_some_and(|at|conn.connected_at() < at)
     }
 
     /// `Ready` returns is always with retry polled
@@ -294,28 +498,33 @@ impl WorkerFutureV2 {
             self.connect_delay.take();
         }
 
-        if self.connecting.is_none() && self.actives >= self.config.max_conn {
+        if self.connecting.is_none() && self.actives >= self.max_conn() {
             // wait for `Release`
             verbose!("new connection backpressured");
             return Poll::Pending;
         }
 
-        let poll = self
-            .connecting
-            .get_or_insert_with(||Box::pin(Connection::connect_with(self.config.conn.clone())))
-            .as_mut()
-            .poll(cx);
+        if self.connecting.is_none() {
+            let shard = self.shards.next(&self.config);
+            let mut conn = self.config.conns[shard].0.clone();
+            if !self.config.stmt_cache {
+                conn = conn.statement_cache(false);
+            }
+            self.connecting_shard = shard;
+            self.connecting = Some(Box::pin(Connection::connect_with(conn)));
+        }
 
         // wait for `Connection::connect`
-        let result = ready!(poll);
+        let result = ready!(self.connecting.as_mut().unwrap().as_mut().poll(cx));
         self.connecting.take();
+        self.shards.record(self.connecting_shard, result.is_ok());
 
         match result {
             Ok(conn) => {
                 self.connect_retry = 0;
                 self.actives += 1;
                 verbose!(actives=self.actives,"new-connection");
-                Poll::Ready(Ok(PoolConnection::now(conn)))
+                Poll::Ready(Ok(PoolConnection::new(conn, self.clock.now())))
             },
             Err(err) => {
                 #[cfg(feature = "log")]
@@ -323,7 +532,8 @@ impl WorkerFutureV2 {
 
                 if self.connect_retry < self.config.max_retry {
                     self.connect_retry += 1;
-                    self.connect_delay = Some(Box::pin(sleep(self.config.retry_delay)));
+                    let deadline = self.clock.now() + self.config.retry_delay;
+                    self.connect_delay = Some(Box::pin(self.clock.sleep_until(deadline)));
                     // wait for `connect_delay: Sleep`
                     Poll::Pending
                 } else {
@@ -336,16 +546,14 @@ impl WorkerFutureV2 {
     }
 
     fn healthcheck(&mut self, conn: Connection, cx: &mut Context) {
-        if let Some(conn) = self.healthcheck.take() {
-            self.poll_healthcheck(conn, cx);
-        }
         self.poll_healthcheck(PoolConnection::new(conn, self.started), cx);
     }
 
     fn poll_healthcheck(&mut self, mut conn: PoolConnection, cx: &mut Context) {
-        match conn.poll_healthcheck(cx) {
-            Pending if self.healthcheck.is_none() => self.healthcheck = Some(conn),
+        match conn.poll_healthcheck(self.clock.now(), self.config.healthcheck_jitter, cx) {
+            Pending if self.healthchecks.len() < self.config.healthcheck_concurrency => self.healthchecks.push(conn),
             Pending => self.conns.push_back(conn),
+            Ready(Ok(())) if self.drain.is_some() || self.is_stale(&conn.conn) => self.close(conn.conn, cx),
             Ready(Ok(())) if !self.acquires.is_empty() => self.send_acquire_queue(Ok(conn)),
             Ready(Ok(())) => self.conns.push_front(conn),
             Ready(Err(_err)) => {
@@ -356,9 +564,69 @@ impl WorkerFutureV2 {
         }
     }
 
+    /// Returns `true` if connection is in progress, or idle connection is below `min_idle`.
+    fn should_connect(&self) -> bool {
+        self.connecting.is_some()
+            || self.connect_delay.is_some()
+            || (self.drain.is_none() && self.conns.len() + self.healthchecks.len() < self.config.min_idle)
+    }
+
+    /// Close one surplus idle connection for each interval.
+    fn shrink_idle(&mut self, cx: &mut Context) {
+        let now = self.clock.now();
+        let max_idle = self.config.max_idle.max(self.config.min_idle);
+        if self.conns.len() <= max_idle {
+            self.shrunk_at = now;
+            return;
+        }
+        if now.saturating_duration_since(self.shrunk_at) < self.config.interval {
+            return;
+        }
+        self.shrunk_at = now;
+        // back of the queue is the least recently used
+        if let Some(conn) = self.conns.pop_back() {
+            verbose!(idle=self.conns.len(),"shrink");
+            self.close(conn.conn, cx);
+        }
+    }
+
+    fn push_acquire(&mut self, send: AcquireSend) {
+        let deadline = self.clock.now().checked_add(self.config.acquire_timeout);
+        if let Some(deadline) = deadline && deadline < self.clock.deadline(&self.sleep) {
+            self.clock.reset(self.sleep.as_mut(), deadline);
+        }
+        self.acquires.push_back(Acquire {
+            send,
+            deadline,
+            #[cfg(feature = "adaptive-pool")]
+            queued_at: self.clock.now(),
+        });
+    }
+
+    /// Returns [`PoolTimedOut`] error to acquire requests that exceed its deadline.
+    fn expire_acquires(&mut self) {
+        let now = self.clock.now();
+        let mut i = 0;
+        while i < self.acquires.len() {
+            if self.acquires[i].deadline.is_some_and(|deadline|deadline <= now) {
+                let Acquire { send, .. } = self.acquires.remove(i).unwrap();
+                verbose!("acquire timed out");
+                send.send(Err(PoolTimedOut.into())).unwrap_or(());
+            } else {
+                i += 1;
+            }
+        }
+    }
+
     fn send_acquire_queue(&mut self, result: Result<PoolConnection>) {
         match (self.acquires.pop_front(), result) {
-            (Some(send), result) => self.send_acquire(send, result),
+            (Some(acquire), result) => {
+                #[cfg(feature = "adaptive-pool")]
+                if let Some(adaptive) = self.adaptive.as_mut() {
+                    adaptive.record_wait(self.clock.now().saturating_duration_since(acquire.queued_at));
+                }
+                self.send_acquire(acquire.send, result)
+            },
             (None, Ok(conn)) => self.conns.push_back(conn),
             (None, Err(_)) => {}
         }
@@ -366,26 +634,68 @@ impl WorkerFutureV2 {
 
     fn send_acquire(&mut self, send: AcquireSend, result: Result<PoolConnection>) {
         match result {
-            Ok(PoolConnection { last_hc, conn }) => {
+            Ok(PoolConnection { last_hc, jitter, conn }) => {
                 let Err(Ok(conn)) = send.send(Ok(conn)) else {
                     return;
                 };
                 if self.acquires.is_empty() {
-                    self.conns.push_front(PoolConnection::new(conn, last_hc));
+                    self.conns.push_front(PoolConnection { last_hc, jitter, conn });
                 } else {
-                    self.send_acquire_queue(Ok(PoolConnection::new(conn, last_hc)));
+                    self.send_acquire_queue(Ok(PoolConnection { last_hc, jitter, conn }));
                 }
             },
             Err(err) => send.send(Err(err)).unwrap_or(()),
         }
     }
 
+    /// Returns the max number of connection, which is the adaptive target if enabled.
+    fn max_conn(&self) -> usize {
+        #[cfg(feature = "adaptive-pool")]
+        if let Some(adaptive) = self.adaptive.as_ref() {
+            return adaptive.target().min(self.config.max_conn);
+        }
+        self.config.max_conn
+    }
+
+    /// Observe utilization and evaluate adaptive target, surplus idle connections are
+    /// closed when the target shrink.
+    #[cfg(feature = "adaptive-pool")]
+    fn adapt(&mut self, cx: &mut Context) {
+        let now = self.clock.now();
+        let in_use = self.actives.saturating_sub(self.conns.len() + self.healthchecks.len());
+        let Some(adaptive) = self.adaptive.as_mut() else {
+            return;
+        };
+
+        adaptive.record_in_use(in_use);
+        if adaptive.tick(now, self.config.max_conn) {
+            verbose!(target=adaptive.target(),"adaptive");
+            while self.actives > self.max_conn() && let Some(conn) = self.conns.pop_back() {
+                self.close(conn.conn, cx);
+            }
+        }
+
+        // wake up to evaluate the next window
+        let adaptive = self.adaptive.as_ref().unwrap();
+        if adaptive.window_end() < self.clock.deadline(&self.sleep) {
+            self.clock.reset(self.sleep.as_mut(), adaptive.window_end());
+            let _ = self.sleep.as_mut().poll(cx);
+        }
+    }
+
     fn reset_interval(&mut self) {
+        let now = self.clock.now();
         let least_time_hc = self.conns.iter().fold(self.config.interval, |acc, n| {
-            (self.config.interval.saturating_sub(n.last_hc.elapsed())).min(acc)
+            (self.config.interval.saturating_sub(now.saturating_duration_since(n.last_hc))).min(acc)
         });
 
-        self.sleep.as_mut().reset(Instant::now() + least_time_hc);
+        // wake up to expire pending acquire
+        let deadline = self.acquires
+            .iter()
+            .filter_map(|e|e.deadline)
+            .fold(now + least_time_hc, Instant::min);
+
+        self.clock.reset(self.sleep.as_mut(), deadline);
     }
 
     fn close(&mut self, conn: Connection, cx: &mut Context) {
@@ -416,3 +726,139 @@ impl WorkerFutureV2 {
     }
 }
 
+
+#[cfg(test)]
+mod test {
+    use std::{
+        pin::Pin,
+        sync::{Arc, Mutex},
+        task::{Context, Poll, Waker},
+        time::Duration,
+    };
+    use tokio::time::Instant;
+
+    use super::{Clock, WorkerHandle};
+    use crate::{Config, PoolConfig};
+
+    /// Clock which only move forward when told to.
+    #[derive(Clone)]
+    struct ManualClock {
+        base: Instant,
+        offset: Arc<Mutex<Duration>>,
+    }
+
+    impl ManualClock {
+        fn advance(&self, duration: Duration) {
+            *self.offset.lock().unwrap() += duration;
+        }
+    }
+
+    struct ManualSleep {
+        clock: ManualClock,
+        deadline: Instant,
+    }
+
+    impl Future for ManualSleep {
+        type Output = ();
+
+        fn poll(self: Pin<&mut Self>, _: &mut Context) -> Poll<()> {
+            match self.clock.now() >= self.deadline {
+                true => Poll::Ready(()),
+                false => Poll::Pending,
+            }
+        }
+    }
+
+    impl Clock for ManualClock {
+        type Sleep = ManualSleep;
+
+        fn now(&self) -> Instant {
+            self.base + *self.offset.lock().unwrap()
+        }
+
+        fn sleep_until(&self, deadline: Instant) -> Self::Sleep {
+            ManualSleep { clock: self.clone(), deadline }
+        }
+
+        fn reset(&self, mut sleep: Pin<&mut Self::Sleep>, deadline: Instant) {
+            sleep.deadline = deadline;
+        }
+
+        fn deadline(&self, sleep: &Self::Sleep) -> Instant {
+            sleep.deadline
+        }
+    }
+
+    #[tokio::test]
+    async fn interval_follows_clock() {
+        let clock = ManualClock { base: Instant::now(), offset: <_>::default() };
+        let config = PoolConfig::from_env()
+            .with_connection(Config::parse("postgres://postgres:@127.0.0.1:1/postgres").unwrap());
+        let interval = config.interval;
+
+        let (_handle, mut worker) = WorkerHandle::with_clock(config, clock.clone());
+        let mut cx = Context::from_waker(Waker::noop());
+
+        assert!(Pin::new(&mut worker).poll(&mut cx).is_pending());
+        assert_eq!(clock.deadline(&worker.sleep), clock.now() + interval);
+
+        clock.advance(interval / 2);
+        assert!(Pin::new(&mut worker).poll(&mut cx).is_pending());
+        assert_eq!(clock.deadline(&worker.sleep), clock.now() + interval / 2);
+
+        clock.advance(interval / 2);
+        assert!(Pin::new(&mut worker).poll(&mut cx).is_pending());
+        assert_eq!(clock.deadline(&worker.sleep), clock.now() + interval);
+    }
+
+    #[tokio::test]
+    async fn acquire_timeout() {
+        let clock = ManualClock { base: Instant::now(), offset: <_>::default() };
+        let config = PoolConfig::from_env()
+            .with_connection(Config::parse("postgres://postgres:@127.0.0.1:1/postgres").unwrap())
+            .acquire_timeout(Duration::from_secs(1));
+
+        let (mut handle, mut worker) = WorkerHandle::with_clock(config, clock.clone());
+        let mut cx = Context::from_waker(Waker::noop());
+
+        assert!(handle.poll_acquire(&mut cx).is_pending());
+        assert!(Pin::new(&mut worker).poll(&mut cx).is_pending());
+        assert_eq!(worker.acquires.len(), 1);
+        assert_eq!(clock.deadline(&worker.sleep), clock.now() + Duration::from_secs(1));
+
+        // connect retry is still in progress
+        clock.advance(Duration::from_secs(1));
+        assert!(Pin::new(&mut worker).poll(&mut cx).is_pending());
+        assert!(worker.acquires.is_empty());
+
+        let Poll::Ready(Err(err)) = handle.poll_acquire(&mut cx) else {
+            panic!("acquire should timed out");
+        };
+        assert!(matches!(err.kind(), crate::error::ErrorKind::PoolTimedOut(_)));
+    }
+
+    #[test]
+    fn jitter_is_bounded() {
+        let max = Duration::from_millis(10);
+        let jitters = (0..64).map(|_|super::jitter(max)).collect::<Vec<_>>();
+        assert!(jitters.iter().all(|e|*e < max));
+        // spread instead of the same schedule
+        assert!(jitters.iter().any(|e|*e != jitters[0]));
+        assert_eq!(super::jitter(Duration::ZERO), Duration::ZERO);
+    }
+
+    #[tokio::test]
+    async fn drain_rejects_acquire() {
+        let config = PoolConfig::from_env()
+            .with_connection(Config::parse("postgres://postgres:@127.0.0.1:1/postgres").unwrap());
+
+        let (mut handle, worker) = WorkerHandle::new(config);
+        tokio::spawn(worker);
+
+        handle.drain().await;
+        assert!(handle.is_draining());
+
+        let err = std::future::poll_fn(|cx|handle.poll_acquire(cx)).await.unwrap_err();
+        assert!(matches!(err.kind(), crate::error::ErrorKind::PoolDraining(_)));
+    }
+}
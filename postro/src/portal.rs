@@ -0,0 +1,235 @@
+//! Portal that is resumed on demand.
+use std::marker::PhantomData;
+
+use crate::{
+    Result, Row,
+    common::{leak, unit_error},
+    encode::Encoded,
+    executor::Executor,
+    fetch::{StreamMap, bind, prepare, prepare_params},
+    postgres::{BackendMessage, backend, frontend},
+    sql::Sql,
+    statement::PortalName,
+    transport::{PgTransport, PgTransportExt},
+};
+
+/// A named portal which rows are fetched in pages on demand.
+///
+/// Unlike paged [`Query::fetch`][1], the connection is not held between pages,
+/// so other queries can run in between, e.g: to back an interactive paging ui.
+///
+/// Portal only lives until the end of the transaction, so it must be opened and
+/// resumed within the same [`Transaction`][2]. Portal is closed when all rows are
+/// fetched or explicitly with [`close`][SuspendedPortal::close], otherwise it is
+/// closed when the transaction ends.
+///
+/// Created via [`Query::fetch_portal`][3].
+///
+/// ```no_run
+/// # async fn test(mut conn: postro::Connection) -> postro::Result<()> {
+/// let mut tx = postro::begin(&mut conn).await?;
+///
+/// let mut portal = postro::query_as::<_, _, (i32,)>("SELECT * FROM foo", &mut tx)
+///     .fetch_portal(20)
+///     .await?;
+///
+/// let first = portal.fetch_next(&mut tx).await?;
+/// let second = portal.fetch_next(&mut tx).await?;
+///
+/// portal.close(&mut tx).await?;
+/// # Ok(())
+/// # }
+/// ```
+///
+/// [1]: crate::query::Query::fetch
+/// [2]: crate::transaction::Transaction
+/// [3]: crate::query::Query::fetch_portal
+pub struct SuspendedPortal<M> {
+    portal: PortalName,
+    /// `None` if statement returns no rows
+    row: Option<Row>,
+    page_size: u32,
+    complete: bool,
+    _p: PhantomData<M>,
+}
+
+impl<M: StreamMap> SuspendedPortal<M> {
+    /// Bind the statement to a new named portal without executing it.
+    pub(crate) async fn open<SQL, Exe>(
+        mut sql: SQL,
+        mut params: Vec<Encoded<'_>>,
+        no_cache: bool,
+        page_size: u32,
+        exe: Exe,
+    ) -> Result<Self>
+    where
+        SQL: Sql,
+        Exe: Executor,
+    {
+        prepare_params(&mut sql, &mut params)?;
+        let mut io = exe.connection().await?;
+        Self::open_inner(&sql, params, no_cache, page_size, &mut io)
+            .await
+            // error position is relative to the trimmed sql sent in `prepare`
+            .map_err(|err| err.with_sql(sql.sql().trim()))
+    }
+
+    async fn open_inner<IO: PgTransport>(
+        sql: &impl Sql,
+        mut params: Vec<Encoded<'_>>,
+        no_cache: bool,
+        page_size: u32,
+        mut io: IO,
+    ) -> Result<Self> {
+        let portal = PortalName::next();
+        let data = prepare(sql, &params, no_cache, &mut io);
+        bind(&portal, &data.stmt, &mut params, M::FORMATS, &mut io);
+        io.send(frontend::Flush);
+        io.flush().await?;
+
+        if !data.cache_hit {
+            io.recv::<backend::ParseComplete>().await?;
+            io.add_stmt(data.sqlid, data.stmt.clone());
+        }
+        io.recv::<backend::BindComplete>().await?;
+
+        let row = match io.recv().await? {
+            BackendMessage::RowDescription(rd) => Some(Row::new(rd.body).with_codecs(io.codecs())),
+            BackendMessage::NoData(_) => None,
+            f => {
+                io.poison();
+                Err(f.unexpected("portal description"))?
+            },
+        };
+
+        io.send(frontend::Sync);
+        io.flush().await?;
+        let ready = io.recv::<backend::ReadyForQuery>().await?;
+
+        // outside transaction block, the portal is closed by the implicit commit
+        if ready.tx_status == b'I' {
+            return Err(PortalNotInTransaction.into());
+        }
+
+        Ok(Self { portal, row, page_size, complete: false, _p: PhantomData })
+    }
+
+    /// Returns the portal name.
+    pub fn name(&self) -> &str {
+        self.portal.as_str()
+    }
+
+    /// Returns the maximum rows fetched per page.
+    pub fn page_size(&self) -> u32 {
+        self.page_size
+    }
+
+    /// Returns `true` if all rows is fetched and the portal is closed.
+    pub fn is_complete(&self) -> bool {
+        self.complete
+    }
+
+    /// Fetch the next page of rows.
+    ///
+    /// Returns empty [`Vec`] if all rows is fetched.
+    ///
+    /// If mapping a row returns error, the rest of the page is discarded.
+    pub async fn fetch_next<Exe: Executor>(&mut self, exe: Exe) -> Result<Vec<M::Output>> {
+        if self.complete {
+            return Ok(Vec::new());
+        }
+
+        let mut io = exe.connection().await?;
+        io.send(frontend::Execute {
+            portal_name: self.portal.as_str(),
+            max_row: self.page_size,
+        });
+        io.send(frontend::Flush);
+        io.flush().await?;
+
+        let mut rows = Vec::with_capacity(self.page_size as _);
+        let mut result = Ok(());
+
+        loop {
+            use BackendMessage::*;
+            match io.recv().await? {
+                DataRow(dr) => {
+                    let Some(row) = self.row.as_ref() else {
+                        io.poison();
+                        Err(DataRow(dr).unexpected("fetching portal rows"))?
+                    };
+                    if result.is_ok() {
+                        match M::map(row.inner_clone(dr.body)) {
+                            Ok(ok) => rows.push(ok),
+                            Err(err) => result = Err(err),
+                        }
+                    }
+                },
+                PortalSuspended(_) => break,
+                CommandComplete(_) | EmptyQueryResponse(_) => {
+                    self.complete = true;
+                    break;
+                },
+                f => {
+                    io.poison();
+                    Err(f.unexpected("fetching portal rows"))?
+                },
+            }
+        }
+
+        if self.complete {
+            close(b'P', self.portal.as_str(), &mut io).await?;
+        } else {
+            io.send(frontend::Sync);
+            io.flush().await?;
+            io.recv::<backend::ReadyForQuery>().await?;
+        }
+
+        result.map(|_| rows)
+    }
+
+    /// Close the portal, releasing its resources in the server.
+    ///
+    /// Does nothing if all rows is already fetched.
+    pub async fn close<Exe: Executor>(mut self, exe: Exe) -> Result<()> {
+        if std::mem::replace(&mut self.complete, true) {
+            return Ok(());
+        }
+        close(b'P', self.portal.as_str(), exe.connection().await?).await
+    }
+}
+
+impl<M> Drop for SuspendedPortal<M> {
+    fn drop(&mut self) {
+        if !self.complete {
+            leak!("portal {:?} dropped without close, it is held until the transaction ends", self.portal.as_str());
+        }
+    }
+}
+
+/// Close prepared statement (`b'S'`) or portal (`b'P'`) by name.
+///
+/// Closing a nonexistent name is not an error.
+pub(crate) async fn close(variant: u8, name: &str, mut io: impl PgTransport) -> Result<()> {
+    io.send(frontend::Close { variant, name });
+    io.send(frontend::Sync);
+    io.flush().await?;
+    io.recv::<backend::CloseComplete>().await?;
+    io.recv::<backend::ReadyForQuery>().await?;
+    Ok(())
+}
+
+impl<M> std::fmt::Debug for SuspendedPortal<M> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("SuspendedPortal")
+            .field("portal", &self.portal)
+            .field("page_size", &self.page_size)
+            .field("complete", &self.complete)
+            .finish_non_exhaustive()
+    }
+}
+
+unit_error! {
+    /// An error when opening [`SuspendedPortal`] outside of transaction.
+    pub struct PortalNotInTransaction("portal can only be suspended inside a transaction");
+}
@@ -26,6 +26,16 @@ pub enum BackendMessage {
     CloseComplete(CloseComplete),
     /// Identifies the message as a command-completed response.
     CommandComplete(CommandComplete),
+    /// Identifies the message as a Start Copy Both response.
+    CopyBothResponse(CopyBothResponse),
+    /// Identifies the message as COPY data.
+    CopyData(CopyData),
+    /// Identifies the message as a COPY-complete indicator.
+    CopyDone(CopyDone),
+    /// Identifies the message as a Start Copy In response.
+    CopyInResponse(CopyInResponse),
+    /// Identifies the message as a Start Copy Out response.
+    CopyOutResponse(CopyOutResponse),
     /// Identifies the message as a data row.
     DataRow(DataRow),
     /// Identifies the message as an error.
@@ -38,6 +48,8 @@ pub enum BackendMessage {
     NoData(NoData),
     /// Identifies the message as a notice.
     NoticeResponse(NoticeResponse),
+    /// Identifies the message as a notification response.
+    NotificationResponse(NotificationResponse),
     /// Identifies the message as a parameter description.
     ParameterDescription(ParameterDescription),
     /// Identifies the message as a run-time parameter status report
@@ -71,6 +83,11 @@ macro_rules! match_backend {
                     _ => "Unknown",
                 }
             }
+
+            /// Returns `true` if message type is known.
+            pub const fn is_known(msgtype: u8) -> bool {
+                matches!(msgtype, $($name::MSGTYPE)|*)
+            }
         }
         impl BackendProtocol for BackendMessage {
             fn decode(msgtype: u8, body: Bytes) -> Result<Self, ProtocolError> {
@@ -98,12 +115,18 @@ match_backend! {
     BindComplete,
     CloseComplete,
     CommandComplete,
+    CopyBothResponse,
+    CopyData,
+    CopyDone,
+    CopyInResponse,
+    CopyOutResponse,
     DataRow,
     ErrorResponse,
     EmptyQueryResponse,
     NegotiateProtocolVersion,
     NoData,
     NoticeResponse,
+    NotificationResponse,
     ParameterDescription,
     ParameterStatus,
     ParseComplete,
@@ -218,7 +241,7 @@ impl BackendProtocol for BackendKeyData {
 }
 
 /// Identifies the message as a run-time parameter status report.
-#[derive(Debug)]
+#[derive(Debug, Clone)]
 pub struct ParameterStatus {
     /// The name of the run-time parameter being reported.
     pub name: ByteStr,
@@ -238,6 +261,30 @@ impl BackendProtocol for ParameterStatus {
     }
 }
 
+/// Identifies the message as a notification response.
+#[derive(Debug)]
+pub struct NotificationResponse {
+    /// The process ID of the notifying backend process.
+    pub process_id: u32,
+    /// The name of the channel that the notify has been raised on.
+    pub channel: ByteStr,
+    /// The “payload” string passed from the notifying process.
+    pub payload: ByteStr,
+}
+
+msgtype!(NotificationResponse, b'A');
+
+impl BackendProtocol for NotificationResponse {
+    fn decode(msgtype: u8, mut body: Bytes) -> Result<Self,ProtocolError> {
+        assert_msgtype!(msgtype);
+        Ok(Self {
+            process_id: body.get_u32(),
+            channel: body.get_nul_bytestr()?,
+            payload: body.get_nul_bytestr()?,
+        })
+    }
+}
+
 /// Identifies the message as a notice.
 pub struct NoticeResponse {
     /// Raw message body.
@@ -408,6 +455,122 @@ impl BackendProtocol for CommandComplete {
     }
 }
 
+/// Identifies the message as a Start Copy In response.
+///
+/// The frontend must now send copy-in data.
+#[derive(Debug)]
+pub struct CopyInResponse {
+    /// 0 indicates the overall COPY format is textual (rows separated by newlines,
+    /// columns separated by separator characters, etc.). 1 indicates the overall copy
+    /// format is binary (similar to DataRow format).
+    pub format: u8,
+    /// The number of columns in the data to be copied.
+    pub columns: u16,
+    /// Raw buffer for column formats.
+    ///
+    /// For each column, there is the following:
+    ///
+    /// - `Int16` The format codes to be used for each column. Each must presently be
+    ///   zero (text) or one (binary). All must be zero if the overall copy format is textual.
+    pub formats: Bytes,
+}
+
+msgtype!(CopyInResponse, b'G');
+
+impl BackendProtocol for CopyInResponse {
+    fn decode(msgtype: u8, mut body: Bytes) -> Result<Self, ProtocolError> {
+        assert_msgtype!(msgtype);
+        Ok(Self {
+            format: body.get_u8(),
+            columns: body.get_u16(),
+            formats: body,
+        })
+    }
+}
+
+/// Identifies the message as a Start Copy Out response.
+///
+/// This message will be followed by copy-out data.
+#[derive(Debug)]
+pub struct CopyOutResponse {
+    /// 0 indicates the overall COPY format is textual (rows separated by newlines,
+    /// columns separated by separator characters, etc.). 1 indicates the overall copy
+    /// format is binary (similar to DataRow format).
+    pub format: u8,
+    /// The number of columns in the data to be copied.
+    pub columns: u16,
+    /// Raw buffer for column formats.
+    ///
+    /// For each column, there is the following:
+    ///
+    /// - `Int16` The format codes to be used for each column. Each must presently be
+    ///   zero (text) or one (binary). All must be zero if the overall copy format is textual.
+    pub formats: Bytes,
+}
+
+msgtype!(CopyOutResponse, b'H');
+
+impl BackendProtocol for CopyOutResponse {
+    fn decode(msgtype: u8, mut body: Bytes) -> Result<Self, ProtocolError> {
+        assert_msgtype!(msgtype);
+        Ok(Self {
+            format: body.get_u8(),
+            columns: body.get_u16(),
+            formats: body,
+        })
+    }
+}
+
+/// Identifies the message as a Start Copy Both response.
+///
+/// This message is used only for Streaming Replication.
+#[derive(Debug)]
+pub struct CopyBothResponse {
+    /// 0 indicates the overall COPY format is textual (rows separated by newlines,
+    /// columns separated by separator characters, etc.). 1 indicates the overall copy
+    /// format is binary (similar to DataRow format).
+    pub format: u8,
+    /// The number of columns in the data to be copied.
+    pub columns: u16,
+    /// Raw buffer for column formats.
+    ///
+    /// For each column, there is the following:
+    ///
+    /// - `Int16` The format codes to be used for each column. Each must presently be
+    ///   zero (text) or one (binary). All must be zero if the overall copy format is textual.
+    pub formats: Bytes,
+}
+
+msgtype!(CopyBothResponse, b'W');
+
+impl BackendProtocol for CopyBothResponse {
+    fn decode(msgtype: u8, mut body: Bytes) -> Result<Self, ProtocolError> {
+        assert_msgtype!(msgtype);
+        Ok(Self {
+            format: body.get_u8(),
+            columns: body.get_u16(),
+            formats: body,
+        })
+    }
+}
+
+/// Identifies the message as COPY data.
+pub struct CopyData {
+    /// Data that forms part of a COPY data stream.
+    ///
+    /// Messages sent from the backend will always correspond to single data rows.
+    pub data: Bytes,
+}
+
+msgtype!(CopyData, b'd');
+
+impl BackendProtocol for CopyData {
+    fn decode(msgtype: u8, body: Bytes) -> Result<Self, ProtocolError> {
+        assert_msgtype!(msgtype);
+        Ok(Self { data: body })
+    }
+}
+
 /// Identifies the message as a protocol version negotiation message.
 #[derive(Debug)]
 pub struct NegotiateProtocolVersion {
@@ -508,6 +671,9 @@ unit_msg! {
     /// Identifies the message as a Close-complete indicator.
     struct CloseComplete, b'3';
 
+    /// Identifies the message as a COPY-complete indicator.
+    struct CopyDone, b'c';
+
     /// Identifies the message as a response to an empty query string.
     ///
     /// This substitutes for CommandComplete.
@@ -557,6 +723,14 @@ impl std::fmt::Debug for RowDescription {
     }
 }
 
+impl std::fmt::Debug for CopyData {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("CopyData")
+            .field("data", &"<BINARY>")
+            .finish()
+    }
+}
+
 impl std::fmt::Debug for DataRow {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
         f.debug_struct("DataRow")
@@ -63,6 +63,43 @@ pub struct Startup<'a> {
     ///
     /// Value can be true, false, or database, and the default is false.
     pub replication: Option<&'a str>,
+    /// Sets the client-side encoding (character set).
+    pub client_encoding: Option<&'a str>,
+    /// Sets the application name reported in `pg_stat_activity`.
+    pub application_name: Option<&'a str>,
+    /// Other run-time parameters, as name and value pairs.
+    pub params: Vec<(&'a str, &'a str)>,
+}
+
+/// Postgres CancelRequest frontend message
+///
+/// Sent via new connection instead of the [`Startup`] message, thus [`CancelRequest`]
+/// does not implement [`FrontendProtocol`].
+///
+/// To write cancel request message, use [`CancelRequest::write`].
+#[derive(Debug)]
+pub struct CancelRequest {
+    /// The process ID of the target backend.
+    pub process_id: u32,
+    /// The secret key for the target backend.
+    pub secret_key: u32,
+}
+
+impl CancelRequest {
+    pub fn write(self, buf: &mut BytesMut) {
+        // Int32(16)
+        // Length of message contents in bytes, including self.
+        buf.put_u32(16);
+
+        // Int32(80877102)
+        // The cancel request code. The value is chosen to contain 1234 in the most significant 16 bits,
+        // and 5678 in the least significant 16 bits. (To avoid confusion, this code must not be the same
+        // as any protocol version number.)
+        buf.put_u32(80_877_102);
+
+        buf.put_u32(self.process_id);
+        buf.put_u32(self.secret_key);
+    }
 }
 
 impl Startup<'_> {
@@ -116,6 +153,21 @@ impl Startup<'_> {
         // Such settings will be applied during backend start
         // (after parsing the command-line arguments if any) and will act as session defaults.
 
+        if let Some(encoding) = self.client_encoding {
+            buf.put_nul_string("client_encoding");
+            buf.put_nul_string(encoding);
+        }
+
+        if let Some(name) = self.application_name {
+            buf.put_nul_string("application_name");
+            buf.put_nul_string(name);
+        }
+
+        for (name, value) in self.params {
+            buf.put_nul_string(name);
+            buf.put_nul_string(value);
+        }
+
         // A zero byte is required as a terminator after the last name/value pair.
         buf.put_u8(b'\0');
 
@@ -152,6 +204,27 @@ impl FrontendProtocol for PasswordMessage<'_> {
     }
 }
 
+/// Identifies the message as a GSSAPI or SSPI response.
+///
+/// Note that this is also used for SASL and password response messages. The exact
+/// message type can be deduced from the context.
+pub struct GSSResponse<'a> {
+    /// GSSAPI/SSPI specific message data.
+    pub data: &'a [u8],
+}
+
+impl FrontendProtocol for GSSResponse<'_> {
+    const MSGTYPE: u8 = b'p';
+
+    fn size_hint(&self) -> u32 {
+        self.data.len().to_u32()
+    }
+
+    fn encode(self, mut buf: impl BufMut) {
+        buf.put_slice(self.data);
+    }
+}
+
 /// Identifies the message as a simple query
 #[derive(Debug)]
 pub struct Query<'a> {
@@ -393,6 +466,57 @@ impl FrontendProtocol for Describe<'_> {
     }
 }
 
+/// Identifies the message as COPY data.
+pub struct CopyData<'a> {
+    /// Data that forms part of a COPY data stream.
+    ///
+    /// Messages sent from the frontend may divide the data stream arbitrarily.
+    pub data: &'a [u8],
+}
+
+impl FrontendProtocol for CopyData<'_> {
+    const MSGTYPE: u8 = b'd';
+
+    fn size_hint(&self) -> u32 {
+        self.data.len().to_u32()
+    }
+
+    fn encode(self, mut buf: impl BufMut) {
+        buf.put_slice(self.data);
+    }
+}
+
+/// Identifies the message as a COPY-complete indicator.
+#[derive(Debug)]
+pub struct CopyDone;
+
+impl FrontendProtocol for CopyDone {
+    const MSGTYPE: u8 = b'c';
+
+    fn size_hint(&self) -> u32 { 0 }
+
+    fn encode(self, _: impl BufMut) { }
+}
+
+/// Identifies the message as a COPY-failure indicator.
+#[derive(Debug)]
+pub struct CopyFail<'a> {
+    /// An error message to report as the cause of failure.
+    pub message: &'a str,
+}
+
+impl FrontendProtocol for CopyFail<'_> {
+    const MSGTYPE: u8 = b'f';
+
+    fn size_hint(&self) -> u32 {
+        self.message.nul_string_len()
+    }
+
+    fn encode(self, mut buf: impl BufMut) {
+        buf.put_nul_string(self.message);
+    }
+}
+
 /// Identifies the message as a termination.
 #[derive(Debug)]
 pub struct Terminate;
@@ -407,6 +531,14 @@ impl FrontendProtocol for Terminate {
 
 // CUSTOM DEBUG
 
+impl fmt::Debug for CopyData<'_> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.debug_struct("CopyData")
+            .field("len", &self.data.len())
+            .finish()
+    }
+}
+
 impl fmt::Debug for Describe<'_> {
     fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
         f.debug_struct("Describe")
@@ -444,6 +576,14 @@ impl<ParamFmts: fmt::Debug, Params: fmt::Debug, ResultFmts: fmt::Debug> fmt::Deb
     }
 }
 
+impl fmt::Debug for GSSResponse<'_> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.debug_struct("GSSResponse")
+            .field("data", &"<REDACTED>")
+            .finish()
+    }
+}
+
 impl fmt::Debug for PasswordMessage<'_> {
     fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
         f.debug_struct("PasswordMessage")
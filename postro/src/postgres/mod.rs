@@ -39,11 +39,15 @@ pub mod backend;
 
 mod notice;
 mod error;
+mod sqlstate;
 
-pub use pg_type::{Oid, PgType};
+pub use pg_type::{Oid, PgType, type_name};
+pub(crate) use pg_type::suggest_rust_type;
 pub use pg_format::PgFormat;
 
 pub use frontend::FrontendProtocol;
 pub use backend::{BackendMessage, BackendProtocol, ErrorResponse, NoticeResponse};
 pub use error::ProtocolError;
+pub use notice::DatabaseError;
+pub use sqlstate::SqlState;
 
@@ -1,6 +1,9 @@
 use crate::{
     ext::FmtExt,
-    postgres::backend::{ErrorResponse, NoticeResponse},
+    postgres::{
+        SqlState,
+        backend::{ErrorResponse, NoticeResponse},
+    },
 };
 
 // TODO: Appendix A, error code / sqlstate message
@@ -96,13 +99,25 @@ pub enum MessageFields {
 }
 
 impl MessageFields {
+    /// Find the value of given field.
+    pub fn find(body: &[u8], field: u8) -> Option<&[u8]> {
+        let mut iter = body.iter().copied().enumerate();
+        while let Some((i,key)) = iter.next() {
+            if key == b'\0' {
+                break;
+            }
+            let (end,_) = iter.find(|(_,e)|matches!(e,b'\0'))?;
+            if key == field {
+                return Some(&body[i + 1..end]);
+            }
+        }
+        None
+    }
+
     pub fn debug(body: &[u8], f: &mut std::fmt::Formatter) -> std::fmt::Result {
         let mut map = f.debug_map();
         let mut iter = body.iter().copied().enumerate();
-        loop {
-            let Some((i,key)) = iter.next() else {
-                break;
-            };
+        while let Some((i,key)) = iter.next() {
             let Some(key) = MessageFields::from_byte(key) else {
                 break;
             };
@@ -123,10 +138,7 @@ impl MessageFields {
         let mut hint = None;
 
         let mut iter = body.iter().copied().enumerate();
-        loop {
-            let Some((i,key)) = iter.next() else {
-                break;
-            };
+        while let Some((i,key)) = iter.next() {
             let Some((end,_)) = iter.find(|(_,e)|matches!(e,b'\0')) else {
                 break;
             };
@@ -220,12 +232,265 @@ impl std::fmt::Debug for ErrorResponse {
     }
 }
 
+impl ErrorResponse {
+    /// Returns the SQLSTATE code for the error, e.g: `57014` for `query_canceled`.
+    ///
+    /// <https://www.postgresql.org/docs/current/errcodes-appendix.html>
+    pub fn code(&self) -> Option<&str> {
+        std::str::from_utf8(MessageFields::find(&self.body, b'C')?).ok()
+    }
+
+    /// Returns the error cursor position as an index into the original query string.
+    ///
+    /// The first character has index 1, and positions are measured in characters not bytes.
+    pub fn position(&self) -> Option<usize> {
+        std::str::from_utf8(MessageFields::find(&self.body, b'P')?).ok()?.parse().ok()
+    }
+}
+
 impl std::fmt::Display for ErrorResponse {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
         MessageFields::display(&self.body, f)
     }
 }
 
+/// Error reported by the database, parsed from [`ErrorResponse`].
+///
+/// ```no_run
+/// # async fn test(mut conn: postro::Connection) -> postro::Result<()> {
+/// use postro::error::{ErrorKind, SqlState};
+///
+/// let result = postro::query("INSERT INTO post(name) VALUES('foo')", &mut conn).await;
+///
+/// match result {
+///     Ok(_) => {},
+///     Err(err) => match err.kind() {
+///         ErrorKind::Database(err) if err.is_unique_violation() => {},
+///         ErrorKind::Database(err) => match err.code() {
+///             SqlState::NOT_NULL_VIOLATION => {},
+///             _ => {},
+///         },
+///         _ => {},
+///     },
+/// }
+/// # Ok(())
+/// # }
+/// ```
+#[derive(Debug, Clone)]
+pub struct DatabaseError {
+    severity: String,
+    code: SqlState,
+    message: String,
+    detail: Option<String>,
+    hint: Option<String>,
+    position: Option<usize>,
+    context: Option<String>,
+    schema: Option<String>,
+    table: Option<String>,
+    column: Option<String>,
+    data_type: Option<String>,
+    constraint: Option<String>,
+    routine: Option<String>,
+    /// The sql string that cause the error, used to render error position.
+    query: Option<String>,
+}
+
+impl From<ErrorResponse> for DatabaseError {
+    fn from(err: ErrorResponse) -> Self {
+        let field = |key| MessageFields::find(&err.body, key).map(|e| String::from_utf8_lossy(e).into_owned());
+        Self {
+            severity: field(b'S').or_else(|| field(b'V')).unwrap_or_default(),
+            code: err.code().and_then(SqlState::from_code).unwrap_or(SqlState::INTERNAL_ERROR),
+            message: field(b'M').unwrap_or_default(),
+            detail: field(b'D'),
+            hint: field(b'H'),
+            position: err.position(),
+            context: field(b'W'),
+            schema: field(b's'),
+            table: field(b't'),
+            column: field(b'c'),
+            data_type: field(b'd'),
+            constraint: field(b'n'),
+            routine: field(b'R'),
+            query: None,
+        }
+    }
+}
+
+impl DatabaseError {
+    /// Returns the severity, e.g: `ERROR` or `FATAL`, possibly localized.
+    pub fn severity(&self) -> &str {
+        &self.severity
+    }
+
+    /// Returns the SQLSTATE code.
+    pub fn code(&self) -> SqlState {
+        self.code
+    }
+
+    /// Returns the primary human-readable error message.
+    pub fn message(&self) -> &str {
+        &self.message
+    }
+
+    /// Returns the secondary error message carrying more detail about the problem.
+    pub fn detail(&self) -> Option<&str> {
+        self.detail.as_deref()
+    }
+
+    /// Returns the suggestion what to do about the problem.
+    pub fn hint(&self) -> Option<&str> {
+        self.hint.as_deref()
+    }
+
+    /// Returns the error cursor position as an index into the original query string.
+    ///
+    /// The first character has index 1, and positions are measured in characters not bytes.
+    pub fn position(&self) -> Option<usize> {
+        self.position
+    }
+
+    /// Returns the context in which the error occurred, e.g: call stack of procedural language functions.
+    pub fn context(&self) -> Option<&str> {
+        self.context.as_deref()
+    }
+
+    /// Returns the schema name of the associated object.
+    pub fn schema(&self) -> Option<&str> {
+        self.schema.as_deref()
+    }
+
+    /// Returns the table name of the associated object.
+    pub fn table(&self) -> Option<&str> {
+        self.table.as_deref()
+    }
+
+    /// Returns the column name of the associated object.
+    pub fn column(&self) -> Option<&str> {
+        self.column.as_deref()
+    }
+
+    /// Returns the data type name of the associated object.
+    pub fn data_type(&self) -> Option<&str> {
+        self.data_type.as_deref()
+    }
+
+    /// Returns the constraint name of the associated object.
+    pub fn constraint(&self) -> Option<&str> {
+        self.constraint.as_deref()
+    }
+
+    /// Returns the name of the source-code routine reporting the error.
+    pub fn routine(&self) -> Option<&str> {
+        self.routine.as_deref()
+    }
+
+    /// Returns the sql string that cause the error, if known.
+    pub fn query(&self) -> Option<&str> {
+        self.query.as_deref()
+    }
+
+    /// Returns `true` if the error is [`SqlState::UNIQUE_VIOLATION`].
+    pub fn is_unique_violation(&self) -> bool {
+        self.code == SqlState::UNIQUE_VIOLATION
+    }
+
+    /// Returns `true` if the error is [`SqlState::FOREIGN_KEY_VIOLATION`].
+    pub fn is_foreign_key_violation(&self) -> bool {
+        self.code == SqlState::FOREIGN_KEY_VIOLATION
+    }
+
+    /// Returns `true` if the error is [`SqlState::NOT_NULL_VIOLATION`].
+    pub fn is_not_null_violation(&self) -> bool {
+        self.code == SqlState::NOT_NULL_VIOLATION
+    }
+
+    /// Returns `true` if the error is [`SqlState::CHECK_VIOLATION`].
+    pub fn is_check_violation(&self) -> bool {
+        self.code == SqlState::CHECK_VIOLATION
+    }
+
+    /// Returns `true` if the transaction can be retried, which is
+    /// [`SqlState::SERIALIZATION_FAILURE`] or [`SqlState::DEADLOCK_DETECTED`].
+    pub fn is_transaction_retryable(&self) -> bool {
+        matches!(self.code, SqlState::SERIALIZATION_FAILURE | SqlState::DEADLOCK_DETECTED)
+    }
+
+    /// Returns `true` if the error is caused by a cached prepared statement that is
+    /// invalidated, e.g: `cached plan must not change result type` after schema change.
+    pub(crate) fn is_stale_statement(&self) -> bool {
+        match self.code {
+            // other `feature_not_supported` is not retryable
+            SqlState::FEATURE_NOT_SUPPORTED => self.routine() == Some("RevalidateCachedQuery"),
+            // e.g: after `DISCARD ALL`
+            SqlState::INVALID_SQL_STATEMENT_NAME => true,
+            _ => false,
+        }
+    }
+
+    /// Attach the sql string that cause the error.
+    pub(crate) fn set_query(&mut self, sql: &str) {
+        if self.query.is_none() && self.position.is_some() {
+            self.query = Some(sql.into());
+        }
+    }
+}
+
+impl std::error::Error for DatabaseError { }
+
+impl std::fmt::Display for DatabaseError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "[{}] {} (", self.severity, self.message)?;
+        if let Some(routine) = &self.routine {
+            write!(f, "{routine}, ")?;
+        }
+        write!(f, "{})", self.code)?;
+        if let Some(detail) = &self.detail {
+            write!(f, ",\n\n{detail}")?;
+        }
+        if let Some(hint) = &self.hint {
+            write!(f, ",\n\nHINT: {hint}")?;
+        }
+        if let (Some(query), Some(position)) = (&self.query, self.position) {
+            write!(f, "\n\n")?;
+            render_position(query, position, f)?;
+        }
+        Ok(())
+    }
+}
+
+/// Render sql line at position with caret, similar to `psql`.
+///
+/// ```text
+/// LINE 1: SELECT foo FROM bar
+///                ^
+/// ```
+fn render_position(sql: &str, position: usize, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+    let position = position.saturating_sub(1);
+    let mut line_no = 1;
+    let mut line_start = 0;
+
+    for (i,(offset,ch)) in sql.char_indices().enumerate() {
+        if i == position {
+            break;
+        }
+        if ch == '\n' {
+            line_no += 1;
+            line_start = offset + 1;
+        }
+    }
+
+    let line = sql[line_start..].lines().next().unwrap_or_default();
+    let prefix = format!("LINE {line_no}: ");
+    let column = sql[line_start..]
+        .chars()
+        .take(position.saturating_sub(sql[..line_start].chars().count()))
+        .count();
+
+    writeln!(f, "{prefix}{line}")?;
+    write!(f, "{:width$}^", "", width = prefix.len() + column)
+}
+
 impl std::error::Error for NoticeResponse { }
 
 impl std::fmt::Debug for NoticeResponse {
@@ -241,3 +506,24 @@ impl std::fmt::Display for NoticeResponse {
     }
 }
 
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn error_position() {
+        let mut err = DatabaseError::from(ErrorResponse::new(bytes::Bytes::from_static(
+            b"SERROR\0C42601\0Msyntax error at or near \"FORM\"\0P20\0\0",
+        )));
+        assert_eq!(err.position(), Some(20));
+        assert_eq!(err.code(), SqlState::SYNTAX_ERROR);
+        assert_eq!(err.to_string(), "[ERROR] syntax error at or near \"FORM\" (42601)");
+
+        err.set_query("SELECT id\n  , name FORM post");
+        assert_eq!(
+            err.to_string().split_once("\n\n").unwrap().1,
+            "LINE 2:   , name FORM post\n                 ^",
+        );
+    }
+}
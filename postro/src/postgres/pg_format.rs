@@ -9,7 +9,7 @@
 ///
 /// [t]: PgFormat::Text
 /// [b]: PgFormat::Binary
-#[derive(Debug)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
 pub enum PgFormat {
     /// Text has format code zero.
     ///
@@ -37,6 +37,14 @@ impl PgFormat {
             Self::Binary => 1,
         }
     }
+
+    /// Returns format from format code, unknown code is treated as [`Text`][PgFormat::Text].
+    pub const fn from_code(code: u16) -> Self {
+        match code {
+            1 => Self::Binary,
+            _ => Self::Text,
+        }
+    }
 }
 
 
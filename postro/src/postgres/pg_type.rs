@@ -6,6 +6,67 @@
 /// <https://www.postgresql.org/docs/current/datatype-oid.html>
 pub type Oid = u32;
 
+/// Returns the name of builtin postgres type, e.g: `int4` for oid `23`.
+///
+/// Returns [`None`] for unknown type, e.g: user-defined or extension type.
+pub fn type_name(oid: Oid) -> Option<&'static str> {
+    builtin(oid).map(|(name, _)| name)
+}
+
+/// Returns the suggested rust type to decode builtin postgres type.
+pub(crate) fn suggest_rust_type(oid: Oid) -> Option<&'static str> {
+    builtin(oid).and_then(|(_, suggestion)| suggestion)
+}
+
+const CAST_TEXT: Option<&str> = Some("`String` with `::text` cast");
+const DECIMAL: Option<&str> = Some(
+    "`rust_decimal::Decimal` (feature `rust_decimal`) or `bigdecimal::BigDecimal` (feature `bigdecimal`)"
+);
+const JSON: Option<&str> = Some("`types::Json<T>` or `serde_json::Value` (feature `json`)");
+
+/// Returns builtin type name with the suggested rust type.
+fn builtin(oid: Oid) -> Option<(&'static str, Option<&'static str>)> {
+    let ty = match oid {
+        16 => ("bool", Some("`bool`")),
+        17 => ("bytea", Some("`Vec<u8>` or `bytes::Bytes`")),
+        18 => ("char", CAST_TEXT),
+        19 => ("name", CAST_TEXT),
+        20 => ("int8", Some("`i64`")),
+        21 => ("int2", Some("`i16`")),
+        23 => ("int4", Some("`i32`")),
+        25 => ("text", Some("`String`")),
+        26 => ("oid", Some("`u32`")),
+        114 => ("json", JSON),
+        700 => ("float4", Some("`f32`")),
+        701 => ("float8", Some("`f64`")),
+        705 => ("unknown", CAST_TEXT),
+        650 => ("cidr", Some("`types::PgInet`")),
+        829 => ("macaddr", Some("`types::MacAddr`")),
+        869 => ("inet", Some("`std::net::IpAddr` or `types::PgInet`")),
+        1042 => ("bpchar", CAST_TEXT),
+        1043 => ("varchar", CAST_TEXT),
+        1082 => ("date", CAST_TEXT),
+        1083 => ("time", CAST_TEXT),
+        1114 => ("timestamp", Some("`time::PrimitiveDateTime` (feature `time`)")),
+        1184 => ("timestamptz", Some("`time::UtcDateTime` (feature `time`)")),
+        1186 => ("interval", CAST_TEXT),
+        1700 => ("numeric", DECIMAL),
+        2249 => ("record", Some("`types::Composite`")),
+        2950 => ("uuid", CAST_TEXT),
+        3220 => ("pg_lsn", Some("`types::PgLsn`")),
+        3802 => ("jsonb", JSON),
+        3904 => ("int4range", Some("`types::PgRange<i32>`")),
+        3906 => ("numrange", Some("`types::PgRange<T>` of decimal type")),
+        3908 => ("tsrange", Some("`types::PgRange<time::PrimitiveDateTime>` (feature `time`)")),
+        3910 => ("tstzrange", Some("`types::PgRange<time::UtcDateTime>` (feature `time`)")),
+        3912 => ("daterange", CAST_TEXT),
+        3926 => ("int8range", Some("`types::PgRange<i64>`")),
+        4072 => ("jsonpath", Some("`types::JsonPath`")),
+        _ => return None,
+    };
+    Some(ty)
+}
+
 /// A type that have corresponding postgred oid.
 pub trait PgType {
     const OID: Oid;
@@ -13,6 +74,7 @@ pub trait PgType {
 
 // json, 114, "JSON stored as text"
 // jsonb, 3802, "Binary JSON"
+// jsonpath, 4072, "JSON path"
 // date, 1082, "date"
 // time, 1083, "time of day"
 // timestamp, 1114, "date and time"
@@ -30,11 +92,15 @@ macro_rules! oid {
 // oid!((), 0); // 0 means type unspecified
 oid!(bool, 16);
 oid!(char, 18);
+oid!([u8], 17, "`bytea` variable-length string, binary values escaped");
+oid!(Vec<u8>, 17, "`bytea` variable-length string, binary values escaped");
+oid!(bytes::Bytes, 17, "`bytea` variable-length string, binary values escaped");
 oid!(i64, 20, "`int8` ~18 digit integer, 8-byte storage");
 oid!(i16, 21, "`int2` -32 thousand to 32 thousand, 2-byte storage");
 oid!(i32, 23, "`int4` -2 billion to 2 billion integer, 4-byte storage");
 oid!(str, 25, "`text` variable-length string, no limit specified");
 oid!(String, 25, "`text` variable-length string, no limit specified");
+oid!(u32, 26, "`oid` object identifier(oid), maximum 4 billion");
 oid!(f32, 700, "`float4` single-precision floating point number, 4-byte storage");
 oid!(f64, 701, "`float8` double-precision floating point number, 8-byte storage");
 
@@ -0,0 +1,200 @@
+/// SQLSTATE error code.
+///
+/// Commonly used codes are provided as associated constants, which can be used in
+/// pattern matching:
+///
+/// ```
+/// use postro::postgres::SqlState;
+///
+/// let code = SqlState::from_code("23505").unwrap();
+///
+/// match code {
+///     SqlState::UNIQUE_VIOLATION => {},
+///     _ => unreachable!(),
+/// }
+/// assert_eq!(code.class(), "23");
+/// ```
+///
+/// See [Appendix A](https://www.postgresql.org/docs/current/errcodes-appendix.html) for all codes.
+#[derive(Clone, Copy, PartialEq, Eq, Hash)]
+pub struct SqlState([u8; 5]);
+
+impl SqlState {
+    /// Create [`SqlState`] from 5 characters code.
+    ///
+    /// Returns `None` if `code` is not 5 ascii alphanumeric characters.
+    pub fn from_code(code: &str) -> Option<Self> {
+        let code: [u8; 5] = code.as_bytes().try_into().ok()?;
+        code.iter().all(u8::is_ascii_alphanumeric).then_some(Self(code))
+    }
+
+    /// Returns the 5 characters code.
+    pub fn code(&self) -> &str {
+        // SAFETY: only constructed from ascii
+        unsafe { std::str::from_utf8_unchecked(&self.0) }
+    }
+
+    /// Returns the error class, which is the first 2 characters of the code.
+    pub fn class(&self) -> &str {
+        &self.code()[..2]
+    }
+}
+
+impl std::fmt::Display for SqlState {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(self.code())
+    }
+}
+
+impl std::fmt::Debug for SqlState {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_tuple("SqlState").field(&self.code()).finish()
+    }
+}
+
+macro_rules! sqlstate {
+    ($($(#[$meta:meta])* $name:ident = $code:literal;)*) => {
+        impl SqlState {
+            $(
+                $(#[$meta])*
+                pub const $name: SqlState = SqlState(*$code);
+            )*
+        }
+    };
+}
+
+sqlstate! {
+    // Class 00 — Successful Completion
+    /// `successful_completion`
+    SUCCESSFUL_COMPLETION = b"00000";
+
+    // Class 08 — Connection Exception
+    /// `connection_exception`
+    CONNECTION_EXCEPTION = b"08000";
+    /// `connection_failure`
+    CONNECTION_FAILURE = b"08006";
+    /// `protocol_violation`
+    PROTOCOL_VIOLATION = b"08P01";
+
+    // Class 0A — Feature Not Supported
+    /// `feature_not_supported`
+    FEATURE_NOT_SUPPORTED = b"0A000";
+
+    // Class 21 — Cardinality Violation
+    /// `cardinality_violation`
+    CARDINALITY_VIOLATION = b"21000";
+
+    // Class 22 — Data Exception
+    /// `data_exception`
+    DATA_EXCEPTION = b"22000";
+    /// `string_data_right_truncation`
+    STRING_DATA_RIGHT_TRUNCATION = b"22001";
+    /// `numeric_value_out_of_range`
+    NUMERIC_VALUE_OUT_OF_RANGE = b"22003";
+    /// `invalid_datetime_format`
+    INVALID_DATETIME_FORMAT = b"22007";
+    /// `datetime_field_overflow`
+    DATETIME_FIELD_OVERFLOW = b"22008";
+    /// `division_by_zero`
+    DIVISION_BY_ZERO = b"22012";
+    /// `invalid_parameter_value`
+    INVALID_PARAMETER_VALUE = b"22023";
+    /// `invalid_text_representation`
+    INVALID_TEXT_REPRESENTATION = b"22P02";
+
+    // Class 23 — Integrity Constraint Violation
+    /// `integrity_constraint_violation`
+    INTEGRITY_CONSTRAINT_VIOLATION = b"23000";
+    /// `restrict_violation`
+    RESTRICT_VIOLATION = b"23001";
+    /// `not_null_violation`
+    NOT_NULL_VIOLATION = b"23502";
+    /// `foreign_key_violation`
+    FOREIGN_KEY_VIOLATION = b"23503";
+    /// `unique_violation`
+    UNIQUE_VIOLATION = b"23505";
+    /// `check_violation`
+    CHECK_VIOLATION = b"23514";
+    /// `exclusion_violation`
+    EXCLUSION_VIOLATION = b"23P01";
+
+    // Class 25 — Invalid Transaction State
+    /// `active_sql_transaction`
+    ACTIVE_SQL_TRANSACTION = b"25001";
+    /// `read_only_sql_transaction`
+    READ_ONLY_SQL_TRANSACTION = b"25006";
+    /// `in_failed_sql_transaction`
+    IN_FAILED_SQL_TRANSACTION = b"25P02";
+
+    // Class 26 — Invalid SQL Statement Name
+    /// `invalid_sql_statement_name`
+    INVALID_SQL_STATEMENT_NAME = b"26000";
+
+    // Class 28 — Invalid Authorization Specification
+    /// `invalid_authorization_specification`
+    INVALID_AUTHORIZATION_SPECIFICATION = b"28000";
+    /// `invalid_password`
+    INVALID_PASSWORD = b"28P01";
+
+    // Class 34 — Invalid Cursor Name
+    /// `invalid_cursor_name`
+    INVALID_CURSOR_NAME = b"34000";
+
+    // Class 3D — Invalid Catalog Name
+    /// `invalid_catalog_name`
+    INVALID_CATALOG_NAME = b"3D000";
+
+    // Class 3F — Invalid Schema Name
+    /// `invalid_schema_name`
+    INVALID_SCHEMA_NAME = b"3F000";
+
+    // Class 40 — Transaction Rollback
+    /// `transaction_rollback`
+    TRANSACTION_ROLLBACK = b"40000";
+    /// `serialization_failure`
+    SERIALIZATION_FAILURE = b"40001";
+    /// `deadlock_detected`
+    DEADLOCK_DETECTED = b"40P01";
+
+    // Class 42 — Syntax Error or Access Rule Violation
+    /// `syntax_error_or_access_rule_violation`
+    SYNTAX_ERROR_OR_ACCESS_RULE_VIOLATION = b"42000";
+    /// `insufficient_privilege`
+    INSUFFICIENT_PRIVILEGE = b"42501";
+    /// `syntax_error`
+    SYNTAX_ERROR = b"42601";
+    /// `duplicate_column`
+    DUPLICATE_COLUMN = b"42701";
+    /// `undefined_column`
+    UNDEFINED_COLUMN = b"42703";
+    /// `duplicate_object`
+    DUPLICATE_OBJECT = b"42710";
+    /// `datatype_mismatch`
+    DATATYPE_MISMATCH = b"42804";
+    /// `undefined_function`
+    UNDEFINED_FUNCTION = b"42883";
+    /// `undefined_table`
+    UNDEFINED_TABLE = b"42P01";
+    /// `duplicate_table`
+    DUPLICATE_TABLE = b"42P07";
+
+    // Class 53 — Insufficient Resources
+    /// `insufficient_resources`
+    INSUFFICIENT_RESOURCES = b"53000";
+    /// `too_many_connections`
+    TOO_MANY_CONNECTIONS = b"53300";
+
+    // Class 55 — Object Not In Prerequisite State
+    /// `lock_not_available`
+    LOCK_NOT_AVAILABLE = b"55P03";
+
+    // Class 57 — Operator Intervention
+    /// `query_canceled`
+    QUERY_CANCELED = b"57014";
+    /// `admin_shutdown`
+    ADMIN_SHUTDOWN = b"57P01";
+
+    // Class XX — Internal Error
+    /// `internal_error`
+    INTERNAL_ERROR = b"XX000";
+}
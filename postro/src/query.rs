@@ -1,32 +1,166 @@
 //! Query API types.
-use std::marker::PhantomData;
+use std::{
+    fmt,
+    marker::PhantomData,
+    sync::{Arc, Mutex, MutexGuard, PoisonError},
+};
 
 use crate::{
     Decode, FromRow, Result, Row,
+    describe::StatementInfo,
     encode::{Encode, Encoded},
     executor::Executor,
-    fetch::{Fetch, FetchCollect, FetchStream, StreamMap, command_complete},
-    postgres::backend,
+    fetch::{Fetch, FetchChunks, FetchCollect, FetchOptions, FetchStream, StreamMap, check_message_len, command_complete},
+    portal::SuspendedPortal,
+    postgres::{PgFormat, backend, frontend},
     row::{RowNotFound, RowResult},
-    sql::Sql,
+    sql::{Sql, SqlExt},
+    transport::{PgTransport, PgTransportExt},
 };
 
+/// Maximum number of parameters in a single statement supported by postgres.
+pub const MAX_PARAMS: usize = u16::MAX as usize;
+
+/// Maximum length of a single frontend message accepted by postgres, which is 1 GB.
+pub const MAX_MESSAGE_LEN: usize = 0x3fff_ffff;
+
 /// Entrypoint of the query API.
 #[inline]
 pub fn query<'val, SQL, Exe>(sql: SQL, exe: Exe) -> Query<'val, SQL, Exe, StreamRow<Row>> {
-    Query { sql, exe, params: Vec::new(), _p: PhantomData }
+    Query { sql, exe, params: Vec::new(), buffer: None, opts: FetchOptions::default(), _p: PhantomData }
 }
 
 /// Entrypoint of the query API.
 #[inline]
 pub fn query_as<'val, SQL, Exe, R>(sql: SQL, exe: Exe) -> Query<'val, SQL, Exe, StreamRow<R>> {
-    Query { sql, exe, params: Vec::new(), _p: PhantomData }
+    Query { sql, exe, params: Vec::new(), buffer: None, opts: FetchOptions::default(), _p: PhantomData }
 }
 
 /// Entrypoint of the query API.
 #[inline]
 pub fn query_scalar<'val, SQL, Exe, D>(sql: SQL, exe: Exe) -> Query<'val, SQL, Exe, StreamScalar<D>> {
-    Query { sql, exe, params: Vec::new(), _p: PhantomData }
+    Query { sql, exe, params: Vec::new(), buffer: None, opts: FetchOptions::default(), _p: PhantomData }
+}
+
+/// Entrypoint of the query API reusing parameter allocation of `buffer`.
+///
+/// Same as [`query`] with [`Query::buffer`], see [`ParamBuffer`] for more details.
+#[inline]
+pub fn query_with<'val, SQL, Exe>(
+    buffer: &ParamBuffer,
+    sql: SQL,
+    exe: Exe,
+) -> Query<'val, SQL, Exe, StreamRow<Row>> {
+    query(sql, exe).buffer(buffer)
+}
+
+/// Entrypoint of the query API with sql from a file, requires `macros` feature.
+///
+/// The file is included with [`include_sql!`][crate::include_sql], which validate
+/// placeholder numbering at compile time. The path is relative to the crate root.
+///
+/// To map rows into a type, use [`query_file_as!`][crate::query_file_as].
+///
+/// ```ignore
+/// let result = postro::query_file!("sql/delete_user.sql", &mut conn)
+///     .bind(id)
+///     .execute()
+///     .await?;
+/// ```
+#[cfg(feature = "macros")]
+#[macro_export]
+macro_rules! query_file {
+    ($path:literal, $exe:expr $(,)?) => {
+        $crate::query($crate::include_sql!($path), $exe)
+    };
+}
+
+/// Entrypoint of the query API with sql from a file, requires `macros` feature.
+///
+/// See [`query_file!`][crate::query_file] for more details.
+///
+/// ```ignore
+/// let user = postro::query_file_as!(User, "sql/get_user.sql", &mut conn)
+///     .bind(id)
+///     .fetch_one()
+///     .await?;
+/// ```
+#[cfg(feature = "macros")]
+#[macro_export]
+macro_rules! query_file_as {
+    ($ty:ty, $path:literal, $exe:expr $(,)?) => {
+        $crate::query_as::<_, _, $ty>($crate::include_sql!($path), $exe)
+    };
+}
+
+/// Execute `sql` using the simple query protocol, returns the result of each statement.
+///
+/// The `sql` can contains multiple statements separated by semicolon, which is useful
+/// for migration script or DDL that cannot be prepared. Parameters is not supported.
+///
+/// Unless the script contains explicit transaction control, all statements are executed
+/// in a single implicit transaction, so an error rollback all statements.
+///
+/// Note that values in returned rows are in text format.
+///
+/// ```no_run
+/// # async fn test(mut conn: postro::Connection) -> postro::Result<()> {
+/// let results = postro::raw_sql("CREATE TABLE post(id int); SELECT 1;", &mut conn).await?;
+/// assert_eq!(results.len(), 2);
+/// assert_eq!(results[1].rows.len(), 1);
+/// # Ok(())
+/// # }
+/// ```
+pub async fn raw_sql<Exe: Executor>(sql: &str, exe: Exe) -> Result<Vec<RawResult>> {
+    use backend::BackendMessage::*;
+
+    check_message_len(sql.len(), [].into_iter())?;
+
+    let commented = crate::sql::comment(sql);
+    let sql = commented.as_ref().map_or(sql, |e|e.0.as_str());
+
+    let mut io = exe.connection().await?;
+    io.send(frontend::Query { sql });
+    io.flush().await?;
+
+    let mut results = Vec::new();
+    let mut rows = Vec::new();
+    let mut desc = None::<Row>;
+
+    loop {
+        match io.recv().await.map_err(|err| err.with_sql(sql))? {
+            RowDescription(rd) => desc = Some(Row::new(rd.body).with_codecs(io.codecs())),
+            DataRow(dr) if desc.is_some() => rows.push(desc.as_ref().unwrap().inner_clone(dr.body)),
+            CommandComplete(cmd) => {
+                desc = None;
+                results.push(RawResult {
+                    rows: std::mem::take(&mut rows),
+                    result: RowResult {
+                        rows_affected: command_complete(&cmd),
+                        tag: cmd.tag,
+                    },
+                });
+            },
+            // whole `sql` is empty
+            EmptyQueryResponse(_) => {},
+            ReadyForQuery(_) => break,
+            f => {
+                io.poison();
+                Err(f.unexpected("simple query"))?
+            },
+        }
+    }
+
+    Ok(results)
+}
+
+/// Result of a single statement executed by [`raw_sql`].
+#[derive(Debug)]
+pub struct RawResult {
+    /// Rows returned by the statement, empty for statement that returns no rows.
+    pub rows: Vec<Row>,
+    /// The executed command and rows affected.
+    pub result: RowResult,
 }
 
 /// The query API.
@@ -36,6 +170,8 @@ pub struct Query<'val, SQL, Exe, M> {
     sql: SQL,
     exe: Exe,
     params: Vec<Encoded<'val>>,
+    buffer: Option<ParamBuffer>,
+    opts: FetchOptions,
     _p: PhantomData<M>,
 }
 
@@ -46,6 +182,128 @@ impl<'val, SQL, Exe, M> Query<'val, SQL, Exe, M> {
         self.params.push(value.encode());
         self
     }
+
+    /// Bind query parameters from an iterator, in order.
+    ///
+    /// Capacity is reserved up front from the iterator size hint, so arrays, slices and
+    /// vectors are encoded directly into the parameter list without reallocation.
+    ///
+    /// ```no_run
+    /// # async fn test(mut conn: postro::Connection) -> postro::Result<()> {
+    /// let ids = [1, 2, 3];
+    /// postro::query("INSERT INTO post(id) VALUES ($1),($2),($3)", &mut conn)
+    ///     .bind_all(ids)
+    ///     .await?;
+    /// # Ok(())
+    /// # }
+    /// ```
+    #[inline]
+    pub fn bind_all<I>(mut self, values: I) -> Self
+    where
+        I: IntoIterator,
+        I::Item: Encode<'val>,
+    {
+        let values = values.into_iter();
+        self.params.reserve(values.size_hint().0);
+        self.params.extend(values.map(Encode::encode));
+        self
+    }
+
+    /// Reuse parameter allocation of `buffer`, see [`ParamBuffer`].
+    ///
+    /// The allocation is returned to `buffer` when the returned future or stream is
+    /// completed, it is discarded if dropped before that.
+    #[inline]
+    pub fn buffer(mut self, buffer: &ParamBuffer) -> Self {
+        let mut params = buffer.take_params();
+        params.append(&mut self.params);
+        self.params = params;
+        self.buffer = Some(buffer.clone());
+        self
+    }
+
+    /// Bound each wait for socket read or write to `timeout`.
+    ///
+    /// Returns [`ErrorKind::IoTimeout`][crate::error::ErrorKind::IoTimeout] error if no message
+    /// is sent or received within `timeout`, which protect against half-open connection
+    /// that neither error nor make progress.
+    ///
+    /// Unlike `statement_timeout`, this is not enforced by the server. Therefore, a query
+    /// that takes longer than `timeout` before returning the first row also times out.
+    ///
+    /// After timing out, the connection state is unknown, it is
+    /// [poisoned][crate::Connection::is_poisoned] and discarded by the pool.
+    #[cfg(feature = "tokio")]
+    #[inline]
+    pub fn io_timeout(mut self, timeout: std::time::Duration) -> Self {
+        self.opts.io_timeout = Some(timeout);
+        self
+    }
+
+    /// Disable prepared statement caching for this query.
+    ///
+    /// The statement is prepared as unnamed statement, same as [`SqlExt::once`], useful
+    /// for one-off query with unique sql that would otherwise evict other cached statements.
+    #[inline]
+    pub fn once(self) -> Self {
+        self.persistent(false)
+    }
+
+    /// Set whether the prepared statement is cached, default to `true`.
+    ///
+    /// `false` is the same as [`Query::once`]. To disable caching for all queries, e.g:
+    /// behind transaction pooling proxy, see [`Config::statement_cache`][1] or
+    /// [`PoolConfig::statement_cache`][2].
+    ///
+    /// [1]: crate::Config::statement_cache
+    /// [2]: crate::PoolConfig::statement_cache
+    #[inline]
+    pub fn persistent(mut self, persistent: bool) -> Self {
+        self.opts.no_cache = !persistent;
+        self
+    }
+
+    /// Abort the query if the server returns more than `rows` rows.
+    ///
+    /// Returns [`ErrorKind::TooManyRows`][crate::error::ErrorKind::TooManyRows] error instead
+    /// of buffering a runaway result set, e.g: caused by missing `WHERE` clause. Unless paged,
+    /// the server also stops sending rows after the limit is exceeded.
+    #[inline]
+    pub fn max_rows(mut self, rows: u32) -> Self {
+        self.opts.max_rows = Some(rows);
+        self
+    }
+
+    /// Bound the whole query, including acquiring connection, to `timeout`.
+    ///
+    /// Returns [`ErrorKind::QueryTimeout`][crate::error::ErrorKind::QueryTimeout] error if the
+    /// query is not completed within `timeout`. If the statement is already sent, a cancel
+    /// request is sent to the server and the connection is [poisoned][1], so the pool
+    /// discards it instead of waiting for the server.
+    ///
+    /// For stream, the timer covers all rows, and starts on the first poll.
+    ///
+    /// [1]: crate::Connection::is_poisoned
+    #[cfg(feature = "tokio")]
+    #[inline]
+    pub fn timeout(mut self, timeout: std::time::Duration) -> Self {
+        self.opts.timeout = Some(timeout);
+        self
+    }
+
+    /// Cancel the query when `token` is cancelled.
+    ///
+    /// If the query is waiting for connection, e.g: acquiring from [`Pool`][crate::Pool],
+    /// it returns [`ErrorKind::Cancelled`][crate::error::ErrorKind::Cancelled] error
+    /// immediately. If the statement is already sent, a cancel request is sent to the server,
+    /// then the query returns `query_canceled` database error if the statement is cancelled
+    /// in time, keeping the connection usable.
+    #[cfg(feature = "tokio-util")]
+    #[inline]
+    pub fn cancel_on(mut self, token: tokio_util::sync::CancellationToken) -> Self {
+        self.opts.cancel = Some(token);
+        self
+    }
 }
 
 impl<'val, SQL, Exe, M> Query<'val, SQL, Exe, M> {
@@ -56,6 +314,9 @@ impl<'val, SQL, Exe, M> Query<'val, SQL, Exe, M> {
     ///
     /// Also if [`FromRow`][crate::FromRow] implementation returns error,
     /// stream is suspended.
+    ///
+    /// To avoid buffering a large result set, use
+    /// [`FetchStream::page_size`] to fetch rows in pages.
     #[inline]
     pub fn fetch(self) -> FetchStream<'val, SQL, Exe::Future, Exe::Transport, M>
     where
@@ -63,6 +324,27 @@ impl<'val, SQL, Exe, M> Query<'val, SQL, Exe, M> {
         M: StreamMap,
     {
         FetchStream::new(self.sql, self.exe.connection(), self.params, 0)
+            .with_options(self.opts)
+            .with_buffer(self.buffer)
+    }
+
+    /// Fetch rows in chunks of `size` rows.
+    ///
+    /// Each chunk is one page of the portal execution, the next page is only
+    /// requested when the next chunk is polled, so at most one chunk is kept in
+    /// memory. The last chunk may contain less than `size` rows.
+    ///
+    /// See [`FetchStream::page_size`] for transaction caveat. Zero `size` fetches
+    /// all rows in one chunk.
+    ///
+    /// If a row fails to be mapped, the rows collected in the current chunk are discarded.
+    #[inline]
+    pub fn fetch_chunked(self, size: u32) -> FetchChunks<'val, SQL, Exe::Future, Exe::Transport, M>
+    where
+        Exe: Executor,
+        M: StreamMap,
+    {
+        FetchChunks::new(self.fetch(), size)
     }
 
     /// Fetch all rows into [`Vec`].
@@ -79,9 +361,15 @@ impl<'val, SQL, Exe, M> Query<'val, SQL, Exe, M> {
             CollectAll(Vec::new()),
             0,
         )
+        .with_options(self.opts)
+        .with_buffer(self.buffer)
     }
 
     /// Fetch one row.
+    ///
+    /// Returns [`RowNotFound`] error if no row returned.
+    ///
+    /// Only one row is requested from the server, the rest is discarded.
     #[inline]
     pub fn fetch_one(self) -> Fetch<'val, SQL, Exe::Future, Exe::Transport, M, CollectOne<M::Output>>
     where
@@ -95,9 +383,13 @@ impl<'val, SQL, Exe, M> Query<'val, SQL, Exe, M> {
             CollectOne(None),
             1,
         )
+        .with_options(self.opts)
+        .with_buffer(self.buffer)
     }
 
     /// Optionally fetch one row.
+    ///
+    /// Only one row is requested from the server, the rest is discarded.
     #[inline]
     pub fn fetch_optional(self) -> Fetch<'val, SQL, Exe::Future, Exe::Transport, M, CollectOpt<M::Output>>
     where
@@ -111,6 +403,8 @@ impl<'val, SQL, Exe, M> Query<'val, SQL, Exe, M> {
             CollectOpt(None),
             1,
         )
+        .with_options(self.opts)
+        .with_buffer(self.buffer)
     }
 
     /// Execute statement and return number of rows affected.
@@ -120,6 +414,43 @@ impl<'val, SQL, Exe, M> Query<'val, SQL, Exe, M> {
         Exe: Executor,
     {
         Fetch::new(self.sql, self.exe.connection(), self.params, CollectCmd, 0)
+            .with_options(self.opts)
+            .with_buffer(self.buffer)
+    }
+}
+
+impl<'val, SQL, Exe, M> Query<'val, SQL, Exe, M> {
+    /// Open a [`SuspendedPortal`] which fetch `page_size` rows per page on demand.
+    ///
+    /// Must be called inside a transaction. Zero `page_size` fetches all rows in one page.
+    ///
+    /// Timeout and cancellation options are not applied.
+    pub fn fetch_portal(self, page_size: u32) -> impl Future<Output = Result<SuspendedPortal<M>>> + use<'val, SQL, Exe, M>
+    where
+        SQL: Sql,
+        Exe: Executor,
+        M: StreamMap,
+    {
+        SuspendedPortal::open(self.sql, self.params, self.opts.no_cache, page_size, self.exe)
+    }
+
+    /// Replace the sql and row mapping, keeping bound parameters and options.
+    #[cfg(feature = "testing")]
+    pub(crate) fn map_sql<S, N>(self, f: impl FnOnce(SQL) -> S) -> Query<'val, S, Exe, N> {
+        Query { sql: f(self.sql), exe: self.exe, params: self.params, buffer: self.buffer, opts: self.opts, _p: PhantomData }
+    }
+}
+
+impl<SQL, Exe, M> Query<'_, SQL, Exe, M> {
+    /// Describe the statement without executing it, bound parameters are ignored.
+    ///
+    /// See [`describe`][crate::describe::describe] for more details.
+    pub fn describe(self) -> impl Future<Output = Result<StatementInfo>>
+    where
+        SQL: Sql,
+        Exe: Executor,
+    {
+        crate::describe::describe(self.sql, self.exe)
     }
 }
 
@@ -139,6 +470,280 @@ where
     }
 }
 
+/// Reusable allocation of query parameters.
+///
+/// Each query allocates a list of its parameters, and another one to retry a cached
+/// statement invalidated by schema change. In a tight loop, the allocations can be
+/// reused across queries via [`query_with`] or [`Query::buffer`].
+///
+/// Cloning is cheap, all clones share the same allocations. When used by concurrent
+/// queries, the allocations is taken by one of them, and the others allocate as usual.
+///
+/// Note that only the list is reused, owned value of each parameter, e.g: [`String`],
+/// is still allocated when encoded.
+///
+/// ```no_run
+/// # async fn test(mut conn: postro::Connection) -> postro::Result<()> {
+/// use postro::query::{ParamBuffer, query_with};
+///
+/// let buffer = ParamBuffer::new();
+/// for id in 0..1000 {
+///     query_with(&buffer, "INSERT INTO post(id, title) VALUES($1, $2)", &mut conn)
+///         .bind(id)
+///         .bind("title")
+///         .await?;
+/// }
+/// # Ok(())
+/// # }
+/// ```
+#[derive(Debug, Clone, Default)]
+pub struct ParamBuffer {
+    inner: Arc<Mutex<Buffers>>,
+}
+
+#[derive(Debug, Default)]
+struct Buffers {
+    params: Vec<Encoded<'static>>,
+    retry: Vec<Encoded<'static>>,
+}
+
+impl ParamBuffer {
+    /// Create empty [`ParamBuffer`], parameters are not allocated until used.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Create [`ParamBuffer`] with capacity for `params` parameters.
+    pub fn with_capacity(params: usize) -> Self {
+        let buffers = Buffers { params: Vec::with_capacity(params), retry: Vec::with_capacity(params) };
+        Self { inner: Arc::new(Mutex::new(buffers)) }
+    }
+
+    /// Returns the number of parameters the buffer can hold without reallocating.
+    ///
+    /// Returns zero while the allocation is taken by a query.
+    pub fn capacity(&self) -> usize {
+        self.lock().params.capacity()
+    }
+
+    fn lock(&self) -> MutexGuard<'_, Buffers> {
+        self.inner.lock().unwrap_or_else(PoisonError::into_inner)
+    }
+
+    /// Take the allocation of parameters.
+    fn take_params<'val>(&self) -> Vec<Encoded<'val>> {
+        recycle(std::mem::take(&mut self.lock().params))
+    }
+
+    /// Take the allocation of retry parameters.
+    pub(crate) fn take_retry<'val>(&self) -> Vec<Encoded<'val>> {
+        recycle(std::mem::take(&mut self.lock().retry))
+    }
+
+    /// Returns the allocations to the buffer, clearing the parameters.
+    ///
+    /// The larger allocation is kept when used by concurrent queries.
+    pub(crate) fn put(&self, params: Vec<Encoded>, retry: Vec<Encoded>) {
+        let mut buffers = self.lock();
+        if params.capacity() > buffers.params.capacity() {
+            buffers.params = recycle(params);
+        }
+        if retry.capacity() > buffers.retry.capacity() {
+            buffers.retry = recycle(retry);
+        }
+    }
+}
+
+/// Clear `params` and reuse its allocation for another lifetime.
+fn recycle<'val>(mut params: Vec<Encoded>) -> Vec<Encoded<'val>> {
+    params.clear();
+    let mut params = std::mem::ManuallyDrop::new(params);
+    let (ptr, cap) = (params.as_mut_ptr(), params.capacity());
+    // SAFETY: the allocation is from `Vec` of the same type differing only in lifetime,
+    // and there is no element left to outlive
+    unsafe { Vec::from_raw_parts(ptr.cast::<Encoded<'val>>(), 0, cap) }
+}
+
+/// Insert multiple rows using multi-row `VALUES` list.
+///
+/// `insert` is the statement without `VALUES`, e.g: `INSERT INTO post(name,body)`,
+/// each row is the bound values of one row, which all must have the same length.
+///
+/// When the number of parameters would exceed [`MAX_PARAMS`], rows are inserted in
+/// multiple statements. Note that this is not atomic, use [`Transaction`][1] if required.
+///
+/// Returns the total rows affected.
+///
+/// ```no_run
+/// # async fn test(mut conn: postro::Connection) -> postro::Result<()> {
+/// use postro::Encode;
+///
+/// let posts = [("foo", "lorem"), ("bar", "ipsum")];
+/// let rows = posts.into_iter().map(|(name, body)| vec![name.encode(), body.encode()]);
+///
+/// postro::query::insert_many("INSERT INTO post(name,body)", rows, &mut conn).await?;
+/// # Ok(())
+/// # }
+/// ```
+///
+/// # Panics
+///
+/// Panics if rows have different length.
+///
+/// [1]: crate::transaction::Transaction
+pub async fn insert_many<'val, Exe, I>(insert: &str, rows: I, exe: Exe) -> Result<u64>
+where
+    Exe: Executor,
+    I: IntoIterator<Item = Vec<Encoded<'val>>>,
+{
+    let mut rows = rows.into_iter().peekable();
+    let Some(columns) = rows.peek().map(Vec::len) else {
+        return Ok(0);
+    };
+
+    let chunk_len = (MAX_PARAMS / columns.max(1)).max(1);
+    let mut io = exe.connection().await?;
+    let mut rows_affected = 0;
+
+    while rows.peek().is_some() {
+        let chunk = rows.by_ref().take(chunk_len).collect::<Vec<_>>();
+        let mut sql = String::from(insert.trim());
+        sql.push_str(" VALUES");
+        for (i, row) in chunk.iter().enumerate() {
+            assert_eq!(row.len(), columns, "rows have different length");
+            sql.push_str(if i == 0 { "(" } else { ",(" });
+            for j in 0..columns {
+                if j != 0 {
+                    sql.push(',');
+                }
+                sql.push('$');
+                sql.push_str(itoa::Buffer::new().format(i * columns + j + 1));
+            }
+            sql.push(')');
+        }
+
+        // only full chunks are repeated, avoid caching the remainder
+        let persistent = chunk.len() == chunk_len;
+        let params = chunk.into_iter().flatten().collect();
+        let result = match persistent {
+            true => Query { params, ..query(sql.as_str(), &mut io) }.await?,
+            false => Query { params, ..query(sql.as_str().once(), &mut io) }.await?,
+        };
+        rows_affected += result.rows_affected;
+    }
+
+    Ok(rows_affected)
+}
+
+/// Build dynamic sql with its parameters, generating `$n` placeholders automatically.
+///
+/// # Example
+///
+/// ```no_run
+/// # async fn test(mut conn: postro::Connection, name: Option<&str>) -> postro::Result<()> {
+/// use postro::query::QueryBuilder;
+///
+/// let mut builder = QueryBuilder::new("SELECT id FROM users WHERE deleted_at IS NULL");
+/// if let Some(name) = name {
+///     builder.push(" AND name = ").push_bind(name);
+/// }
+/// builder.push(" LIMIT ").push_bind(10i64);
+///
+/// let ids = builder.build_scalar::<_, i32>(&mut conn).fetch_all().await?;
+/// # Ok(())
+/// # }
+/// ```
+#[derive(Debug, Default)]
+pub struct QueryBuilder<'val> {
+    sql: String,
+    params: Vec<Encoded<'val>>,
+}
+
+impl<'val> QueryBuilder<'val> {
+    /// Create new builder with initial sql.
+    pub fn new(init: impl Into<String>) -> Self {
+        Self { sql: init.into(), params: Vec::new() }
+    }
+
+    /// Append raw sql.
+    ///
+    /// Note that `sql` is not escaped, use [`push_bind`][Self::push_bind] for user input.
+    pub fn push(&mut self, sql: &str) -> &mut Self {
+        self.sql.push_str(sql);
+        self
+    }
+
+    /// Append a `$n` placeholder and bind its value.
+    pub fn push_bind<'v: 'val, V: Encode<'v>>(&mut self, value: V) -> &mut Self {
+        self.params.push(value.encode());
+        self.sql.push('$');
+        self.sql.push_str(itoa::Buffer::new().format(self.params.len()));
+        self
+    }
+
+    /// Returns the sql built so far.
+    pub fn sql(&self) -> &str {
+        &self.sql
+    }
+
+    /// Build the query, bound parameters are moved to the query.
+    ///
+    /// The builder should not be pushed afterward, as the moved parameters are no longer
+    /// counted for the next placeholder.
+    pub fn build<Exe>(&mut self, exe: Exe) -> Query<'val, &str, Exe, StreamRow<Row>> {
+        Query { params: std::mem::take(&mut self.params), ..query(self.sql.as_str(), exe) }
+    }
+
+    /// Build the query with rows mapped into `R`, bound parameters are moved to the query.
+    pub fn build_as<Exe, R>(&mut self, exe: Exe) -> Query<'val, &str, Exe, StreamRow<R>> {
+        Query { params: std::mem::take(&mut self.params), ..query_as(self.sql.as_str(), exe) }
+    }
+
+    /// Build the query with a single column decoded into `D`, bound parameters are moved to
+    /// the query.
+    pub fn build_scalar<Exe, D>(&mut self, exe: Exe) -> Query<'val, &str, Exe, StreamScalar<D>> {
+        Query { params: std::mem::take(&mut self.params), ..query_scalar(self.sql.as_str(), exe) }
+    }
+}
+
+/// An error when query bind more than [`MAX_PARAMS`] parameters.
+pub struct TooManyParams(pub usize);
+
+impl std::error::Error for TooManyParams { }
+
+impl fmt::Display for TooManyParams {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "too many parameters: {}, maximum is {MAX_PARAMS}", self.0)
+    }
+}
+
+impl fmt::Debug for TooManyParams {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "\"{self}\"")
+    }
+}
+
+/// An error when the sql or bound parameters exceed [`MAX_MESSAGE_LEN`].
+pub struct MessageTooLarge(pub usize);
+
+impl std::error::Error for MessageTooLarge { }
+
+impl fmt::Display for MessageTooLarge {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "message too large: {} bytes, maximum is {MAX_MESSAGE_LEN}, \
+            consider using `COPY` or splitting the query",
+            self.0,
+        )
+    }
+}
+
+impl fmt::Debug for MessageTooLarge {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "\"{self}\"")
+    }
+}
 
 // ===== Stream Adapters =====
 
@@ -152,6 +757,8 @@ where
 {
     type Output = R;
 
+    const FORMATS: &'static [PgFormat] = R::FORMATS;
+
     #[inline]
     fn map(row: Row) -> Result<Self::Output> {
         R::from_row(row).map_err(Into::into)
@@ -164,6 +771,8 @@ where
 {
     type Output = D;
 
+    const FORMATS: &'static [PgFormat] = <(D,)>::FORMATS;
+
     #[inline]
     fn map(row: Row) -> Result<Self::Output> {
         match <(D,)>::from_row(row) {
@@ -244,8 +853,10 @@ impl FetchCollect<Row> for CollectCmd {
 
     #[inline]
     fn finish(&mut self, cmd: Option<backend::CommandComplete>) -> Result<Self::Output> {
+        let cmd = cmd.expect("only PortalSuspended");
         Ok(RowResult {
-            rows_affected: cmd.map(command_complete).expect("only PortalSuspended"),
+            rows_affected: command_complete(&cmd),
+            tag: cmd.tag,
         })
     }
 }
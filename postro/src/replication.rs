@@ -0,0 +1,590 @@
+//! Streaming replication protocol, e.g: change data capture via logical decoding.
+//!
+//! A replication connection is created with [`Config::replication`][crate::Config::replication].
+//! It accepts replication commands instead of sql, and in [`ReplicationMode::Logical`],
+//! sql via simple query such as [`raw_sql`][crate::raw_sql].
+//!
+//! ```no_run
+//! # async fn test() -> postro::Result<()> {
+//! use futures::TryStreamExt;
+//! use postro::{Config, Connection, replication::*};
+//!
+//! let config = Config::from_env().replication(ReplicationMode::Logical);
+//! let mut conn = Connection::connect_with(config).await?;
+//!
+//! let system = identify_system(&mut conn).await?;
+//! eprintln!("replicating {:?} from {}", system.dbname, system.xlogpos);
+//!
+//! let slot = CreateSlot::logical("cdc", "test_decoding")
+//!     .temporary()
+//!     .execute(&mut conn)
+//!     .await?;
+//!
+//! let mut stream = StartReplication::logical(&slot.slot_name)
+//!     .option("include-xids", "off")
+//!     .start(&mut conn)
+//!     .await?;
+//! let mut processed = slot.consistent_point;
+//!
+//! while let Some(message) = stream.try_next().await? {
+//!     match message {
+//!         ReplicationMessage::XLogData(xlog) => {
+//!             eprintln!("{}", String::from_utf8_lossy(&xlog.data));
+//!             processed = xlog.wal_end;
+//!         }
+//!         ReplicationMessage::Keepalive(keepalive) => {
+//!             if keepalive.reply_requested {
+//!                 stream.send_status(StandbyStatus::new(processed)).await?;
+//!             }
+//!         }
+//!     }
+//! }
+//! # Ok(())
+//! # }
+//! ```
+//!
+//! <https://www.postgresql.org/docs/current/protocol-replication.html>
+use bytes::{Buf, Bytes};
+use futures_core::Stream;
+use std::{
+    pin::Pin,
+    task::{Context, Poll, ready},
+    time::{Duration, SystemTime, UNIX_EPOCH},
+};
+
+use crate::{
+    DecodeError, Result, Row,
+    common::quote_ident,
+    executor::Executor,
+    postgres::{BackendMessage, ProtocolError, frontend},
+    row::Column,
+    transport::{PgTransport, PgTransportExt},
+    types::PgLsn,
+};
+
+/// Seconds between unix epoch and postgres epoch, `2000-01-01`.
+const PG_EPOCH: Duration = Duration::from_secs(946_684_800);
+
+/// Streaming replication mode, see [`Config::replication`][crate::Config::replication].
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum ReplicationMode {
+    /// Physical replication, only replication commands are accepted.
+    Physical,
+    /// Logical replication, connected to the database in config.
+    Logical,
+}
+
+impl ReplicationMode {
+    /// Returns the `replication` startup parameter value.
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Self::Physical => "true",
+            Self::Logical => "database",
+        }
+    }
+}
+
+/// Result of [`identify_system`].
+#[derive(Clone, Debug)]
+pub struct IdentifySystem {
+    /// The unique system identifier identifying the cluster.
+    pub systemid: String,
+    /// Current timeline ID.
+    pub timeline: u32,
+    /// Current WAL flush location.
+    pub xlogpos: PgLsn,
+    /// Database connected to, `None` in physical replication.
+    pub dbname: Option<String>,
+}
+
+/// Request the server to identify itself via `IDENTIFY_SYSTEM`.
+pub async fn identify_system<Exe: Executor>(exe: Exe) -> Result<IdentifySystem> {
+    let row = command("IDENTIFY_SYSTEM", exe).await?.ok_or(DecodeError::IndexOutOfBounds(0))?;
+    Ok(IdentifySystem {
+        systemid: text(&row, 0)?.ok_or(DecodeError::Null)?,
+        timeline: parse(&row, 1)?,
+        xlogpos: parse(&row, 2)?,
+        dbname: text(&row, 3)?,
+    })
+}
+
+/// Replication slot builder via `CREATE_REPLICATION_SLOT`.
+#[derive(Clone, Debug)]
+pub struct CreateSlot<'a> {
+    name: &'a str,
+    plugin: Option<&'a str>,
+    temporary: bool,
+}
+
+/// Result of [`CreateSlot::execute`].
+#[derive(Clone, Debug)]
+pub struct ReplicationSlot {
+    /// The name of the newly-created replication slot.
+    pub slot_name: String,
+    /// The WAL location at which the slot became consistent, where streaming can start.
+    pub consistent_point: PgLsn,
+    /// The identifier of the snapshot exported by the command, `None` for physical slot.
+    pub snapshot_name: Option<String>,
+    /// The name of the output plugin, `None` for physical slot.
+    pub output_plugin: Option<String>,
+}
+
+impl<'a> CreateSlot<'a> {
+    /// Create logical slot `name` which decodes changes using `plugin`, e.g: `pgoutput`.
+    pub fn logical(name: &'a str, plugin: &'a str) -> Self {
+        Self { name, plugin: Some(plugin), temporary: false }
+    }
+
+    /// Create physical slot `name`, which reserves WAL immediately.
+    pub fn physical(name: &'a str) -> Self {
+        Self { name, plugin: None, temporary: false }
+    }
+
+    /// Drop the slot when the connection is closed or on error.
+    pub fn temporary(mut self) -> Self {
+        self.temporary = true;
+        self
+    }
+
+    /// Create the slot.
+    pub async fn execute<Exe: Executor>(self, exe: Exe) -> Result<ReplicationSlot> {
+        let mut sql = format!("CREATE_REPLICATION_SLOT {}", quote_ident(self.name));
+        if self.temporary {
+            sql.push_str(" TEMPORARY");
+        }
+        match self.plugin {
+            Some(plugin) => {
+                sql.push_str(" LOGICAL ");
+                sql.push_str(&quote_ident(plugin));
+            },
+            None => sql.push_str(" PHYSICAL RESERVE_WAL"),
+        }
+
+        let row = command(&sql, exe).await?.ok_or(DecodeError::IndexOutOfBounds(0))?;
+        Ok(ReplicationSlot {
+            slot_name: text(&row, 0)?.ok_or(DecodeError::Null)?,
+            consistent_point: parse(&row, 1)?,
+            snapshot_name: text(&row, 2)?,
+            output_plugin: text(&row, 3)?,
+        })
+    }
+}
+
+/// Drop replication slot `name` via `DROP_REPLICATION_SLOT`.
+///
+/// Returns error if the slot is active, e.g: streaming in another connection.
+pub async fn drop_replication_slot<Exe: Executor>(name: &str, exe: Exe) -> Result<()> {
+    command(&format!("DROP_REPLICATION_SLOT {}", quote_ident(name)), exe).await?;
+    Ok(())
+}
+
+/// Replication stream builder via `START_REPLICATION`.
+#[derive(Clone, Debug)]
+pub struct StartReplication<'a> {
+    slot: Option<&'a str>,
+    logical: bool,
+    start_lsn: PgLsn,
+    timeline: Option<u32>,
+    options: Vec<(&'a str, &'a str)>,
+}
+
+impl<'a> StartReplication<'a> {
+    /// Stream logical changes from `slot`.
+    pub fn logical(slot: &'a str) -> Self {
+        Self { slot: Some(slot), logical: true, start_lsn: PgLsn(0), timeline: None, options: Vec::new() }
+    }
+
+    /// Stream WAL, optionally from `slot`.
+    pub fn physical(slot: Option<&'a str>) -> Self {
+        Self { slot, logical: false, start_lsn: PgLsn(0), timeline: None, options: Vec::new() }
+    }
+
+    /// Set the WAL location to start streaming from, default to `0/0`.
+    ///
+    /// For logical slot, `0/0` starts from the slot `confirmed_flush_lsn`. Physical
+    /// replication requires the actual location, e.g: [`IdentifySystem::xlogpos`].
+    pub fn start_lsn(mut self, lsn: PgLsn) -> Self {
+        self.start_lsn = lsn;
+        self
+    }
+
+    /// Set the timeline to stream in physical replication, default to the current timeline.
+    pub fn timeline(mut self, timeline: u32) -> Self {
+        self.timeline = Some(timeline);
+        self
+    }
+
+    /// Add output plugin option in logical replication, e.g: `proto_version` of `pgoutput`.
+    pub fn option(mut self, name: &'a str, value: &'a str) -> Self {
+        self.options.push((name, value));
+        self
+    }
+
+    fn sql(&self) -> String {
+        let mut sql = String::from("START_REPLICATION");
+        if let Some(slot) = self.slot {
+            sql.push_str(" SLOT ");
+            sql.push_str(&quote_ident(slot));
+        }
+        sql.push_str(if self.logical { " LOGICAL " } else { " PHYSICAL " });
+        sql.push_str(&self.start_lsn.to_string());
+
+        if let Some(timeline) = self.timeline.filter(|_| !self.logical) {
+            sql.push_str(&format!(" TIMELINE {timeline}"));
+        }
+
+        if self.logical && !self.options.is_empty() {
+            let options = self
+                .options
+                .iter()
+                .map(|(name, value)| format!("{} '{}'", quote_ident(name), value.replace('\'', "''")))
+                .collect::<Vec<_>>();
+            sql.push_str(&format!(" ({})", options.join(", ")));
+        }
+
+        sql
+    }
+
+    /// Start streaming, returns [`ReplicationStream`] to receive the messages.
+    pub async fn start<Exe: Executor>(self, exe: Exe) -> Result<ReplicationStream<Exe::Transport>> {
+        let sql = self.sql();
+        let mut io = exe.connection().await?;
+        io.send(frontend::Query { sql: &sql });
+        io.flush().await?;
+
+        match io.recv().await.map_err(|err| err.with_sql(&sql))? {
+            BackendMessage::CopyBothResponse(_) => { },
+            f => {
+                io.poison();
+                Err(f.unexpected("start replication"))?
+            },
+        }
+
+        Ok(ReplicationStream {
+            io,
+            wal_end: self.start_lsn,
+            stopping: false,
+            flushing: false,
+            done: false,
+        })
+    }
+}
+
+/// Message received in [`ReplicationStream`].
+#[derive(Clone, Debug)]
+pub enum ReplicationMessage {
+    /// WAL data, or logical changes in the output plugin format.
+    XLogData(XLogData),
+    /// Primary keepalive message.
+    Keepalive(Keepalive),
+}
+
+/// WAL data message.
+#[derive(Clone, Debug)]
+pub struct XLogData {
+    /// The starting point of the WAL data in this message.
+    pub wal_start: PgLsn,
+    /// The current end of WAL on the server.
+    pub wal_end: PgLsn,
+    /// The server's system clock at the time of transmission.
+    pub send_time: SystemTime,
+    /// A section of the WAL data stream, or the output plugin data in logical replication.
+    pub data: Bytes,
+}
+
+/// Primary keepalive message.
+#[derive(Clone, Debug)]
+pub struct Keepalive {
+    /// The current end of WAL on the server.
+    pub wal_end: PgLsn,
+    /// The server's system clock at the time of transmission.
+    pub send_time: SystemTime,
+    /// `true` if the client should reply as soon as possible, to avoid a timeout
+    /// disconnect, see [`ReplicationStream::send_status`].
+    pub reply_requested: bool,
+}
+
+/// Standby status update, see [`ReplicationStream::send_status`].
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct StandbyStatus {
+    /// The location of the last WAL byte + 1 received and written to disk in the standby.
+    pub written: PgLsn,
+    /// The location of the last WAL byte + 1 flushed to disk in the standby.
+    pub flushed: PgLsn,
+    /// The location of the last WAL byte + 1 applied in the standby.
+    pub applied: PgLsn,
+    /// Request the server to reply to this message immediately.
+    pub reply_requested: bool,
+}
+
+impl StandbyStatus {
+    /// Report every WAL up to `lsn` as written, flushed and applied.
+    pub fn new(lsn: PgLsn) -> Self {
+        Self { written: lsn, flushed: lsn, applied: lsn, reply_requested: false }
+    }
+}
+
+/// Message stream of `START_REPLICATION`, created via [`StartReplication::start`].
+///
+/// The server only discards WAL, or in logical replication advances the slot, up to
+/// the `flushed` location reported via [`send_status`][ReplicationStream::send_status].
+/// Status should be sent periodically, at least when [`Keepalive::reply_requested`],
+/// otherwise the server disconnects after `wal_sender_timeout`.
+///
+/// The stream is stopped via [`stop`][ReplicationStream::stop]. If dropped before
+/// stopped, the streaming is stopped when the connection is used next.
+pub struct ReplicationStream<IO: PgTransport> {
+    io: IO,
+    wal_end: PgLsn,
+    /// `CopyDone` is sent
+    stopping: bool,
+    /// `CopyDone` is sent in `poll_next` but not yet flushed
+    flushing: bool,
+    done: bool,
+}
+
+impl<IO: PgTransport> ReplicationStream<IO> {
+    /// Returns the highest server WAL end received.
+    pub fn wal_end(&self) -> PgLsn {
+        self.wal_end
+    }
+
+    /// Send standby status update to the server.
+    pub async fn send_status(&mut self, status: StandbyStatus) -> Result<()> {
+        let mut data = [0u8; 34];
+        data[0] = b'r';
+        data[1..9].copy_from_slice(&status.written.0.to_be_bytes());
+        data[9..17].copy_from_slice(&status.flushed.0.to_be_bytes());
+        data[17..25].copy_from_slice(&status.applied.0.to_be_bytes());
+        data[25..33].copy_from_slice(&to_pg_time(SystemTime::now()).to_be_bytes());
+        data[33] = status.reply_requested as u8;
+
+        self.io.send(frontend::CopyData { data: &data });
+        self.io.flush().await?;
+        Ok(())
+    }
+
+    /// Stop streaming, remaining messages are discarded.
+    pub async fn stop(mut self) -> Result<()> {
+        if self.done {
+            return Ok(());
+        }
+        self.done = true;
+        if !self.stopping {
+            self.io.send(frontend::CopyDone);
+        }
+        self.io.flush().await?;
+
+        loop {
+            match self.io.recv().await? {
+                BackendMessage::ReadyForQuery(_) => return Ok(()),
+                BackendMessage::CopyData(_)
+                | BackendMessage::CopyDone(_)
+                | BackendMessage::RowDescription(_)
+                | BackendMessage::DataRow(_)
+                | BackendMessage::CommandComplete(_) => { },
+                f => {
+                    self.io.poison();
+                    Err(f.unexpected("stop replication"))?
+                },
+            }
+        }
+    }
+}
+
+impl<IO: PgTransport + Unpin> Stream for ReplicationStream<IO> {
+    type Item = Result<ReplicationMessage>;
+
+    fn poll_next(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Option<Self::Item>> {
+        let me = self.get_mut();
+
+        while !me.done {
+            if me.flushing {
+                if let Err(err) = ready!(me.io.poll_flush(cx)) {
+                    me.done = true;
+                    return Poll::Ready(Some(Err(err.into())));
+                }
+                me.flushing = false;
+            }
+
+            let message = match ready!(me.io.poll_recv(cx)) {
+                Ok(ok) => ok,
+                Err(err) => {
+                    // the connection already handle the error
+                    me.done = true;
+                    return Poll::Ready(Some(Err(err)));
+                },
+            };
+            match message {
+                BackendMessage::CopyData(copy) => match decode(copy.data) {
+                    Ok(message) => {
+                        let (ReplicationMessage::XLogData(XLogData { wal_end, .. })
+                            | ReplicationMessage::Keepalive(Keepalive { wal_end, .. })) = &message;
+                        me.wal_end = me.wal_end.max(*wal_end);
+                        return Poll::Ready(Some(Ok(message)));
+                    },
+                    Err(err) => {
+                        me.io.poison();
+                        me.done = true;
+                        return Poll::Ready(Some(Err(err.into())));
+                    },
+                },
+                // server ends the streaming, e.g: end of timeline in physical replication
+                BackendMessage::CopyDone(_) => if !me.stopping {
+                    me.io.send(frontend::CopyDone);
+                    me.stopping = true;
+                    me.flushing = true;
+                },
+                // next timeline in physical replication
+                BackendMessage::RowDescription(_)
+                | BackendMessage::DataRow(_)
+                | BackendMessage::CommandComplete(_) => { },
+                BackendMessage::ReadyForQuery(_) => me.done = true,
+                f => {
+                    me.io.poison();
+                    me.done = true;
+                    return Poll::Ready(Some(Err(f.unexpected("replication").into())));
+                },
+            }
+        }
+
+        Poll::Ready(None)
+    }
+}
+
+impl<IO: PgTransport> Drop for ReplicationStream<IO> {
+    fn drop(&mut self) {
+        if !self.done {
+            if !self.stopping {
+                self.io.send(frontend::CopyDone);
+            }
+            self.io.ready_request();
+        }
+    }
+}
+
+impl<IO: PgTransport> std::fmt::Debug for ReplicationStream<IO> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("ReplicationStream")
+            .field("wal_end", &self.wal_end)
+            .field("done", &self.done)
+            .finish_non_exhaustive()
+    }
+}
+
+fn decode(mut data: Bytes) -> Result<ReplicationMessage, ProtocolError> {
+    match data.first() {
+        Some(b'w') if data.len() >= 25 => {
+            data.advance(1);
+            Ok(ReplicationMessage::XLogData(XLogData {
+                wal_start: PgLsn(data.get_u64()),
+                wal_end: PgLsn(data.get_u64()),
+                send_time: from_pg_time(data.get_i64()),
+                data,
+            }))
+        },
+        Some(b'k') if data.len() >= 18 => {
+            data.advance(1);
+            Ok(ReplicationMessage::Keepalive(Keepalive {
+                wal_end: PgLsn(data.get_u64()),
+                send_time: from_pg_time(data.get_i64()),
+                reply_requested: data.get_u8() == 1,
+            }))
+        },
+        tag => Err(ProtocolError::unexpected_phase(tag.copied().unwrap_or(0), "replication")),
+    }
+}
+
+/// Microseconds since postgres epoch.
+fn from_pg_time(micros: i64) -> SystemTime {
+    let epoch = UNIX_EPOCH + PG_EPOCH;
+    match micros >= 0 {
+        true => epoch + Duration::from_micros(micros as u64),
+        false => epoch - Duration::from_micros(micros.unsigned_abs()),
+    }
+}
+
+fn to_pg_time(time: SystemTime) -> i64 {
+    let epoch = UNIX_EPOCH + PG_EPOCH;
+    match time.duration_since(epoch) {
+        Ok(ok) => ok.as_micros() as i64,
+        Err(err) => -(err.duration().as_micros() as i64),
+    }
+}
+
+/// Run replication command via simple query, returns the first row if any.
+///
+/// Unlike [`raw_sql`][crate::raw_sql], sqlcommenter tags are not added, which is not
+/// accepted by the replication command parser.
+async fn command<Exe: Executor>(sql: &str, exe: Exe) -> Result<Option<Row>> {
+    let mut io = exe.connection().await?;
+    io.send(frontend::Query { sql });
+    io.flush().await?;
+
+    let mut desc = None::<Row>;
+    let mut row = None;
+
+    loop {
+        match io.recv().await.map_err(|err| err.with_sql(sql))? {
+            BackendMessage::RowDescription(rd) => desc = Some(Row::new(rd.body)),
+            BackendMessage::DataRow(dr) if row.is_none() && desc.is_some() => {
+                row = Some(desc.as_ref().unwrap().inner_clone(dr.body));
+            },
+            BackendMessage::DataRow(_) | BackendMessage::CommandComplete(_) => { },
+            BackendMessage::ReadyForQuery(_) => return Ok(row),
+            f => {
+                io.poison();
+                Err(f.unexpected("replication command"))?
+            },
+        }
+    }
+}
+
+/// Replication commands returns text value regardless of the column type.
+fn text(row: &Row, idx: usize) -> Result<Option<String>, DecodeError> {
+    let column = row.try_get::<_, Column>(idx)?;
+    match column.as_slice() {
+        Some(value) => Ok(Some(std::str::from_utf8(value)?.to_owned())),
+        None => Ok(None),
+    }
+}
+
+fn parse<T: std::str::FromStr>(row: &Row, idx: usize) -> Result<T, DecodeError> {
+    text(row, idx)?
+        .ok_or(DecodeError::Null)?
+        .parse()
+        .map_err(|_| DecodeError::Invalid(format!("invalid value of column {idx}").into()))
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn replication_message() {
+        let mut xlog = vec![b'w'];
+        xlog.extend_from_slice(&0x1_0000_0010u64.to_be_bytes());
+        xlog.extend_from_slice(&0x1_0000_0020u64.to_be_bytes());
+        xlog.extend_from_slice(&1_000_000i64.to_be_bytes());
+        xlog.extend_from_slice(b"BEGIN");
+
+        let ReplicationMessage::XLogData(xlog) = decode(xlog.into()).unwrap() else { panic!() };
+        assert_eq!(xlog.wal_start.to_string(), "1/10");
+        assert_eq!(xlog.wal_end.to_string(), "1/20");
+        assert_eq!(xlog.send_time, UNIX_EPOCH + PG_EPOCH + Duration::from_secs(1));
+        assert_eq!(&xlog.data[..], b"BEGIN");
+
+        let mut keepalive = vec![b'k'];
+        keepalive.extend_from_slice(&0x20u64.to_be_bytes());
+        keepalive.extend_from_slice(&(-1i64).to_be_bytes());
+        keepalive.push(1);
+
+        let ReplicationMessage::Keepalive(keepalive) = decode(keepalive.into()).unwrap() else { panic!() };
+        assert_eq!(keepalive.wal_end, PgLsn(0x20));
+        assert_eq!(to_pg_time(keepalive.send_time), -1);
+        assert!(keepalive.reply_requested);
+
+        assert!(decode(Bytes::from_static(b"k")).is_err());
+        assert!(decode(Bytes::new()).is_err());
+    }
+}
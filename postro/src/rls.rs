@@ -0,0 +1,57 @@
+//! Row-level security context helper.
+//!
+//! A common pattern with postgres row-level security is to set the role and request
+//! scoped settings, such as `app.tenant_id`, which then referenced in policies with
+//! `current_setting('app.tenant_id')`.
+//!
+//! # Example
+//!
+//! ```no_run
+//! # async fn app(mut pool: postro::Pool) -> postro::Result<()> {
+//! let posts = postro::rls::scope(&mut pool, Some("app_user"), &[("app.tenant_id", "42")], async |tx| {
+//!     postro::query_as::<_, _, (i32,)>("SELECT id FROM post", tx).fetch_all().await
+//! })
+//! .await?;
+//! # Ok(())
+//! # }
+//! ```
+use crate::{
+    Result, common::quote_ident, executor::Executor, sql::SqlExt, transaction::Transaction,
+};
+
+/// Run `f` inside a transaction with given role and settings.
+///
+/// The role is set with `SET LOCAL ROLE` and each setting with `set_config(name, value, true)`,
+/// which only last until the end of transaction. This guarantees that the context is reset
+/// whether `f` succeed or not.
+///
+/// If `f` returns [`Ok`], the transaction is commited, otherwise it is rolled back.
+pub async fn scope<Exe, F, T>(
+    exe: Exe,
+    role: Option<&str>,
+    settings: &[(&str, &str)],
+    f: F,
+) -> Result<T>
+where
+    Exe: Executor,
+    F: AsyncFnOnce(&mut Transaction<Exe::Transport>) -> Result<T>,
+{
+    let mut tx = crate::begin(exe).await?;
+
+    if let Some(role) = role {
+        let sql = format!("SET LOCAL ROLE {}", quote_ident(role));
+        crate::query(sql.as_str().once(), &mut tx).execute().await?;
+    }
+
+    for (name, value) in settings {
+        crate::query("SELECT set_config($1, $2, true)", &mut tx)
+            .bind(*name)
+            .bind(*value)
+            .execute()
+            .await?;
+    }
+
+    let output = f(&mut tx).await?;
+    tx.commit().await?;
+    Ok(output)
+}
\ No newline at end of file
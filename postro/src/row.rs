@@ -8,12 +8,20 @@
 //! - [`Index`]
 //! - [`DecodeError`]
 use bytes::{Buf, Bytes};
-use std::{borrow::Cow, fmt, str::Utf8Error, string::FromUtf8Error};
+use std::{
+    borrow::Cow,
+    fmt,
+    str::Utf8Error,
+    string::FromUtf8Error,
+    sync::{Arc, OnceLock, RwLock},
+};
 
 use crate::{
+    codec::{Codecs, TypeInfo, TypeKind},
+    describe::ColumnInfo,
     common::{ByteStr, unit_error},
     ext::{BytesExt, FmtExt},
-    postgres::{Oid, PgType},
+    postgres::{Oid, PgFormat, PgType, suggest_rust_type, type_name},
 };
 
 // <https://www.postgresql.org/docs/current/protocol-message-formats.html#PROTOCOL-MESSAGE-FORMATS-ROWDESCRIPTION>
@@ -32,13 +40,72 @@ const SUFFIX: usize = size_of::<u32>()
 
 const OID_OFFSET: usize = size_of::<u32>() + size_of::<u16>();
 
+const FORMAT_OFFSET: usize = SUFFIX - size_of::<u16>();
+
+#[cfg(feature = "testing")]
+mod builder;
+#[cfg(feature = "testing")]
+pub use builder::RowBuilder;
+
+/// Column names which value is masked in [`Debug`][fmt::Debug] output.
+static REDACTED_COLUMNS: RwLock<Vec<Box<str>>> = RwLock::new(Vec::new());
+
+/// Set column names which value is masked in [`Debug`][fmt::Debug] output of [`Row`]
+/// and [`Column`], e.g: `password` or `ssn`, replacing previous names.
+///
+/// This prevent accidental `dbg!(row)` from leaking sensitive data to logs. The names
+/// are compared case insensitively. For query parameters, see [`Redacted`][1].
+///
+/// ```
+/// postro::row::redact_columns(["password", "ssn"]);
+/// ```
+///
+/// [1]: crate::encode::Redacted
+pub fn redact_columns<I>(names: I)
+where
+    I: IntoIterator,
+    I::Item: Into<Box<str>>,
+{
+    let names = names.into_iter().map(Into::into).collect();
+    *REDACTED_COLUMNS.write().unwrap_or_else(|e|e.into_inner()) = names;
+}
+
+fn is_redacted(name: &str) -> bool {
+    REDACTED_COLUMNS
+        .read()
+        .unwrap_or_else(|e|e.into_inner())
+        .iter()
+        .any(|e|e.eq_ignore_ascii_case(name))
+}
+
 /// Postgres row.
+///
+/// Cloning is cheap, the underlying buffer is reference counted.
+#[derive(Clone)]
 pub struct Row {
     field_len: u16,
     body: Bytes,
     values: Bytes,
+    /// lazily computed `(offset, len)` of each value, `len` of `-1` is NULL
+    offsets: OnceLock<Box<[(u32, i32)]>>,
+    /// shared by all rows in the same result set
+    desc: Arc<Description>,
+    codecs: Option<Arc<Codecs>>,
 }
 
+/// Lazily computed `RowDescription` information, shared by all rows in a result set.
+#[derive(Default)]
+struct Description {
+    /// `(offset, nul)` of each field name
+    fields: OnceLock<Box<[(u32, u32)]>>,
+    /// column ordinals requested by [`Row::ordinals`]
+    ordinals: OnceLock<(ColumnNames, Box<[Option<u16>]>)>,
+    /// column metadata requested by [`Row::columns`]
+    columns: OnceLock<Box<[ColumnInfo]>>,
+}
+
+type ColumnNames = &'static [&'static str];
+
 impl Row {
     /// `RowDescription` message
     pub(crate) fn new(mut bytes: Bytes) -> Self {
@@ -46,9 +113,18 @@ impl Row {
             field_len: bytes.get_u16(),
             body: bytes,
             values: Bytes::new(),
+            offsets: OnceLock::new(),
+            desc: Arc::default(),
+            codecs: None,
         }
     }
 
+    /// Attach connection custom type [`Codecs`].
+    pub(crate) fn with_codecs(mut self, codecs: Option<Arc<Codecs>>) -> Self {
+        self.codecs = codecs;
+        self
+    }
+
     /// `DataRow` message
     pub(crate) fn inner_clone(&self, mut bytes: Bytes) -> Row {
         assert_eq!(
@@ -59,6 +135,9 @@ impl Row {
             field_len: self.field_len,
             body: self.body.clone(),
             values: bytes,
+            offsets: OnceLock::new(),
+            desc: self.desc.clone(),
+            codecs: self.codecs.clone(),
         }
     }
 
@@ -74,25 +153,101 @@ impl Row {
 
     /// Try get and decode column.
     pub fn try_get<I: Index, R: Decode>(&self, idx: I) -> Result<R, DecodeError> {
-        let (offset,nul,nth) = idx.position(&self.body, self.field_len)?;
+        let (_, _, nth) = idx.position(&self.body, self.field_len)?;
+        self.try_get_ordinal(nth)
+    }
+
+    /// Try get and decode column by its ordinal.
+    ///
+    /// Unlike [`try_get`][Row::try_get] with `usize`, field description lookup
+    /// is computed once per result set.
+    pub fn try_get_ordinal<R: Decode>(&self, nth: u16) -> Result<R, DecodeError> {
+        let Some(&(offset, nul)) = self.fields().get(nth as usize) else {
+            return Err(DecodeError::IndexOutOfBounds(nth as _));
+        };
+        let (offset, nul) = (offset as usize, nul as usize);
 
         let name = ByteStr::from_utf8(self.body.slice(offset..nul))?;
 
-        let mut i = 0;
-        let mut values = self.values.clone();
-        let value = loop {
-            let len = values.get_i32();
-            let value = match len {
-                -1 => None,
-                _ => Some(values.split_to(len as _)),
-            };
-            if i == nth {
-                break value;
-            }
-            i += 1;
+        let value = match self.offsets()[nth as usize] {
+            (_, -1) => None,
+            (offset, len) => Some(self.values.slice(offset as usize..offset as usize + len as usize)),
         };
 
-        R::decode(Column::new(name, &self.body[nul + 1..], value))
+        R::decode(Column::new(name, &self.body[nul + 1..], value, self.codecs.clone()))
+    }
+
+    /// Resolve the ordinal of each column `names`, [`None`] if column not found.
+    ///
+    /// The result is cached per result set, so subsequent rows with the same `names`
+    /// does not lookup columns by name. This is used by [`FromRow`] derive macro.
+    pub fn ordinals(&self, names: &'static [&'static str]) -> Cow<'_, [Option<u16>]> {
+        let resolve = || {
+            names
+                .iter()
+                .map(|name| {
+                    self.fields()
+                        .iter()
+                        .position(|&(offset, nul)| &self.body[offset as usize..nul as usize] == name.as_bytes())
+                        .map(|nth| nth as u16)
+                })
+                .collect::<Box<[_]>>()
+        };
+
+        let (cached, ordinals) = self.desc.ordinals.get_or_init(|| (names, resolve()));
+        match std::ptr::eq(*cached, names) || cached == &names {
+            true => Cow::Borrowed(ordinals),
+            false => Cow::Owned(resolve().into_vec()),
+        }
+    }
+
+    /// Returns the columns metadata, computed once per result set.
+    ///
+    /// [`ColumnInfo::nullable`] is always [`None`], use [`describe`][crate::query::Query::describe]
+    /// to resolve it.
+    pub fn columns(&self) -> &[ColumnInfo] {
+        self.desc.columns.get_or_init(|| {
+            ColumnInfo::from_fields(self.body.clone(), self.field_len)
+                .unwrap_or_default()
+                .into()
+        })
+    }
+
+    /// Returns the field name positions, computed once per result set.
+    fn fields(&self) -> &[(u32, u32)] {
+        self.desc.fields.get_or_init(|| {
+            let mut offset = 0;
+            (0..self.field_len)
+                .map_while(|_| {
+                    let nul = offset + self.body[offset..].iter().position(|e| *e == b'\0')?;
+                    let field = (offset as u32, nul as u32);
+                    offset = nul + 1 + SUFFIX;
+                    Some(field)
+                })
+                .collect()
+        })
+    }
+
+    /// Returns the value offsets, computed once on first access.
+    ///
+    /// This avoid walking all preceding values for each column lookup.
+    fn offsets(&self) -> &[(u32, i32)] {
+        self.offsets.get_or_init(|| {
+            let mut values = &self.values[..];
+            let mut offset = 0;
+            (0..self.field_len)
+                .map(|_| {
+                    let len = values.get_i32();
+                    offset += size_of::<i32>();
+                    let start = offset;
+                    if len > 0 {
+                        values.advance(len as _);
+                        offset += len as usize;
+                    }
+                    (start as u32, len)
+                })
+                .collect()
+        })
     }
 
     /// Try decode type using [`FromRow`] implementation.
@@ -111,21 +266,32 @@ impl IntoIterator for Row {
             field_len: self.field_len,
             body: self.body,
             values: self.values,
+            codecs: self.codecs,
             iter_n: 0,
         }
     }
 }
 
 /// [`IntoIterator`] implementation from [`Row`].
-#[derive(Debug)]
 pub struct IntoIter {
     field_len: u16,
     body: Bytes,
     values: Bytes,
+    codecs: Option<Arc<Codecs>>,
 
     iter_n: u16,
 }
 
+impl fmt::Debug for IntoIter {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        // values is omitted, which may contains redacted column
+        f.debug_struct("IntoIter")
+            .field("field_len", &self.field_len)
+            .field("iter_n", &self.iter_n)
+            .finish_non_exhaustive()
+    }
+}
+
 impl IntoIter {
     /// Same as [`Iterator::next`] but returns [`Result`] instead.
     pub fn try_next(&mut self) -> Result<Column, DecodeError> {
@@ -159,7 +325,7 @@ impl Iterator for IntoIter {
         };
         self.iter_n += 1;
 
-        Some(Ok(Column::new(field_name, &column, value)))
+        Some(Ok(Column::new(field_name, &column, value, self.codecs.clone())))
     }
 }
 
@@ -175,6 +341,10 @@ impl fmt::Debug for Row {
             dbg.key(&key);
             match len {
                 -1 => dbg.value(&format_args!("NULL")),
+                len if is_redacted(&key) => {
+                    v.advance(len as _);
+                    dbg.value(&format_args!("<redacted>"))
+                },
                 len => dbg.value(&v.split_to(len as _).lossy()),
             };
         }
@@ -183,28 +353,76 @@ impl fmt::Debug for Row {
 }
 
 /// Postgres column.
-#[derive(Debug, Clone)]
+#[derive(Clone)]
 pub struct Column {
     oid: Oid,
+    format: PgFormat,
     value: Option<Bytes>,
     name: ByteStr,
+    codecs: Option<Arc<Codecs>>,
+}
+
+impl fmt::Debug for Column {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let mut dbg = f.debug_struct("Column");
+        dbg.field("oid", &self.oid)
+            .field("format", &self.format)
+            .field("name", &self.name);
+        match is_redacted(&self.name) {
+            true => dbg.field("value", &format_args!("<redacted>")),
+            false => dbg.field("value", &self.value),
+        };
+        dbg.finish_non_exhaustive()
+    }
 }
 
 impl Column {
     /// `body` is start of data **after** field name
-    fn new(name: ByteStr, body: &[u8], value: Option<Bytes>) -> Self {
+    pub(crate) fn new(name: ByteStr, body: &[u8], value: Option<Bytes>, codecs: Option<Arc<Codecs>>) -> Self {
         Self {
             name,
             oid: (&mut &body[OID_OFFSET..]).get_u32(),
-            value
+            format: PgFormat::from_code((&mut &body[FORMAT_OFFSET..]).get_u16()),
+            value,
+            codecs,
         }
     }
 
+    pub(crate) fn from_parts(
+        name: ByteStr,
+        oid: Oid,
+        format: PgFormat,
+        value: Option<Bytes>,
+        codecs: Option<Arc<Codecs>>,
+    ) -> Self {
+        Self { name, oid, format, value, codecs }
+    }
+
+    /// Returns the connection custom type [`Codecs`], if any registered.
+    pub fn codecs(&self) -> Option<&Codecs> {
+        self.codecs.as_deref()
+    }
+
+    pub(crate) fn shared_codecs(&self) -> Option<Arc<Codecs>> {
+        self.codecs.clone()
+    }
+
+    /// Returns the column type [`TypeInfo`], if resolved via
+    /// [`Config::resolve_type`][crate::Config::resolve_type].
+    pub fn type_info(&self) -> Option<&TypeInfo> {
+        self.codecs()?.type_info_by_oid(self.oid)
+    }
+
     /// Returns column [`Oid`].
     pub const fn oid(&self) -> Oid {
         self.oid
     }
 
+    /// Returns column value [`PgFormat`].
+    pub const fn format(&self) -> PgFormat {
+        self.format
+    }
+
     /// Returns column name.
     pub fn name(&self) -> &str {
         &self.name
@@ -247,18 +465,84 @@ impl Column {
     pub fn decode<D: Decode>(self) -> Result<D, DecodeError> {
         D::decode(self)
     }
+
+    /// Try decode the raw value with given function, without checking column [`Oid`].
+    ///
+    /// This is an escape hatch for types that are unknown to `postro`, e.g: types from
+    /// extensions, where the binary representation is known by the caller.
+    ///
+    /// Return [`DecodeError::Null`] if value is `NULL`.
+    ///
+    /// ```no_run
+    /// # fn test(col: postro::row::Column) -> Result<(), postro::DecodeError> {
+    /// // `citext` binary representation is the text itself
+    /// let name = col.decode_with(|value| Ok(String::from_utf8(value.to_vec())?))?;
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn decode_with<T, F>(self, f: F) -> Result<T, DecodeError>
+    where
+        F: FnOnce(&[u8]) -> Result<T, DecodeError>,
+    {
+        f(self.value.as_deref().ok_or(DecodeError::Null)?)
+    }
+
+    /// Returns [`DecodeError::OidMissmatch`] of decoding this column as `T`.
+    ///
+    /// `expected` is the [`Oid`] accepted by `T`, [`None`] if it accept multiple data types.
+    pub fn oid_missmatch<T>(&self, expected: Option<Oid>) -> DecodeError {
+        DecodeError::OidMissmatch(Box::new(OidMissmatch {
+            column: self.name.clone(),
+            rust_type: std::any::type_name::<T>(),
+            expected,
+            actual: self.oid,
+        }))
+    }
+
+    /// Override the column [`Oid`], allowing [`Decode`] implementation of other type
+    /// with the same binary representation to be used.
+    ///
+    /// ```no_run
+    /// # fn test(col: postro::row::Column) -> Result<(), postro::DecodeError> {
+    /// use postro::postgres::PgType;
+    ///
+    /// // decode `citext` as `String`
+    /// let name = col.with_oid(String::OID).decode::<String>()?;
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn with_oid(mut self, oid: Oid) -> Self {
+        self.oid = oid;
+        self
+    }
 }
 
 /// Query result with its rows affected.
 #[derive(Debug)]
 pub struct RowResult {
     pub rows_affected: u64,
+    pub(crate) tag: ByteStr,
+}
+
+impl RowResult {
+    /// Returns the command that was executed, e.g: `INSERT`, `UPDATE` or `MERGE`.
+    ///
+    /// For `CREATE TABLE AS`, the command is `SELECT`.
+    pub fn command(&self) -> &str {
+        self.tag.split_whitespace().next().unwrap_or_default()
+    }
 }
 
 // ===== Traits =====
 
 /// Type that can be constructed from a row.
 pub trait FromRow: Sized {
+    /// Preferred result format of each column by its position.
+    ///
+    /// Empty, which is the default, or all [`PgFormat::Binary`] requests all columns in
+    /// binary. Otherwise, the number of formats must match the number of result columns.
+    const FORMATS: &'static [PgFormat] = &[];
+
     /// Construct self from row.
     fn from_row(row: Row) -> Result<Self, DecodeError>;
 }
@@ -281,6 +565,8 @@ macro_rules! from_row_tuple {
         where
             $($t: Decode),*
         {
+            const FORMATS: &'static [PgFormat] = &[$($t::FORMAT),*];
+
             fn from_row(row: Row) -> Result<Self, DecodeError> {
                 Ok((
                     $(row.try_get($i)?),*,
@@ -297,6 +583,15 @@ from_row_tuple!(T0 0, T1 1, T2 2, T3 3);
 
 /// A type that can be constructed from [`Column`].
 pub trait Decode: Sized {
+    /// Preferred result format of the column, default to [`PgFormat::Binary`].
+    ///
+    /// This is only a preference, the format is honored when the column position is known
+    /// before the query is executed, e.g: decoding tuple or using [`query_scalar`][1].
+    /// Implementation should check [`Column::format`] to support both formats.
+    ///
+    /// [1]: crate::query_scalar
+    const FORMAT: PgFormat = PgFormat::Binary;
+
     /// Try decode self from column.
     fn decode(column: Column) -> Result<Self, DecodeError>;
 }
@@ -308,6 +603,8 @@ impl Decode for Column {
 }
 
 impl<T: Decode> Decode for Option<T> {
+    const FORMAT: PgFormat = T::FORMAT;
+
     fn decode(column: Column) -> Result<Self, DecodeError> {
         match column.is_null() {
             true => Ok(None),
@@ -322,21 +619,100 @@ impl Decode for () {
     }
 }
 
-impl Decode for i32 {
+macro_rules! decode_primitive {
+    ($($ty:ty),*) => {$(
+        impl Decode for $ty {
+            fn decode(col: Column) -> Result<Self, DecodeError> {
+                if col.oid() != Self::OID {
+                    return Err(col.oid_missmatch::<Self>(Some(Self::OID)));
+                }
+                let format = col.format();
+                let value = col.try_into_value()?;
+                match format {
+                    PgFormat::Binary => match value[..].try_into() {
+                        Ok(be) => Ok(<$ty>::from_be_bytes(be)),
+                        Err(_) => Err(DecodeError::Invalid(
+                            concat!("invalid ", stringify!($ty), " length").into()
+                        )),
+                    },
+                    PgFormat::Text => str::from_utf8(&value)?
+                        .parse()
+                        .map_err(|_| DecodeError::Invalid(
+                            concat!("invalid ", stringify!($ty), " text").into()
+                        )),
+                }
+            }
+        }
+    )*};
+}
+
+decode_primitive!(i16, i32, i64, u32, f32, f64);
+
+impl Decode for bool {
     fn decode(col: Column) -> Result<Self, DecodeError> {
         if col.oid() != Self::OID {
-            return Err(DecodeError::OidMissmatch);
+            return Err(col.oid_missmatch::<Self>(Some(Self::OID)));
+        }
+        match (col.format(), &col.try_into_value()?[..]) {
+            (PgFormat::Binary, [b]) => Ok(*b != 0),
+            (PgFormat::Text, b"t") => Ok(true),
+            (PgFormat::Text, b"f") => Ok(false),
+            _ => Err(DecodeError::Invalid("invalid bool".into())),
         }
-        let mut be = [0u8;size_of::<Self>()];
-        be.copy_from_slice(&col.try_into_value()?[..size_of::<Self>()]);
-        Ok(i32::from_be_bytes(be))
     }
 }
 
-impl Decode for String {
+impl Decode for Bytes {
     fn decode(col: Column) -> Result<Self, DecodeError> {
         if col.oid() != Self::OID {
-            return Err(DecodeError::OidMissmatch);
+            return Err(col.oid_missmatch::<Self>(Some(Self::OID)));
+        }
+        let format = col.format();
+        let value = col.try_into_value()?;
+        match format {
+            PgFormat::Binary => Ok(value),
+            PgFormat::Text => decode_bytea_hex(&value).map(Into::into),
+        }
+    }
+}
+
+impl Decode for Vec<u8> {
+    fn decode(col: Column) -> Result<Self, DecodeError> {
+        col.decode::<Bytes>().map(Into::into)
+    }
+}
+
+/// Decode `bytea` hex text format, e.g: `\xdeadbeef`.
+///
+/// <https://www.postgresql.org/docs/current/datatype-binary.html#DATATYPE-BINARY-BYTEA-HEX-FORMAT>
+fn decode_bytea_hex(value: &[u8]) -> Result<Vec<u8>, DecodeError> {
+    fn hex(b: u8) -> Result<u8, DecodeError> {
+        match b {
+            b'0'..=b'9' => Ok(b - b'0'),
+            b'a'..=b'f' => Ok(b - b'a' + 10),
+            b'A'..=b'F' => Ok(b - b'A' + 10),
+            _ => Err(DecodeError::Invalid("invalid bytea hex digit".into())),
+        }
+    }
+
+    let Some(value) = value.strip_prefix(b"\\x") else {
+        return Err(DecodeError::Invalid("unsupported bytea text format".into()));
+    };
+    if value.len() % 2 != 0 {
+        return Err(DecodeError::Invalid("invalid bytea hex length".into()));
+    }
+    value
+        .chunks_exact(2)
+        .map(|e| Ok(hex(e[0])? << 4 | hex(e[1])?))
+        .collect()
+}
+
+impl Decode for String {
+    fn decode(col: Column) -> Result<Self, DecodeError> {
+        // enum label is sent as text in both format
+        let is_enum = matches!(col.type_info().map(TypeInfo::kind), Some(TypeKind::Enum(_)));
+        if col.oid() != Self::OID && !is_enum {
+            return Err(col.oid_missmatch::<Self>(Some(Self::OID)));
         }
         Ok(String::from_utf8(col.try_into_value().map(Into::into)?)?)
     }
@@ -419,6 +795,54 @@ macro_rules! from {
     };
 }
 
+/// Column data type does not match the requested type.
+#[derive(Debug, Clone)]
+pub struct OidMissmatch {
+    pub(crate) column: ByteStr,
+    pub(crate) rust_type: &'static str,
+    pub(crate) expected: Option<Oid>,
+    pub(crate) actual: Oid,
+}
+
+impl OidMissmatch {
+    /// Returns the column name, empty if unknown.
+    pub fn column(&self) -> &str {
+        &self.column
+    }
+
+    /// Returns the requested rust type name.
+    pub fn rust_type(&self) -> &'static str {
+        self.rust_type
+    }
+
+    /// Returns the expected [`Oid`], [`None`] if the type accept multiple data types.
+    pub fn expected(&self) -> Option<Oid> {
+        self.expected
+    }
+
+    /// Returns the actual column [`Oid`].
+    pub fn actual(&self) -> Oid {
+        self.actual
+    }
+}
+
+impl fmt::Display for OidMissmatch {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str("data type missmatch")?;
+        if !self.column.is_empty() {
+            write!(f, " of column {:?}", &*self.column)?;
+        }
+        write!(f, ", cannot decode oid {} as `{}`", self.actual, self.rust_type)?;
+        if let Some(oid) = self.expected {
+            write!(f, " which expect oid {oid}")?;
+        }
+        if let (Some(name), Some(suggestion)) = (type_name(self.actual), suggest_rust_type(self.actual)) {
+            write!(f, "; column is `{name}`, consider {suggestion}")?;
+        }
+        Ok(())
+    }
+}
+
 /// An error when decoding row value.
 pub enum DecodeError {
     /// Postgres return non utf8 string.
@@ -427,10 +851,12 @@ pub enum DecodeError {
     ColumnNotFound(Cow<'static,str>),
     /// Index requested is out of bounds.
     IndexOutOfBounds(usize),
-    /// Oid requested missmatch.
-    OidMissmatch,
+    /// Column data type does not match the requested type.
+    OidMissmatch(Box<OidMissmatch>),
     /// Row is null.
     Null,
+    /// Value is not valid for the requested type.
+    Invalid(Cow<'static,str>),
     /// Failed to deserialize using `serde_json`.
     #[cfg(feature = "json")]
     Json(serde_json::error::Error),
@@ -443,8 +869,9 @@ impl fmt::Display for DecodeError {
             Self::Utf8(e) => write!(f, "{e}"),
             Self::ColumnNotFound(name) => write!(f, "column not found: {name:?}"),
             Self::IndexOutOfBounds(u) => write!(f, "index out of bounds: {u:?}"),
-            Self::OidMissmatch => write!(f, "data type missmatch"),
+            Self::OidMissmatch(e) => e.fmt(f),
             Self::Null => write!(f, "unexpected NULL value"),
+            Self::Invalid(reason) => write!(f, "invalid value: {reason}"),
             #[cfg(feature = "json")]
             Self::Json(e) => write!(f, "{e}"),
         }
@@ -464,3 +891,217 @@ impl fmt::Debug for DecodeError {
     }
 }
 
+
+#[cfg(test)]
+mod test {
+    use bytes::{BufMut, BytesMut};
+
+    use super::*;
+
+    #[test]
+    fn result_formats() {
+        struct Ltree;
+
+        impl Decode for Ltree {
+            const FORMAT: PgFormat = PgFormat::Text;
+
+            fn decode(_: Column) -> Result<Self, DecodeError> {
+                Ok(Self)
+            }
+        }
+
+        assert_eq!(<(i32, String)>::FORMATS, [PgFormat::Binary; 2]);
+        assert_eq!(<(i32, Option<Ltree>)>::FORMATS, [PgFormat::Binary, PgFormat::Text]);
+        assert!(Row::FORMATS.is_empty());
+    }
+
+    #[test]
+    fn row_offsets() {
+        const TEXT_OID: u32 = 25;
+        let values = [Some("foo"), None, None, Some(""), Some("bar")];
+
+        let mut desc = BytesMut::new();
+        desc.put_u16(values.len() as _);
+        for i in 0..values.len() {
+            desc.put_slice(format!("c{i}\0").as_bytes());
+            desc.put_u32(0);
+            desc.put_u16(0);
+            desc.put_u32(TEXT_OID);
+            desc.put_slice(&[0; SUFFIX - OID_OFFSET - size_of::<u32>()]);
+        }
+
+        let mut data = BytesMut::new();
+        data.put_u16(values.len() as _);
+        for value in values {
+            match value {
+                Some(value) => {
+                    data.put_i32(value.len() as _);
+                    data.put_slice(value.as_bytes());
+                },
+                None => data.put_i32(-1),
+            }
+        }
+
+        let row = Row::new(desc.freeze()).inner_clone(data.freeze());
+        for (i, value) in values.into_iter().enumerate().rev() {
+            assert_eq!(row.try_get::<_, Option<String>>(i).unwrap().as_deref(), value);
+        }
+        assert_eq!(row.try_get::<_, String>("c4").unwrap(), "bar");
+
+        let columns = row.columns();
+        assert_eq!(columns.len(), values.len());
+        assert_eq!((columns[4].name(), columns[4].oid()), ("c4", TEXT_OID));
+
+        let Err(DecodeError::OidMissmatch(err)) = row.try_get::<_, i32>("c4") else {
+            panic!("expected oid missmatch");
+        };
+        assert_eq!((err.column(), err.rust_type()), ("c4", "i32"));
+        assert_eq!((err.expected(), err.actual()), (Some(i32::OID), TEXT_OID));
+        assert_eq!(
+            err.to_string(),
+            "data type missmatch of column \"c4\", cannot decode oid 25 as `i32` which expect oid 23; \
+            column is `text`, consider `String`",
+        );
+    }
+
+    #[test]
+    fn oid_missmatch_suggestion() {
+        let column = |oid| Column::from_parts("amount".into(), oid, PgFormat::Binary, None, None);
+
+        let err = column(1700).oid_missmatch::<f64>(Some(f64::OID));
+        assert!(err.to_string().ends_with(
+            "column is `numeric`, consider `rust_decimal::Decimal` (feature `rust_decimal`) \
+            or `bigdecimal::BigDecimal` (feature `bigdecimal`)"
+        ), "{err}");
+
+        // unknown type has no suggestion
+        let err = column(16384).oid_missmatch::<String>(Some(String::OID));
+        assert!(err.to_string().ends_with("cannot decode oid 16384 as `alloc::string::String` which expect oid 25"), "{err}");
+    }
+
+    #[cfg(feature = "testing")]
+    #[test]
+    fn row_builder() {
+        let row = RowBuilder::new()
+            .column("id", 7)
+            .raw_column("name", String::OID, PgFormat::Text, Some(b"foo"))
+            .raw_column("deleted_at", 1184, PgFormat::Binary, None)
+            .build();
+
+        assert_eq!(row.len(), 3);
+        assert_eq!(row.try_get::<_, i32>("id").unwrap(), 7);
+        assert_eq!(row.try_get::<_, String>("name").unwrap(), "foo");
+        assert!(row.try_get::<_, Option<String>>(2).unwrap().is_none());
+
+        let columns = row.columns();
+        assert_eq!((columns[0].oid(), columns[1].oid(), columns[2].oid()), (i32::OID, String::OID, 1184));
+        assert!(RowBuilder::new().build().is_empty());
+    }
+
+    #[test]
+    fn row_ordinals() {
+        const TEXT_OID: u32 = 25;
+        const NAMES: &[&str] = &["c2", "c0", "c9"];
+
+        let mut desc = BytesMut::new();
+        desc.put_u16(3);
+        for i in 0..3 {
+            desc.put_slice(format!("c{i}\0").as_bytes());
+            desc.put_u32(0);
+            desc.put_u16(0);
+            desc.put_u32(TEXT_OID);
+            desc.put_slice(&[0; SUFFIX - OID_OFFSET - size_of::<u32>()]);
+        }
+        let desc = Row::new(desc.freeze());
+
+        for value in ["foo", "bar"] {
+            let mut data = BytesMut::new();
+            data.put_u16(3);
+            for _ in 0..3 {
+                data.put_i32(value.len() as _);
+                data.put_slice(value.as_bytes());
+            }
+            let row = desc.inner_clone(data.freeze());
+
+            let ordinals = row.ordinals(NAMES);
+            assert!(matches!(ordinals, Cow::Borrowed(_)));
+            assert_eq!(&ordinals[..], [Some(2), Some(0), None]);
+            assert_eq!(row.try_get_ordinal::<String>(2).unwrap(), value);
+            assert!(row.try_get_ordinal::<String>(3).is_err());
+
+            // different names is resolved without cache
+            assert_eq!(&row.ordinals(&["c1"])[..], [Some(1)]);
+        }
+    }
+
+    #[test]
+    fn redacted_debug() {
+        const TEXT_OID: u32 = 25;
+
+        redact_columns(["Test_Secret"]);
+
+        let mut desc = BytesMut::new();
+        desc.put_u16(2);
+        for name in ["test_name", "test_secret"] {
+            desc.put_slice(format!("{name}\0").as_bytes());
+            desc.put_u32(0);
+            desc.put_u16(0);
+            desc.put_u32(TEXT_OID);
+            desc.put_slice(&[0; SUFFIX - OID_OFFSET - size_of::<u32>()]);
+        }
+        let desc = Row::new(desc.freeze());
+
+        let mut data = BytesMut::new();
+        data.put_u16(2);
+        for value in ["Deez", "hunter2"] {
+            data.put_i32(value.len() as _);
+            data.put_slice(value.as_bytes());
+        }
+        let data = data.freeze();
+        let row = desc.inner_clone(data.clone());
+
+        let debug = format!("{row:?}");
+        assert!(debug.contains("Deez"));
+        assert!(!debug.contains("hunter2"));
+        assert!(debug.contains("<redacted>"));
+
+        let column = desc.inner_clone(data).into_iter().nth(1).unwrap().unwrap();
+        assert!(!format!("{column:?}").contains("hunter2"));
+
+        redact_columns(Vec::<String>::new());
+        assert!(format!("{row:?}").contains("hunter2"));
+    }
+
+    fn column(oid: Oid, format: PgFormat, value: &[u8]) -> Column {
+        let mut desc = BytesMut::new();
+        desc.put_u32(0);
+        desc.put_u16(0);
+        desc.put_u32(oid);
+        desc.put_i16(-1);
+        desc.put_i32(-1);
+        desc.put_u16(format.format_code());
+        Column::new("c".into(), &desc, Some(Bytes::copy_from_slice(value)), None)
+    }
+
+    #[test]
+    fn decode_primitive() {
+        use PgFormat::*;
+
+        assert_eq!(column(i16::OID, Binary, &[0xff, 0xfe]).decode::<i16>().unwrap(), -2);
+        assert_eq!(column(i64::OID, Binary, &420i64.to_be_bytes()).decode::<i64>().unwrap(), 420);
+        assert_eq!(column(f64::OID, Binary, &1.5f64.to_be_bytes()).decode::<f64>().unwrap(), 1.5);
+        assert_eq!(column(u32::OID, Binary, &[0, 0, 0, 25]).decode::<u32>().unwrap(), 25);
+        assert!(column(bool::OID, Binary, &[1]).decode::<bool>().unwrap());
+        assert!(column(i32::OID, Binary, &[0, 1]).decode::<i32>().is_err());
+        assert!(column(i64::OID, Binary, &[0; 8]).decode::<i32>().is_err());
+
+        assert_eq!(column(i64::OID, Text, b"-420").decode::<i64>().unwrap(), -420);
+        assert_eq!(column(f32::OID, Text, b"0.25").decode::<f32>().unwrap(), 0.25);
+        assert!(column(f64::OID, Text, b"NaN").decode::<f64>().unwrap().is_nan());
+        assert!(!column(bool::OID, Text, b"f").decode::<bool>().unwrap());
+
+        assert_eq!(column(Bytes::OID, Binary, b"\\xff").decode::<Vec<u8>>().unwrap(), b"\\xff");
+        assert_eq!(column(Bytes::OID, Text, b"\\xDEADbeef").decode::<Vec<u8>>().unwrap(), [0xde, 0xad, 0xbe, 0xef]);
+        assert!(column(Bytes::OID, Text, b"\\xf").decode::<Bytes>().is_err());
+    }
+}
@@ -0,0 +1,98 @@
+//! Construct [`Row`] without connection, requires `testing` feature.
+use bytes::{Buf, BufMut, BytesMut};
+
+use super::{OID_OFFSET, Row, SUFFIX};
+use crate::{
+    Encode,
+    ext::BindParams,
+    postgres::{Oid, PgFormat},
+};
+
+/// Builder to construct [`Row`], e.g: to unit test [`FromRow`][super::FromRow]
+/// implementation without database.
+///
+/// ```
+/// use postro::{Row, postgres::PgFormat, row::RowBuilder};
+///
+/// let row: Row = RowBuilder::new()
+///     .column("id", 420)
+///     .column("name", "Deez")
+///     .raw_column("email", 25, PgFormat::Text, None)
+///     .build();
+///
+/// let (id, name, email) = row.decode::<(i32, String, Option<String>)>().unwrap();
+/// assert_eq!((id, name.as_str(), email), (420, "Deez", None));
+/// ```
+#[derive(Debug, Default)]
+pub struct RowBuilder {
+    len: u16,
+    desc: BytesMut,
+    data: BytesMut,
+}
+
+impl RowBuilder {
+    /// Create new empty [`RowBuilder`].
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Add column with value in binary format, the column [`Oid`] is from [`Encode`].
+    ///
+    /// # Panics
+    ///
+    /// Panics if `value` failed to encode, or the number of columns exceeds `u16::MAX`.
+    pub fn column<'q>(self, name: &str, value: impl Encode<'q>) -> Self {
+        let mut value = value.encode();
+        if let Some(reason) = value.error() {
+            panic!("failed to encode column {name:?}: {reason}");
+        }
+        let oid = value.oid();
+        let bytes = match value.size() {
+            -1 => None,
+            _ => Some(value.copy_to_bytes(value.remaining())),
+        };
+        self.raw_column(name, oid, PgFormat::Binary, bytes.as_deref())
+    }
+
+    /// Add column with the given [`Oid`], format and raw value, [`None`] for `NULL`.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the number of columns exceeds `u16::MAX`, or `value` exceeds `i32::MAX` bytes.
+    pub fn raw_column(mut self, name: &str, oid: Oid, format: PgFormat, value: Option<&[u8]>) -> Self {
+        self.len = self.len.checked_add(1).expect("too many columns");
+
+        // RowDescription field, with zero table oid, attribute number,
+        // data type size, and type modifier
+        self.desc.put_slice(name.as_bytes());
+        self.desc.put_u8(b'\0');
+        self.desc.put_slice(&[0; OID_OFFSET]);
+        self.desc.put_u32(oid);
+        self.desc.put_slice(&[0; SUFFIX - OID_OFFSET - size_of::<u32>() - size_of::<u16>()]);
+        self.desc.put_u16(format.format_code());
+
+        // DataRow value
+        match value {
+            Some(value) => {
+                self.data.put_i32(i32::try_from(value.len()).expect("value too large"));
+                self.data.put_slice(value);
+            },
+            None => self.data.put_i32(-1),
+        }
+
+        self
+    }
+
+    /// Build the [`Row`].
+    pub fn build(self) -> Row {
+        let mut desc = BytesMut::with_capacity(size_of::<u16>() + self.desc.len());
+        desc.put_u16(self.len);
+        desc.put(self.desc);
+
+        let mut data = BytesMut::with_capacity(size_of::<u16>() + self.data.len());
+        data.put_u16(self.len);
+        data.put(self.data);
+
+        Row::new(desc.freeze()).inner_clone(data.freeze())
+    }
+}
@@ -1,4 +1,17 @@
 //! Sql string operation.
+use std::{
+    borrow::Cow,
+    hash::{DefaultHasher, Hash, Hasher},
+};
+
+use crate::{Result, encode::Encoded};
+
+mod commenter;
+mod normalize;
+
+pub use commenter::{Tags, clear_commenter, set_commenter};
+pub use normalize::normalize;
+pub(crate) use commenter::comment;
 
 /// Type that represent sql string.
 pub trait Sql {
@@ -7,6 +20,22 @@ pub trait Sql {
 
     /// Return `true` if current statement should be cached.
     fn persistent(&self) -> bool;
+
+    /// Take the parameters carried by the sql, e.g: statement built by query builder.
+    ///
+    /// The parameters are bound before parameters from [`Query::bind`][1].
+    ///
+    /// [1]: crate::query::Query::bind
+    fn params(&mut self) -> Result<Vec<Encoded<'static>>> {
+        Ok(Vec::new())
+    }
+
+    /// Returns the precomputed statement cache key, see [`PreHashedSql`].
+    ///
+    /// If `None`, the key is computed from [`Sql::sql`] on each execution.
+    fn id(&self) -> Option<u64> {
+        None
+    }
 }
 
 impl Sql for &str {
@@ -19,6 +48,82 @@ impl Sql for &str {
     }
 }
 
+impl Sql for Cow<'static, str> {
+    fn sql(&self) -> &str {
+        self
+    }
+
+    fn persistent(&self) -> bool {
+        true
+    }
+}
+
+/// Returns the statement cache key of the sql string.
+pub(crate) fn sql_id(sql: &str) -> u64 {
+    let mut buf = DefaultHasher::new();
+    sql.trim().hash(&mut buf);
+    buf.finish()
+}
+
+/// Persistent sql string with precomputed statement cache key.
+///
+/// Hashing the sql is skipped on each execution, useful for frameworks that store
+/// statements once and execute them repeatedly.
+///
+/// ```
+/// use postro::sql::{PreHashedSql, Sql};
+/// static SQL: std::sync::LazyLock<PreHashedSql> = std::sync::LazyLock::new(|| {
+///     PreHashedSql::new("SELECT * FROM users WHERE id = $1")
+/// });
+/// assert_eq!(SQL.sql(), "SELECT * FROM users WHERE id = $1");
+/// ```
+#[derive(Debug, Clone)]
+pub struct PreHashedSql {
+    sql: Cow<'static, str>,
+    id: u64,
+}
+
+impl PreHashedSql {
+    /// Create new [`PreHashedSql`], computing the cache key once.
+    pub fn new(sql: impl Into<Cow<'static, str>>) -> Self {
+        let sql = sql.into();
+        Self { id: sql_id(&sql), sql }
+    }
+
+    /// Returns the statement cache key.
+    pub fn id(&self) -> u64 {
+        self.id
+    }
+}
+
+impl Sql for PreHashedSql {
+    fn sql(&self) -> &str {
+        &self.sql
+    }
+
+    fn persistent(&self) -> bool {
+        true
+    }
+
+    fn id(&self) -> Option<u64> {
+        Some(self.id)
+    }
+}
+
+impl Sql for &PreHashedSql {
+    fn sql(&self) -> &str {
+        &self.sql
+    }
+
+    fn persistent(&self) -> bool {
+        true
+    }
+
+    fn id(&self) -> Option<u64> {
+        Some(self.id)
+    }
+}
+
 /// Non persistent query string.
 #[derive(Debug)]
 pub struct SqlOnce<'sql>(&'sql str);
@@ -33,10 +138,83 @@ impl Sql for SqlOnce<'_> {
     }
 }
 
+/// Sql string prefixed with comment.
+///
+/// The comment is part of the statement cache key, so each distinct comment would be prepared
+/// as a separate statement. Only [`SqlExt::tag`] is cached, see [`SqlExt::comment`].
+#[derive(Debug)]
+pub struct SqlComment {
+    sql: String,
+    persistent: bool,
+}
+
+impl SqlComment {
+    fn new<S: Sql>(sql: S, comment: &str, persistent: bool) -> Self {
+        // prevent comment from terminating early, or nesting as block comments nest in postgres
+        let comment = comment.replace("/*", "/ *").replace("*/", "* /");
+        Self {
+            sql: format!("/* {comment} */ {}", sql.sql()),
+            persistent: persistent && sql.persistent(),
+        }
+    }
+
+    /// Disable statement caching.
+    pub fn once(mut self) -> Self {
+        self.persistent = false;
+        self
+    }
+}
+
+impl Sql for SqlComment {
+    fn sql(&self) -> &str {
+        &self.sql
+    }
+
+    fn persistent(&self) -> bool {
+        self.persistent
+    }
+}
+
 /// Extension trait for easier query persistence config.
 pub trait SqlExt<'a> {
     /// Disable statement caching.
     fn once(self) -> SqlOnce<'a>;
+
+    /// Prepend a `/* comment */` to the sql string.
+    ///
+    /// Useful to attribute statements in `pg_stat_statements` or server logs.
+    ///
+    /// The comment is usually unique per request, so the statement is not cached, and it
+    /// is parsed on every execution. Use [`tag`][SqlExt::tag] for a static comment.
+    ///
+    /// ```
+    /// use postro::sql::{Sql, SqlExt};
+    /// let sql = "SELECT 1".comment("request_id=42");
+    /// assert_eq!(sql.sql(), "/* request_id=42 */ SELECT 1");
+    /// // parsed on every execution
+    /// assert!(!sql.persistent());
+    ///
+    /// let sql = "SELECT 1".tag("health");
+    /// assert_eq!(sql.sql(), "/* tag=health */ SELECT 1");
+    /// assert!(sql.persistent());
+    /// ```
+    fn comment(self, comment: &str) -> SqlComment
+    where
+        Self: Sql + Sized,
+    {
+        SqlComment::new(self, comment, false)
+    }
+
+    /// Prepend a `/* tag=name */` comment to the sql string.
+    ///
+    /// Unlike [`comment`][SqlExt::comment], the statement is cached, so `name` should be
+    /// static, e.g: the name of the query.
+    fn tag(self, name: &str) -> SqlComment
+    where
+        Self: Sql + Sized,
+    {
+        SqlComment::new(self, &format!("tag={name}"), true)
+    }
 }
 
 impl<'a> SqlExt<'a> for &'a str {
@@ -51,3 +229,24 @@ impl<'a> SqlExt<'a> for SqlOnce<'a> {
     }
 }
 
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn prehashed_id() {
+        let sql = PreHashedSql::new(" SELECT 1\n");
+        assert_eq!(sql.id(), sql_id("SELECT 1"));
+        assert_eq!(Sql::id(&sql), Some(sql_id("SELECT 1")));
+        assert_eq!(Sql::id(&"SELECT 1"), None);
+    }
+
+    #[test]
+    fn comment_escape() {
+        let sql = "SELECT 1".comment("a */ DROP TABLE users; /* b");
+        assert_eq!(sql.sql(), "/* a * / DROP TABLE users; / * b */ SELECT 1");
+        let sql = "SELECT 1".comment("/*/");
+        assert_eq!(sql.sql(), "/* / * / */ SELECT 1");
+    }
+}
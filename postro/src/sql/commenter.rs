@@ -0,0 +1,157 @@
+//! [sqlcommenter] style comment appended to outgoing statements.
+//!
+//! [sqlcommenter]: https://google.github.io/sqlcommenter/spec/
+use std::{
+    borrow::Cow,
+    fmt::Write,
+    sync::{Arc, RwLock},
+};
+
+type Commenter = Arc<dyn Fn(&mut Tags) + Send + Sync>;
+
+static COMMENTER: RwLock<Option<Commenter>> = RwLock::new(None);
+
+/// Keys that are unique per request, statement with these tags is not cached.
+const VOLATILE_KEYS: &[&str] = &["traceparent", "tracestate"];
+
+/// Set the function that provides tags appended to every statement as [sqlcommenter]
+/// comment, replacing previous function.
+///
+/// The function is called before each statement is sent, e.g: to read the current trace
+/// context from `tracing` span or OpenTelemetry context. When no tags are added, the
+/// statement is sent unchanged.
+///
+/// The comment is part of the statement cache key, so each distinct set of tags is
+/// prepared as a separate statement. Statement with `traceparent` or `tracestate` tag
+/// is unique per request, thus it is prepared as unnamed statement instead of cached.
+///
+/// Applies to [`query`][crate::query()], [`raw_sql`][crate::raw_sql()],
+/// [`pipeline`][crate::pipeline()] and [`copy_in`][crate::copy::copy_in].
+///
+/// ```
+/// use postro::sql::{self, Tags};
+///
+/// fn current_traceparent() -> Option<String> {
+///     // e.g: from OpenTelemetry context of the current `tracing` span
+///     Some("00-5bd66ef5095369c7b0d1f8f4bd33716a-c532cb4098ac3dd2-01".into())
+/// }
+///
+/// sql::set_commenter(|tags: &mut Tags| {
+///     tags.add("application", "billing-svc");
+///     if let Some(traceparent) = current_traceparent() {
+///         tags.add("traceparent", traceparent);
+///     }
+/// });
+/// # sql::clear_commenter();
+/// ```
+///
+/// [sqlcommenter]: https://google.github.io/sqlcommenter/spec/
+pub fn set_commenter(f: impl Fn(&mut Tags) + Send + Sync + 'static) {
+    *COMMENTER.write().unwrap_or_else(|e|e.into_inner()) = Some(Arc::new(f));
+}
+
+/// Remove the function set via [`set_commenter`].
+pub fn clear_commenter() {
+    *COMMENTER.write().unwrap_or_else(|e|e.into_inner()) = None;
+}
+
+/// Tags of sqlcommenter comment, see [`set_commenter`].
+#[derive(Debug, Default)]
+pub struct Tags {
+    tags: Vec<(Cow<'static, str>, String)>,
+}
+
+impl Tags {
+    /// Add tag, replacing previous value of the same `key`.
+    pub fn add(&mut self, key: impl Into<Cow<'static, str>>, value: impl Into<String>) {
+        let (key, value) = (key.into(), value.into());
+        match self.tags.iter_mut().find(|(e, _)| *e == key) {
+            Some((_, old)) => *old = value,
+            None => self.tags.push((key, value)),
+        }
+    }
+
+    /// Returns `true` if no tags added.
+    pub fn is_empty(&self) -> bool {
+        self.tags.is_empty()
+    }
+
+    /// Returns `true` if any tag is unique per request.
+    fn is_volatile(&self) -> bool {
+        self.tags.iter().any(|(key, _)| VOLATILE_KEYS.contains(&key.as_ref()))
+    }
+
+    /// Append the comment to `sql`, before trailing semicolon.
+    fn append_to(mut self, sql: &str) -> String {
+        self.tags.sort_by(|a, b| a.0.cmp(&b.0));
+
+        let sql = sql.trim_end();
+        let (sql, semicolon) = match sql.strip_suffix(';') {
+            Some(sql) => (sql.trim_end(), ";"),
+            None => (sql, ""),
+        };
+
+        let mut commented = String::with_capacity(sql.len() + 64);
+        commented.push_str(sql);
+        commented.push_str(" /*");
+        for (i, (key, value)) in self.tags.iter().enumerate() {
+            if i != 0 {
+                commented.push(',');
+            }
+            encode(&mut commented, key);
+            commented.push_str("='");
+            encode(&mut commented, value);
+            commented.push('\'');
+        }
+        commented.push_str("*/");
+        commented.push_str(semicolon);
+        commented
+    }
+}
+
+/// Percent encode all but unreserved characters, which also escapes `'` and `*/`.
+fn encode(buf: &mut String, value: &str) {
+    for b in value.bytes() {
+        match b {
+            b'A'..=b'Z' | b'a'..=b'z' | b'0'..=b'9' | b'-' | b'_' | b'.' | b'~' => buf.push(b as char),
+            _ => write!(buf, "%{b:02X}").expect("write to String is infallible"),
+        }
+    }
+}
+
+/// Returns the commented sql and whether it can be cached, or [`None`] if no tags.
+pub(crate) fn comment(sql: &str) -> Option<(String, bool)> {
+    let commenter = COMMENTER.read().unwrap_or_else(|e|e.into_inner()).clone()?;
+    let mut tags = Tags::default();
+    commenter(&mut tags);
+    if tags.is_empty() {
+        return None;
+    }
+    let persistent = !tags.is_volatile();
+    Some((tags.append_to(sql), persistent))
+}
+
+#[cfg(test)]
+mod test {
+    use super::Tags;
+
+    #[test]
+    fn append_comment() {
+        let mut tags = Tags::default();
+        tags.add("traceparent", "00-5bd66ef5095369c7b0d1f8f4bd33716a-c532cb4098ac3dd2-01");
+        tags.add("route", "/users/{id}");
+        tags.add("action", "it's */");
+        assert!(tags.is_volatile());
+        assert_eq!(
+            tags.append_to("SELECT 1 ;\n"),
+            "SELECT 1 /*action='it%27s%20%2A%2F',route='%2Fusers%2F%7Bid%7D',\
+            traceparent='00-5bd66ef5095369c7b0d1f8f4bd33716a-c532cb4098ac3dd2-01'*/;",
+        );
+
+        let mut tags = Tags::default();
+        tags.add("application", "billing");
+        tags.add("application", "billing-svc");
+        assert!(!tags.is_volatile());
+        assert_eq!(tags.append_to("SELECT 1"), "SELECT 1 /*application='billing-svc'*/");
+    }
+}
@@ -0,0 +1,167 @@
+//! Statement normalization for the statement cache key.
+
+/// Strip comments and collapse whitespace of `sql`.
+///
+/// String literals, quoted identifiers and dollar quoted strings are kept as is, so
+/// the returned sql is equivalent to `sql`. Used as the statement cache key when
+/// [`Config::normalize_statements`][crate::Config::normalize_statements] is enabled.
+///
+/// ```
+/// use postro::sql::normalize;
+///
+/// let sql = "SELECT id -- primary key\n  FROM post /* all */ WHERE title = '  a  '";
+/// assert_eq!(normalize(sql), "SELECT id FROM post WHERE title = '  a  '");
+/// ```
+pub fn normalize(sql: &str) -> String {
+    let bytes = sql.as_bytes();
+    let mut out = String::with_capacity(sql.len());
+    let mut space = false;
+    let mut i = 0;
+
+    while i < bytes.len() {
+        match bytes[i] {
+            b'-' if bytes.get(i + 1) == Some(&b'-') => {
+                i = memchr(bytes, i, b'\n');
+                space = true;
+                continue;
+            },
+            b'/' if bytes.get(i + 1) == Some(&b'*') => {
+                i = block_comment_end(bytes, i);
+                space = true;
+                continue;
+            },
+            e if e.is_ascii_whitespace() => {
+                i += 1;
+                space = true;
+                continue;
+            },
+            _ => { },
+        }
+
+        if space && !out.is_empty() {
+            out.push(' ');
+        }
+        space = false;
+
+        let end = match bytes[i] {
+            b'\'' => {
+                // `E'...'` escape string constant
+                let escape = i > 0
+                    && matches!(bytes[i - 1], b'E' | b'e')
+                    && (i < 2 || !is_ident(bytes[i - 2]));
+                quoted_end(bytes, i, b'\'', escape)
+            },
+            b'"' => quoted_end(bytes, i, b'"', false),
+            b'$' if i == 0 || !is_ident(bytes[i - 1]) => dollar_end(sql, i),
+            _ => i + 1,
+        };
+        // the rest of the token, delimiters are all ascii so `end` is a char boundary
+        let end = (end..bytes.len())
+            .find(|&j| is_delimiter(bytes[j]))
+            .unwrap_or(bytes.len())
+            .max(end);
+
+        out.push_str(&sql[i..end]);
+        i = end;
+    }
+
+    out
+}
+
+fn is_ident(b: u8) -> bool {
+    b.is_ascii_alphanumeric() || b == b'_' || b == b'$' || !b.is_ascii()
+}
+
+fn is_delimiter(b: u8) -> bool {
+    matches!(b, b'-' | b'/' | b'\'' | b'"' | b'$') || b.is_ascii_whitespace()
+}
+
+/// Returns the index of `needle` after `start`, or the end of `bytes`.
+fn memchr(bytes: &[u8], start: usize, needle: u8) -> usize {
+    bytes[start..]
+        .iter()
+        .position(|e| *e == needle)
+        .map_or(bytes.len(), |e| start + e)
+}
+
+/// Returns the end of possibly nested block comment starting at `start`.
+fn block_comment_end(bytes: &[u8], start: usize) -> usize {
+    let mut depth = 0;
+    let mut i = start;
+    while i < bytes.len() {
+        match (bytes[i], bytes.get(i + 1)) {
+            (b'/', Some(b'*')) => {
+                depth += 1;
+                i += 2;
+            },
+            (b'*', Some(b'/')) => {
+                depth -= 1;
+                i += 2;
+                if depth == 0 {
+                    return i;
+                }
+            },
+            _ => i += 1,
+        }
+    }
+    bytes.len()
+}
+
+/// Returns the end of quoted token starting at `start`, where doubled `quote` is escaped.
+fn quoted_end(bytes: &[u8], start: usize, quote: u8, backslash: bool) -> usize {
+    let mut i = start + 1;
+    while i < bytes.len() {
+        match bytes[i] {
+            b'\\' if backslash => i += 2,
+            e if e == quote && bytes.get(i + 1) == Some(&quote) => i += 2,
+            e if e == quote => return i + 1,
+            _ => i += 1,
+        }
+    }
+    bytes.len()
+}
+
+/// Returns the end of dollar quoted string starting at `start`, or the end of `$n`
+/// placeholder.
+fn dollar_end(sql: &str, start: usize) -> usize {
+    let bytes = sql.as_bytes();
+    let tag_end = (start + 1..bytes.len())
+        .find(|&j| !is_ident(bytes[j]) || bytes[j] == b'$')
+        .unwrap_or(bytes.len());
+
+    let is_tag = bytes.get(tag_end) == Some(&b'$')
+        && !bytes.get(start + 1).is_some_and(u8::is_ascii_digit);
+    if !is_tag {
+        return start + 1;
+    }
+
+    let tag = &sql[start..=tag_end];
+    match sql[tag_end + 1..].find(tag) {
+        Some(body) => tag_end + 1 + body + tag.len(),
+        None => bytes.len(),
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::normalize;
+
+    #[test]
+    fn normalize_sql() {
+        let cases = [
+            ("  SELECT\n\t1  ", "SELECT 1"),
+            ("SELECT/* a /* nested */ b */1", "SELECT 1"),
+            ("SELECT 1 -- trailing", "SELECT 1"),
+            ("SELECT $1 - -$2 / 2", "SELECT $1 - -$2 / 2"),
+            ("SELECT '--  not comment', \"a  b\"", "SELECT '--  not comment', \"a  b\""),
+            ("SELECT 'it''s  ',  E'\\'  x'", "SELECT 'it''s  ', E'\\'  x'"),
+            ("SELECT $$ a  -- b $$,  $fn$ $$ $fn$", "SELECT $$ a  -- b $$, $fn$ $$ $fn$"),
+            ("SELECT foo$bar  FROM t", "SELECT foo$bar FROM t"),
+            ("SELECT 'ünï  code'  ,  ü", "SELECT 'ünï  code' , ü"),
+            ("SELECT 'unterminated  ", "SELECT 'unterminated  "),
+        ];
+        for (sql, expected) in cases {
+            assert_eq!(normalize(sql), expected, "{sql:?}");
+        }
+    }
+}
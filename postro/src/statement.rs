@@ -64,7 +64,6 @@ macro_rules! delegate {
                 Self(Id::unnamed())
             }
 
-            #[allow(unused, reason = "Portal `next` used later")]
             pub(crate) fn next() -> Self {
                 static ID: AtomicId = AtomicId::new(0);
                 Self(Id::next(&ID))
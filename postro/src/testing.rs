@@ -0,0 +1,310 @@
+//! Test utilities, requires `testing` feature.
+//!
+//! Guard against regression such as accidental sequential scan by asserting the
+//! plan returned by `EXPLAIN (FORMAT JSON)`.
+//!
+//! For isolated database per test, see [`run_test`] or `#[postro::test]`.
+//!
+//! # Example
+//!
+//! ```no_run
+//! # async fn test(mut conn: postro::Connection) -> postro::Result<()> {
+//! let query = postro::query("SELECT * FROM users WHERE email = $1", &mut conn).bind("foo@bar.com");
+//!
+//! postro::testing::assert_plan(query, |plan| plan.uses_index("users_email_idx")).await?;
+//! # Ok(())
+//! # }
+//! ```
+use std::fmt;
+
+use serde_json::Value;
+
+use crate::{
+    Decode, DecodeError, Result,
+    encode::Encoded,
+    executor::Executor,
+    postgres::Oid,
+    query::{Query, StreamScalar},
+    row::Column,
+    sql::Sql,
+};
+
+#[cfg(feature = "tokio")]
+use crate::{Config, Connection, Error, Pool, PoolConfig, common::ByteStr, query::raw_sql};
+
+/// Query plan returned by `EXPLAIN (FORMAT JSON)`.
+///
+/// See <https://www.postgresql.org/docs/current/using-explain.html>.
+#[derive(Debug, Clone)]
+pub struct Plan {
+    json: Value,
+}
+
+impl Plan {
+    /// Returns the raw json output, which is an array of one object.
+    pub fn json(&self) -> &Value {
+        &self.json
+    }
+
+    /// Returns the root plan node.
+    pub fn root(&self) -> &Value {
+        &self.json[0]["Plan"]
+    }
+
+    /// Iterate all plan nodes, depth first starting from the root.
+    pub fn nodes(&self) -> impl Iterator<Item = &Value> {
+        let mut stack = vec![self.root()];
+        std::iter::from_fn(move || {
+            let node = stack.pop()?;
+            if let Some(plans) = node["Plans"].as_array() {
+                stack.extend(plans.iter().rev());
+            }
+            Some(node)
+        })
+    }
+
+    /// Returns `true` if any node scan using index with given name.
+    pub fn uses_index(&self, name: &str) -> bool {
+        self.nodes().any(|node|node["Index Name"].as_str() == Some(name))
+    }
+
+    /// Returns `true` if any node is a sequential scan on given table.
+    pub fn has_seq_scan(&self, table: &str) -> bool {
+        self.nodes().any(|node|{
+            node["Node Type"].as_str() == Some("Seq Scan")
+                && node["Relation Name"].as_str() == Some(table)
+        })
+    }
+
+    /// Returns the total execution time in milliseconds, only available with `ANALYZE`.
+    pub fn execution_time(&self) -> Option<f64> {
+        self.json[0]["Execution Time"].as_f64()
+    }
+}
+
+impl fmt::Display for Plan {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match serde_json::to_string_pretty(&self.json) {
+            Ok(json) => f.write_str(&json),
+            Err(_) => write!(f, "{}", self.json),
+        }
+    }
+}
+
+impl Decode for Plan {
+    fn decode(column: Column) -> Result<Self, DecodeError> {
+        /// json, `EXPLAIN` output is not jsonb
+        const JSON: Oid = 114;
+        if column.oid() != JSON {
+            return Err(column.oid_missmatch::<Self>(Some(JSON)));
+        }
+        let value = column.try_into_value()?;
+        Ok(Self { json: serde_json::from_slice(&value)? })
+    }
+}
+
+/// Returns the plan of the query without executing it.
+///
+/// Bound parameters are kept, so the plan is the same as executing the query.
+pub async fn explain<SQL, Exe, M>(query: Query<'_, SQL, Exe, M>) -> Result<Plan>
+where
+    SQL: Sql,
+    Exe: Executor,
+    Exe::Future: Unpin,
+    Exe::Transport: Unpin,
+{
+    fetch_plan(query, "EXPLAIN (FORMAT JSON)").await
+}
+
+/// Execute the query with `EXPLAIN ANALYZE`, and returns the actual plan.
+///
+/// Note that the statement is executed, use a [`Transaction`][crate::transaction::Transaction]
+/// that is rolled back to discard any modification.
+pub async fn explain_analyze<SQL, Exe, M>(query: Query<'_, SQL, Exe, M>) -> Result<Plan>
+where
+    SQL: Sql,
+    Exe: Executor,
+    Exe::Future: Unpin,
+    Exe::Transport: Unpin,
+{
+    fetch_plan(query, "EXPLAIN (ANALYZE, FORMAT JSON)").await
+}
+
+/// Assert the plan from [`explain_analyze`] satisfy `f`.
+///
+/// # Panics
+///
+/// Panics with the plan printed if `f` returns `false`.
+pub async fn assert_plan<SQL, Exe, M, F>(query: Query<'_, SQL, Exe, M>, f: F) -> Result<Plan>
+where
+    SQL: Sql,
+    Exe: Executor,
+    Exe::Future: Unpin,
+    Exe::Transport: Unpin,
+    F: FnOnce(&Plan) -> bool,
+{
+    let plan = explain_analyze(query).await?;
+    assert!(f(&plan), "query plan assertion failed:\n{plan}");
+    Ok(plan)
+}
+
+async fn fetch_plan<SQL, Exe, M>(query: Query<'_, SQL, Exe, M>, explain: &str) -> Result<Plan>
+where
+    SQL: Sql,
+    Exe: Executor,
+    Exe::Future: Unpin,
+    Exe::Transport: Unpin,
+{
+    query
+        .map_sql::<_, StreamScalar<Plan>>(|mut sql| ExplainSql {
+            sql: format!("{explain} {}", sql.sql()),
+            params: sql.params(),
+        })
+        .fetch_one()
+        .await
+}
+
+/// The explained statement, carrying the parameters of the inner sql.
+struct ExplainSql {
+    sql: String,
+    params: Result<Vec<Encoded<'static>>>,
+}
+
+impl Sql for ExplainSql {
+    fn sql(&self) -> &str {
+        &self.sql
+    }
+
+    fn persistent(&self) -> bool {
+        false
+    }
+
+    fn params(&mut self) -> Result<Vec<Encoded<'static>>> {
+        std::mem::replace(&mut self.params, Ok(Vec::new()))
+    }
+}
+
+/// Run `f` with a [`Pool`] connected to a temporary database, used by `#[postro::test]`.
+///
+/// The database is created using connection config from [`Config::from_env`], and dropped
+/// after `f` complete, including when it panics. Each call creates its own database, so
+/// tests can run in parallel.
+///
+/// # Panics
+///
+/// Panics if creating or dropping the database failed.
+///
+/// # Example
+///
+/// ```no_run
+/// #[postro::test]
+/// async fn insert_user(pool: postro::Pool) -> postro::Result<()> {
+///     postro::raw_sql("CREATE TABLE users(name text)", &pool).await?;
+///     postro::query("INSERT INTO users VALUES ($1)", &pool).bind("Foo").execute().await?;
+///     Ok(())
+/// }
+/// ```
+#[cfg(feature = "tokio")]
+pub fn run_test<F, Fut>(name: &str, f: F) -> Fut::Output
+where
+    F: FnOnce(Pool) -> Fut,
+    Fut: Future,
+{
+    use std::panic::{AssertUnwindSafe, catch_unwind, resume_unwind};
+
+    let runtime = tokio::runtime::Builder::new_current_thread()
+        .enable_all()
+        .build()
+        .expect("failed to build tokio runtime");
+
+    let config = Config::from_env();
+    let dbname = test_dbname(name);
+
+    let mut pool = runtime
+        .block_on(async {
+            let mut conn = Connection::connect_with(config.clone()).await?;
+            raw_sql(&format!("CREATE DATABASE \"{dbname}\""), &mut conn).await?;
+            conn.close().await?;
+
+            let config = Config { dbname: ByteStr::copy_from_str(&dbname), ..config.clone() };
+            Ok::<_, Error>(Pool::try_connect_lazy_with(PoolConfig::from_env().with_connection(config))?)
+        })
+        .unwrap_or_else(|err| panic!("failed to create test database {dbname:?}: {err}"));
+
+    let output = catch_unwind(AssertUnwindSafe(|| runtime.block_on(f(pool.clone()))));
+
+    let dropped = runtime.block_on(async {
+        // database cannot be dropped while there is connection to it
+        pool.drain().await;
+        let mut conn = Connection::connect_with(config).await?;
+        raw_sql(&format!("DROP DATABASE \"{dbname}\""), &mut conn).await?;
+        conn.close().await?;
+        Ok::<_, Error>(())
+    });
+
+    match (output, dropped) {
+        (Ok(output), Ok(())) => output,
+        (Ok(_), Err(err)) => panic!("failed to drop test database {dbname:?}: {err}"),
+        (Err(panic), _) => resume_unwind(panic),
+    }
+}
+
+/// Returns unique database name, postgres truncate identifier longer than 63 bytes.
+#[cfg(feature = "tokio")]
+fn test_dbname(name: &str) -> String {
+    use std::sync::atomic::{AtomicU32, Ordering};
+
+    static NEXT: AtomicU32 = AtomicU32::new(0);
+
+    let mut dbname = format!(
+        "_postro_test_{}_{}_",
+        std::process::id(),
+        NEXT.fetch_add(1, Ordering::Relaxed),
+    );
+    let name = name.chars().map(|e|match e.is_ascii_alphanumeric() {
+        true => e.to_ascii_lowercase(),
+        false => '_',
+    });
+    dbname.extend(name);
+    dbname.truncate(63);
+    dbname
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn plan_nodes() {
+        let plan = Plan {
+            json: serde_json::json!([{
+                "Plan": {
+                    "Node Type": "Nested Loop",
+                    "Plans": [
+                        { "Node Type": "Seq Scan", "Relation Name": "post" },
+                        { "Node Type": "Index Scan", "Relation Name": "users", "Index Name": "users_email_idx" },
+                    ],
+                },
+                "Execution Time": 0.5,
+            }]),
+        };
+
+        let types = plan.nodes().map(|e|e["Node Type"].as_str().unwrap()).collect::<Vec<_>>();
+        assert_eq!(types, ["Nested Loop", "Seq Scan", "Index Scan"]);
+        assert!(plan.uses_index("users_email_idx"));
+        assert!(!plan.uses_index("post_pkey"));
+        assert!(plan.has_seq_scan("post"));
+        assert!(!plan.has_seq_scan("users"));
+        assert_eq!(plan.execution_time(), Some(0.5));
+    }
+
+    #[cfg(feature = "tokio")]
+    #[test]
+    fn dbname() {
+        let a = test_dbname("Insert-User");
+        let b = test_dbname("Insert-User");
+        assert_ne!(a, b);
+        assert!(a.starts_with("_postro_test_") && a.ends_with("_insert_user"));
+        assert_eq!(test_dbname(&"a".repeat(100)).len(), 63);
+    }
+}
@@ -1,8 +1,11 @@
 //! The [`Transaction`] type.
-use std::io;
+use std::{fmt, future::Ready, io};
 
 use crate::{
-    Result,
+    Error, Result,
+    common::verbose,
+    encode::Encoded,
+    executor::Executor,
     postgres::{
         BackendProtocol, backend,
         frontend::{self, FrontendProtocol},
@@ -19,13 +22,16 @@ use crate::{
 ///
 /// If not commited, when this structure is dropped, transaction will be rolled back.
 ///
+/// Nested transaction can be created using [`Transaction::begin`], which is implemented
+/// using `SAVEPOINT`.
+///
 /// # Example
 ///
 /// ```no_run
 /// # async fn test(mut conn: postro::Connection) -> postro::Result<()> {
 /// let mut tx = postro::begin(&mut conn).await?;
 ///
-/// postro::execute("insert into post(name) values('foo')", &mut tx)
+/// postro::query("insert into post(name) values('foo')", &mut tx)
 ///     .execute()
 ///     .await?;
 ///
@@ -33,9 +39,33 @@ use crate::{
 /// # Ok(())
 /// # }
 /// ```
+///
+/// # Executor
+///
+/// Both `Transaction` and `&mut Transaction` implement [`Executor`], so helper
+/// functions generic over [`Executor`] accept transaction as is.
+///
+/// ```no_run
+/// use postro::{Executor, Result};
+///
+/// async fn insert_post(exe: impl Executor) -> Result<()> {
+///     postro::query("insert into post(name) values('foo')", exe).await?;
+///     Ok(())
+/// }
+///
+/// # async fn test(mut conn: postro::Connection) -> postro::Result<()> {
+/// let mut tx = postro::begin(&mut conn).await?;
+/// insert_post(&mut tx).await?;
+/// insert_post(tx.as_executor()).await?;
+/// tx.commit().await?;
+/// # Ok(())
+/// # }
+/// ```
 pub struct Transaction<IO: PgTransport> {
     io: IO,
     commited: bool,
+    depth: u32,
+    savepoint: Option<String>,
 }
 
 impl<IO> Transaction<IO>
@@ -43,17 +73,100 @@ where
     IO: PgTransport
 {
     pub(crate) fn new(io: IO) -> Self {
-        Self { io, commited: false }
+        verbose!(depth = 1, "begin");
+        Self { io, commited: false, depth: 1, savepoint: None }
+    }
+
+    /// Returns the nesting depth of the transaction.
+    ///
+    /// Top level transaction have depth of `1`.
+    pub fn depth(&self) -> u32 {
+        self.depth
+    }
+
+    /// Returns the transaction as [`Executor`].
+    ///
+    /// This is the same as `&mut tx`, but more explicit when passed to helper functions.
+    pub fn as_executor(&mut self) -> &mut Self {
+        self
+    }
+
+    /// Returns the savepoint name if this is a nested transaction.
+    pub fn savepoint_name(&self) -> Option<&str> {
+        self.savepoint.as_deref()
+    }
+
+    /// Begin a nested transaction using `SAVEPOINT`.
+    ///
+    /// If not commited, when returned nested transaction is dropped,
+    /// it will be rolled back to the savepoint.
+    pub async fn begin(&mut self) -> Result<Transaction<&mut Self>> {
+        let depth = self.depth + 1;
+        let savepoint = format!("postro_savepoint_{depth}");
+
+        self.io.send(frontend::Query { sql: &format!("SAVEPOINT {savepoint}") });
+        self.io.flush().await?;
+        self.io.recv::<backend::CommandComplete>().await?;
+        let r = self.io.recv::<backend::ReadyForQuery>().await?;
+        assert_eq!(r.tx_status,b'T');
+
+        verbose!(depth, savepoint, "begin");
+
+        Ok(Transaction { io: self, commited: false, depth, savepoint: Some(savepoint) })
+    }
+
+    /// Run multiple statements in order, returning the number of rows affected of each statement.
+    ///
+    /// All statements with its parameters are sent in one flush, and statements after the
+    /// first failed one are not executed. The failed statement index is available from
+    /// [`ScriptError::index`].
+    ///
+    /// After an error, the transaction is aborted and rolled back when dropped.
+    ///
+    /// # Example
+    ///
+    /// ```no_run
+    /// use postro::Encode;
+    /// # async fn test(mut conn: postro::Connection) -> postro::Result<()> {
+    /// let mut tx = postro::begin(&mut conn).await?;
+    ///
+    /// tx.run_script([
+    ///     ("INSERT INTO orders(id) VALUES($1)", vec![1.encode()]),
+    ///     ("INSERT INTO items(order_id,name) VALUES($1,$2)", vec![1.encode(), "Foo".encode()]),
+    ///     ("INSERT INTO items(order_id,name) VALUES($1,$2)", vec![1.encode(), "Bar".encode()]),
+    /// ]).await?;
+    ///
+    /// tx.commit().await?;
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub async fn run_script<'s, 'q, I>(&mut self, script: I) -> Result<Vec<u64>>
+    where
+        I: IntoIterator<Item = (&'s str, Vec<Encoded<'q>>)>,
+    {
+        crate::pipeline::run(&mut self.io, script.into_iter().collect()).await
     }
 
     /// Commit transaction.
+    ///
+    /// For nested transaction, this release the savepoint.
     pub async fn commit(mut self) -> Result<()> {
-        self.io.send(frontend::Query { sql: "COMMIT" });
+        let expect = match &self.savepoint {
+            Some(savepoint) => {
+                self.io.send(frontend::Query { sql: &format!("RELEASE SAVEPOINT {savepoint}") });
+                b'T'
+            },
+            None => {
+                self.io.send(frontend::Query { sql: "COMMIT" });
+                b'I'
+            },
+        };
         self.io.flush().await?;
         self.io.recv::<backend::CommandComplete>().await?;
         let r = self.io.recv::<backend::ReadyForQuery>().await?;
-        assert_eq!(r.tx_status,b'I');
+        assert_eq!(r.tx_status,expect);
         self.commited = true;
+        verbose!(depth = self.depth, savepoint = self.savepoint, "commit");
         Ok(())
     }
 }
@@ -64,8 +177,14 @@ where
 {
     fn drop(&mut self) {
         if !self.commited {
-            self.io.send(frontend::Query { sql: "ROLLBACK" });
+            match &self.savepoint {
+                Some(savepoint) => self.io.send(frontend::Query {
+                    sql: &format!("ROLLBACK TO SAVEPOINT {savepoint}; RELEASE SAVEPOINT {savepoint}"),
+                }),
+                None => self.io.send(frontend::Query { sql: "ROLLBACK" }),
+            }
             self.io.ready_request();
+            verbose!(depth = self.depth, savepoint = self.savepoint, "rollback");
         }
     }
 }
@@ -86,6 +205,10 @@ where
         IO::ready_request(&mut self.io)
     }
 
+    fn poison(&mut self) {
+        IO::poison(&mut self.io)
+    }
+
     fn send<F: FrontendProtocol>(&mut self, message: F) {
         IO::send(&mut self.io, message)
     }
@@ -101,5 +224,232 @@ where
     fn add_stmt(&mut self, sql: u64, id: StatementName) {
         IO::add_stmt(&mut self.io, sql, id)
     }
+
+    fn remove_stmt(&mut self, sql: u64) {
+        IO::remove_stmt(&mut self.io, sql)
+    }
+
+    fn in_transaction(&self) -> bool {
+        IO::in_transaction(&self.io)
+    }
+
+    fn stmt_cache_enabled(&self) -> bool {
+        IO::stmt_cache_enabled(&self.io)
+    }
+
+    fn normalize_statements(&self) -> bool {
+        IO::normalize_statements(&self.io)
+    }
+
+    fn codecs(&self) -> Option<std::sync::Arc<crate::codec::Codecs>> {
+        IO::codecs(&self.io)
+    }
+
+    fn cancel_handle(&self) -> Option<crate::connection::CancelHandle> {
+        IO::cancel_handle(&self.io)
+    }
+
+    fn abandoned(&self) -> Option<std::sync::Arc<std::sync::atomic::AtomicBool>> {
+        IO::abandoned(&self.io)
+    }
+}
+
+
+impl<IO> Executor for Transaction<IO>
+where
+    IO: PgTransport + Unpin
+{
+    type Transport = Self;
+
+    type Future = Ready<Result<Self::Transport>>;
+
+    fn connection(self) -> Self::Future {
+        std::future::ready(Ok(self))
+    }
+}
+
+/// An error when a statement in [`Transaction::run_script`] or [`Pipeline`][1] failed.
+///
+/// [1]: crate::pipeline::Pipeline
+pub struct ScriptError {
+    pub(crate) index: usize,
+    pub(crate) source: Box<Error>,
+}
+
+impl ScriptError {
+    /// Returns the index of the failed statement.
+    pub fn index(&self) -> usize {
+        self.index
+    }
+
+    /// Returns the error of the failed statement.
+    pub fn error(&self) -> &Error {
+        &self.source
+    }
+}
+
+impl std::error::Error for ScriptError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        Some(&*self.source)
+    }
+}
+
+impl fmt::Display for ScriptError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "script statement {} failed: {}", self.index, self.source.kind())
+    }
+}
+
+impl fmt::Debug for ScriptError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "\"{self}\"")
+    }
+}
+
+/// Transaction isolation level.
+///
+/// See <https://www.postgresql.org/docs/current/transaction-iso.html>.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum IsolationLevel {
+    /// `READ UNCOMMITTED`, which behaves as `READ COMMITTED` in postgres.
+    ReadUncommitted,
+    /// `READ COMMITTED`, the postgres default.
+    ReadCommitted,
+    /// `REPEATABLE READ`.
+    RepeatableRead,
+    /// `SERIALIZABLE`.
+    Serializable,
+}
+
+impl IsolationLevel {
+    fn as_sql(&self) -> &'static str {
+        match self {
+            Self::ReadUncommitted => "READ UNCOMMITTED",
+            Self::ReadCommitted => "READ COMMITTED",
+            Self::RepeatableRead => "REPEATABLE READ",
+            Self::Serializable => "SERIALIZABLE",
+        }
+    }
+}
+
+/// Begin transaction with options.
+///
+/// # Example
+///
+/// ```no_run
+/// use postro::transaction::IsolationLevel;
+/// # async fn test(mut conn: postro::Connection) -> postro::Result<()> {
+/// let mut tx = postro::begin_with(&mut conn)
+///     .isolation(IsolationLevel::Serializable)
+///     .read_only()
+///     .deferrable()
+///     .begin()
+///     .await?;
+///
+/// postro::query("select * from post", &mut tx).execute().await?;
+///
+/// tx.commit().await?;
+/// # Ok(())
+/// # }
+/// ```
+pub fn begin_with<Exec: Executor>(exec: Exec) -> TransactionBuilder<Exec> {
+    TransactionBuilder { exec, isolation: None, read_only: None, deferrable: None }
+}
+
+/// Transaction builder, created by [`begin_with`].
+///
+/// Options not specified are left to the server default.
+#[derive(Debug)]
+#[must_use = "transaction is not started until `begin` is called"]
+pub struct TransactionBuilder<Exec> {
+    exec: Exec,
+    isolation: Option<IsolationLevel>,
+    read_only: Option<bool>,
+    deferrable: Option<bool>,
+}
+
+impl<Exec> TransactionBuilder<Exec> {
+    /// Set the transaction isolation level.
+    pub fn isolation(mut self, level: IsolationLevel) -> Self {
+        self.isolation = Some(level);
+        self
+    }
+
+    /// Set the transaction access mode to `READ ONLY`.
+    pub fn read_only(mut self) -> Self {
+        self.read_only = Some(true);
+        self
+    }
+
+    /// Set the transaction access mode to `READ WRITE`.
+    pub fn read_write(mut self) -> Self {
+        self.read_only = Some(false);
+        self
+    }
+
+    /// Set the transaction to `DEFERRABLE`.
+    ///
+    /// This only have effect when the transaction is also `SERIALIZABLE` and `READ ONLY`.
+    pub fn deferrable(mut self) -> Self {
+        self.deferrable = Some(true);
+        self
+    }
+
+    /// Set the transaction to `NOT DEFERRABLE`.
+    pub fn not_deferrable(mut self) -> Self {
+        self.deferrable = Some(false);
+        self
+    }
+
+    fn sql(&self) -> String {
+        let mut sql = String::from("BEGIN");
+        if let Some(level) = self.isolation {
+            sql.push_str(" ISOLATION LEVEL ");
+            sql.push_str(level.as_sql());
+        }
+        match self.read_only {
+            Some(true) => sql.push_str(" READ ONLY"),
+            Some(false) => sql.push_str(" READ WRITE"),
+            None => {},
+        }
+        match self.deferrable {
+            Some(true) => sql.push_str(" DEFERRABLE"),
+            Some(false) => sql.push_str(" NOT DEFERRABLE"),
+            None => {},
+        }
+        sql
+    }
 }
 
+impl<Exec> TransactionBuilder<Exec>
+where
+    Exec: Executor
+{
+    /// Begin the transaction.
+    pub async fn begin(self) -> Result<Transaction<Exec::Transport>> {
+        let sql = self.sql();
+        crate::phase::begin_sql(self.exec, &sql).await
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    fn begin_with(exec: ()) -> TransactionBuilder<()> {
+        TransactionBuilder { exec, isolation: None, read_only: None, deferrable: None }
+    }
+
+    #[test]
+    fn builder_sql() {
+        assert_eq!(begin_with(()).sql(), "BEGIN");
+        assert_eq!(
+            begin_with(()).isolation(IsolationLevel::Serializable).read_only().deferrable().sql(),
+            "BEGIN ISOLATION LEVEL SERIALIZABLE READ ONLY DEFERRABLE",
+        );
+        assert_eq!(
+            begin_with(()).isolation(IsolationLevel::RepeatableRead).read_write().not_deferrable().sql(),
+            "BEGIN ISOLATION LEVEL REPEATABLE READ READ WRITE NOT DEFERRABLE",
+        );
+    }
+}
@@ -1,11 +1,14 @@
 //! The [`PgTransport`] trait.
 use std::{
     io,
+    sync::{Arc, atomic::AtomicBool},
     task::{Context, Poll},
 };
 
 use crate::{
     Result,
+    codec::Codecs,
+    connection::CancelHandle,
     postgres::{BackendProtocol, FrontendProtocol, frontend},
     statement::StatementName,
 };
@@ -21,6 +24,9 @@ pub trait PgTransport: Unpin {
     ///
     /// Implementor should handle `NoticeResponse` and should not return it.
     ///
+    /// Implementor should handle `NotificationResponse` which can arrive at any time,
+    /// and should not return it.
+    ///
     /// Implementor also should handle `ErrorResponse` and return it as [`Err`].
     ///
     /// [1]: PgTransport::poll_flush
@@ -29,6 +35,12 @@ pub trait PgTransport: Unpin {
     /// Request implementor to ignore all backend messages until `ReadyForQuery` is received.
     fn ready_request(&mut self);
 
+    /// Mark the transport as poisoned after an unexpected backend message.
+    ///
+    /// The message framing state is unknown at that point, implementor should refuse
+    /// further use instead of trying to recover.
+    fn poison(&mut self) { }
+
     /// Send message to the backend.
     ///
     /// Note that this send is buffered, caller must also call
@@ -53,6 +65,47 @@ pub trait PgTransport: Unpin {
 
     /// Add new prepared statement.
     fn add_stmt(&mut self, sql: u64, id: StatementName);
+
+    /// Remove cached prepared statement, e.g: after it is invalidated by schema change.
+    fn remove_stmt(&mut self, sql: u64) {
+        let _ = sql;
+    }
+
+    /// Returns `true` if the transport is inside a transaction block.
+    ///
+    /// An error aborts the transaction, so a failed statement can only be retried outside
+    /// of it. Default to `true`, which never retry.
+    fn in_transaction(&self) -> bool {
+        true
+    }
+
+    /// Returns `false` if prepared statement caching is disabled.
+    fn stmt_cache_enabled(&self) -> bool {
+        true
+    }
+
+    /// Returns `true` if the statement cache key is computed from normalized sql.
+    fn normalize_statements(&self) -> bool {
+        false
+    }
+
+    /// Returns registered custom type [`Codecs`], if any.
+    fn codecs(&self) -> Option<Arc<Codecs>> {
+        None
+    }
+
+    /// Returns [`CancelHandle`] to cancel the running query, if supported.
+    fn cancel_handle(&self) -> Option<CancelHandle> {
+        None
+    }
+
+    /// Returns a flag which is set when a request is dropped before all of its responses
+    /// are received, if supported.
+    ///
+    /// Implementor should discard the remaining responses before the next request.
+    fn abandoned(&self) -> Option<Arc<AtomicBool>> {
+        None
+    }
 }
 
 impl<P> PgTransport for &mut P where P: PgTransport {
@@ -68,6 +121,10 @@ impl<P> PgTransport for &mut P where P: PgTransport {
         P::ready_request(self);
     }
 
+    fn poison(&mut self) {
+        P::poison(self);
+    }
+
     fn send<F: FrontendProtocol>(&mut self, message: F) {
         P::send(self, message);
     }
@@ -83,6 +140,34 @@ impl<P> PgTransport for &mut P where P: PgTransport {
     fn add_stmt(&mut self, sql: u64, id: StatementName) {
         P::add_stmt(self, sql, id);
     }
+
+    fn remove_stmt(&mut self, sql: u64) {
+        P::remove_stmt(self, sql);
+    }
+
+    fn in_transaction(&self) -> bool {
+        P::in_transaction(self)
+    }
+
+    fn stmt_cache_enabled(&self) -> bool {
+        P::stmt_cache_enabled(self)
+    }
+
+    fn normalize_statements(&self) -> bool {
+        P::normalize_statements(self)
+    }
+
+    fn codecs(&self) -> Option<Arc<Codecs>> {
+        P::codecs(self)
+    }
+
+    fn cancel_handle(&self) -> Option<CancelHandle> {
+        P::cancel_handle(self)
+    }
+
+    fn abandoned(&self) -> Option<Arc<AtomicBool>> {
+        P::abandoned(self)
+    }
 }
 
 /// An extension trait to provide `Future` API for [`PgTransport`].
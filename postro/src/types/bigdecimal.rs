@@ -0,0 +1,43 @@
+use bigdecimal::{BigDecimal, num_bigint::Sign};
+use std::str::FromStr;
+
+use crate::{
+    Decode, DecodeError, Encode,
+    encode::Encoded,
+    postgres::{Oid, PgType},
+    row::Column,
+};
+
+use super::numeric;
+
+impl PgType for BigDecimal {
+    /// numeric, arbitrary precision number
+    const OID: Oid = numeric::NUMERIC_OID;
+}
+
+impl Decode for BigDecimal {
+    fn decode(column: Column) -> Result<Self, DecodeError> {
+        if column.oid() != Self::OID {
+            return Err(column.oid_missmatch::<Self>(Some(Self::OID)));
+        }
+        let value = numeric::decode(&column.try_into_value()?)?;
+        BigDecimal::from_str(&value).map_err(|e| DecodeError::Invalid(e.to_string().into()))
+    }
+}
+
+impl<'q> Encode<'q> for &BigDecimal {
+    fn encode(self) -> Encoded<'q> {
+        let (int, scale) = self.as_bigint_and_exponent();
+        numeric::encode(
+            int.sign() == Sign::Minus,
+            &int.magnitude().to_string(),
+            scale,
+        )
+    }
+}
+
+impl<'q> Encode<'q> for BigDecimal {
+    fn encode(self) -> Encoded<'q> {
+        (&self).encode()
+    }
+}
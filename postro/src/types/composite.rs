@@ -0,0 +1,253 @@
+use bytes::{Buf, BufMut, Bytes, BytesMut};
+use std::{borrow::Cow, fmt, sync::Arc};
+
+use crate::{
+    Decode, DecodeError, Encode,
+    codec::{Codecs, TypeInfo, TypeKind},
+    common::ByteStr,
+    encode::Encoded,
+    ext::BindParams,
+    postgres::{Oid, PgFormat},
+    row::Column,
+};
+
+/// `record` oid, anonymous composite type, e.g: `ROW(1, 'a')`.
+const RECORD: Oid = 2249;
+
+/// Postgres composite value, e.g: type created via `CREATE TYPE .. AS (..)`.
+///
+/// Decoding accepts anonymous `record` and composite type resolved via
+/// [`Config::resolve_type`][crate::Config::resolve_type], where the fields name are
+/// also available.
+///
+/// Encoding requires the composite type [`Oid`], and each field [`Oid`] must match
+/// the declared field type. When created via [`Composite::from_type`], field without
+/// [`Oid`], e.g: `NULL` or derived enum, is sent as the declared field type.
+///
+/// ```no_run
+/// use postro::{Config, Connection, types::Composite};
+///
+/// # async fn app() -> postro::Result<()> {
+/// // CREATE TYPE inventory_item AS (name text, price int4)
+/// let config = Config::from_env().resolve_type("inventory_item");
+/// let mut conn = Connection::connect_with(config).await?;
+/// let item_type = conn.codecs().type_info("inventory_item").unwrap();
+///
+/// let item = Composite::from_type(item_type).field("fuzzy dice").field(42);
+/// let item = postro::query_scalar::<_, _, Composite>("SELECT $1", &mut conn)
+///     .bind(item)
+///     .fetch_one()
+///     .await?;
+///
+/// let name: String = item.try_get_by_name("name")?;
+/// let price: i32 = item.try_get(1)?;
+/// # Ok(())
+/// # }
+/// ```
+#[derive(Clone)]
+pub struct Composite {
+    oid: Oid,
+    fields: Vec<Field>,
+    /// declared fields [`Oid`], if known
+    declared: Vec<Oid>,
+    codecs: Option<Arc<Codecs>>,
+    /// reason of the first field that failed to encode
+    error: Option<Cow<'static, str>>,
+}
+
+#[derive(Clone, Debug)]
+struct Field {
+    name: ByteStr,
+    oid: Oid,
+    value: Option<Bytes>,
+}
+
+impl Composite {
+    /// Create empty composite value of type `oid`, to be encoded.
+    pub fn new(oid: Oid) -> Self {
+        Self { oid, fields: Vec::new(), declared: Vec::new(), codecs: None, error: None }
+    }
+
+    /// Create empty composite value of resolved composite type, to be encoded.
+    pub fn from_type(info: &TypeInfo) -> Self {
+        let declared = match info.kind() {
+            TypeKind::Composite(fields) => fields.iter().map(|(_, oid)| *oid).collect(),
+            _ => Vec::new(),
+        };
+        Self { declared, ..Self::new(info.oid()) }
+    }
+
+    /// Add field value in binary format, the field [`Oid`] is from [`Encode`].
+    pub fn field<'q>(mut self, value: impl Encode<'q>) -> Self {
+        let mut value = value.encode();
+        if self.error.is_none() && let Some(reason) = value.error() {
+            self.error = Some(format!("field {}: {reason}", self.fields.len() + 1).into());
+        }
+        let oid = match value.oid() {
+            0 => self.declared.get(self.fields.len()).copied().unwrap_or_default(),
+            oid => oid,
+        };
+        let value = match value.size() {
+            -1 => None,
+            _ => Some(value.copy_to_bytes(value.remaining())),
+        };
+        self.fields.push(Field { name: ByteStr::default(), oid, value });
+        self
+    }
+
+    /// Returns the composite type [`Oid`].
+    pub fn oid(&self) -> Oid {
+        self.oid
+    }
+
+    /// Returns the number of fields.
+    pub fn len(&self) -> usize {
+        self.fields.len()
+    }
+
+    /// Returns `true` if there is no fields.
+    pub fn is_empty(&self) -> bool {
+        self.fields.is_empty()
+    }
+
+    /// Try decode field by its position.
+    pub fn try_get<R: Decode>(&self, nth: usize) -> Result<R, DecodeError> {
+        let field = self.fields.get(nth).ok_or(DecodeError::IndexOutOfBounds(nth))?;
+        R::decode(self.column(field))
+    }
+
+    /// Try decode field by its name.
+    ///
+    /// Fields name is only available if the type is resolved via
+    /// [`Config::resolve_type`][crate::Config::resolve_type].
+    pub fn try_get_by_name<R: Decode>(&self, name: &str) -> Result<R, DecodeError> {
+        let field = self
+            .fields
+            .iter()
+            .find(|e| e.name.as_str() == name)
+            .ok_or_else(|| DecodeError::ColumnNotFound(name.to_owned().into()))?;
+        R::decode(self.column(field))
+    }
+
+    fn column(&self, field: &Field) -> Column {
+        Column::from_parts(
+            field.name.clone(),
+            field.oid,
+            PgFormat::Binary,
+            field.value.clone(),
+            self.codecs.clone(),
+        )
+    }
+}
+
+impl Decode for Composite {
+    fn decode(column: Column) -> Result<Self, DecodeError> {
+        let names = match column.type_info().map(TypeInfo::kind) {
+            Some(TypeKind::Composite(fields)) => fields
+                .iter()
+                .map(|(name, _)| ByteStr::copy_from_str(name))
+                .collect(),
+            _ if column.oid() == RECORD => Vec::new(),
+            _ => return Err(column.oid_missmatch::<Self>(None)),
+        };
+        if column.format() != PgFormat::Binary {
+            return Err(DecodeError::Invalid("composite text format is not supported".into()));
+        }
+
+        let oid = column.oid();
+        let codecs = column.shared_codecs();
+        let mut value = column.try_into_value()?;
+
+        const INVALID: DecodeError = DecodeError::Invalid(Cow::Borrowed("invalid record length"));
+
+        if value.remaining() < size_of::<i32>() {
+            return Err(INVALID);
+        }
+        let len = usize::try_from(value.get_i32()).map_err(|_| INVALID)?;
+
+        let mut fields = Vec::with_capacity(len.min(value.remaining() / 8));
+        for i in 0..len {
+            if value.remaining() < size_of::<u32>() + size_of::<i32>() {
+                return Err(INVALID);
+            }
+            let oid = value.get_u32();
+            let value = match value.get_i32() {
+                -1 => None,
+                size => match usize::try_from(size) {
+                    Ok(size) if size <= value.remaining() => Some(value.split_to(size)),
+                    _ => return Err(INVALID),
+                },
+            };
+            let name = names.get(i).cloned().unwrap_or_default();
+            fields.push(Field { name, oid, value });
+        }
+
+        Ok(Self { oid, fields, declared: Vec::new(), codecs, error: None })
+    }
+}
+
+impl Encode<'static> for Composite {
+    fn encode(self) -> Encoded<'static> {
+        if let Some(reason) = self.error {
+            return Encoded::invalid(self.oid, reason);
+        }
+        let Ok(len) = i32::try_from(self.fields.len()) else {
+            return Encoded::invalid(self.oid, "too many fields");
+        };
+
+        let mut buf = BytesMut::new();
+        buf.put_i32(len);
+        for field in &self.fields {
+            buf.put_u32(field.oid);
+            match &field.value {
+                Some(value) => match i32::try_from(value.len()) {
+                    Ok(size) => {
+                        buf.put_i32(size);
+                        buf.put_slice(value);
+                    },
+                    Err(_) => return Encoded::invalid(self.oid, "field value too large"),
+                },
+                None => buf.put_i32(-1),
+            }
+        }
+
+        Encoded::owned(buf.freeze(), self.oid)
+    }
+}
+
+impl fmt::Debug for Composite {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("Composite")
+            .field("oid", &self.oid)
+            .field("fields", &self.fields)
+            .finish_non_exhaustive()
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use bytes::Buf;
+
+    use super::{Composite, RECORD};
+    use crate::{DecodeError, Encode, encode::Encoded, ext::BindParams, postgres::PgFormat, row::Column};
+
+    fn column(value: &[u8]) -> Column {
+        Column::from_parts("record".into(), RECORD, PgFormat::Binary, Some(value.to_vec().into()), None)
+    }
+
+    #[test]
+    fn record_roundtrip() {
+        let mut encoded = Composite::new(RECORD).field(420).field("Deez").field(Encoded::null()).encode();
+        assert_eq!(encoded.size(), 4 + (8 + 4) + (8 + 4) + 8);
+        let value = encoded.copy_to_bytes(encoded.remaining());
+
+        let record = column(&value).decode::<Composite>().unwrap();
+        assert_eq!(record.len(), 3);
+        assert_eq!(record.try_get::<i32>(0).unwrap(), 420);
+        assert_eq!(record.try_get::<String>(1).unwrap(), "Deez");
+        assert_eq!(record.try_get::<Option<i32>>(2).unwrap(), None);
+        assert!(matches!(record.try_get::<i32>(3), Err(DecodeError::IndexOutOfBounds(3))));
+
+        assert!(column(&value[..value.len() - 1]).decode::<Composite>().is_err());
+    }
+}
@@ -0,0 +1,68 @@
+use crate::{Decode, DecodeError, Encode, encode::Encoded, row::Column};
+
+/// Treat empty string as `NULL`.
+///
+/// When encoded, empty string is bound as `NULL`, and when decoded, `NULL` is
+/// decoded as empty string, or the [`Default`] value of the inner type.
+///
+/// ```no_run
+/// # async fn test(mut conn: postro::Connection) -> postro::Result<()> {
+/// use postro::types::EmptyAsNull;
+///
+/// postro::query("INSERT INTO post(name,note) VALUES($1,$2)", &mut conn)
+///     .bind("foo")
+///     .bind(EmptyAsNull(""))
+///     .await?;
+///
+/// let note = postro::query_scalar::<_, _, EmptyAsNull<String>>("SELECT note FROM post", &mut conn)
+///     .fetch_one()
+///     .await?;
+///
+/// assert_eq!(note.0, "");
+/// # Ok(())
+/// # }
+/// ```
+#[derive(Debug, Clone, Default, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct EmptyAsNull<T>(pub T);
+
+impl<T> EmptyAsNull<T> {
+    /// Returns the inner value.
+    pub fn into_inner(self) -> T {
+        self.0
+    }
+}
+
+impl<T> std::ops::Deref for EmptyAsNull<T> {
+    type Target = T;
+
+    fn deref(&self) -> &Self::Target {
+        &self.0
+    }
+}
+
+impl<T> From<T> for EmptyAsNull<T> {
+    fn from(value: T) -> Self {
+        Self(value)
+    }
+}
+
+impl<T: Decode + Default> Decode for EmptyAsNull<T> {
+    fn decode(column: Column) -> Result<Self, DecodeError> {
+        match column.is_null() {
+            true => Ok(Self(T::default())),
+            false => column.decode().map(Self),
+        }
+    }
+}
+
+impl<'q, T> Encode<'q> for EmptyAsNull<T>
+where
+    T: AsRef<str> + Encode<'q>,
+{
+    fn encode(self) -> Encoded<'q> {
+        match self.0.as_ref().is_empty() {
+            true => Encoded::null(),
+            false => self.0.encode(),
+        }
+    }
+}
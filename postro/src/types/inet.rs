@@ -0,0 +1,303 @@
+use std::{
+    fmt,
+    net::{IpAddr, Ipv4Addr, Ipv6Addr},
+    str::FromStr,
+};
+
+use crate::{
+    Decode, DecodeError, Encode,
+    encode::Encoded,
+    postgres::{Oid, PgFormat, PgType},
+    row::Column,
+};
+
+/// `cidr` oid, IPv4 or IPv6 network address.
+const CIDR: Oid = 650;
+
+// <https://github.com/postgres/postgres/blob/master/src/include/utils/inet.h>
+const PGSQL_AF_INET: u8 = 2;
+const PGSQL_AF_INET6: u8 = 3;
+
+/// Postgres `inet` or `cidr` value, host address with its network prefix length.
+///
+/// [`IpAddr`] can also be used directly for host address, e.g: client ip, which
+/// encoded as `inet` with the full prefix length.
+///
+/// ```no_run
+/// # async fn test(mut conn: postro::Connection) -> postro::Result<()> {
+/// use std::net::IpAddr;
+/// use postro::types::PgInet;
+///
+/// let client: IpAddr = "10.0.0.7".parse().unwrap();
+/// let (ip, network) = postro::query_as::<_, _, (IpAddr, PgInet)>("SELECT $1, '10.0.0.0/8'::cidr", &mut conn)
+///     .bind(client)
+///     .fetch_one()
+///     .await?;
+/// assert!(network.contains(ip));
+/// # Ok(())
+/// # }
+/// ```
+///
+/// <https://www.postgresql.org/docs/current/datatype-net-types.html>
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub struct PgInet {
+    addr: IpAddr,
+    prefix: u8,
+}
+
+impl PgInet {
+    /// Create [`PgInet`] with given address and prefix length.
+    ///
+    /// Returns [`None`] if `prefix` exceeds the address bits length.
+    pub fn new(addr: IpAddr, prefix: u8) -> Option<Self> {
+        (prefix <= max_prefix(addr)).then_some(Self { addr, prefix })
+    }
+
+    /// Returns the host address.
+    pub fn addr(&self) -> IpAddr {
+        self.addr
+    }
+
+    /// Returns the network prefix length.
+    pub fn prefix(&self) -> u8 {
+        self.prefix
+    }
+
+    /// Returns `true` if `addr` is within the network.
+    pub fn contains(&self, addr: IpAddr) -> bool {
+        let bits = |addr| match addr {
+            IpAddr::V4(v4) => u128::from(v4.to_bits()) << 96,
+            IpAddr::V6(v6) => v6.to_bits(),
+        };
+        if self.addr.is_ipv4() != addr.is_ipv4() {
+            return false;
+        }
+        let mask = u128::MAX.checked_shl(128 - u32::from(self.prefix)).unwrap_or(0);
+        bits(self.addr) & mask == bits(addr) & mask
+    }
+}
+
+fn max_prefix(addr: IpAddr) -> u8 {
+    match addr {
+        IpAddr::V4(_) => 32,
+        IpAddr::V6(_) => 128,
+    }
+}
+
+impl From<IpAddr> for PgInet {
+    fn from(addr: IpAddr) -> Self {
+        Self { addr, prefix: max_prefix(addr) }
+    }
+}
+
+impl PgType for PgInet {
+    /// inet, IP address data type
+    const OID: Oid = 869;
+}
+
+impl PgType for IpAddr {
+    /// inet, IP address data type
+    const OID: Oid = 869;
+}
+
+impl Decode for PgInet {
+    fn decode(column: Column) -> Result<Self, DecodeError> {
+        if column.oid() != Self::OID && column.oid() != CIDR {
+            return Err(column.oid_missmatch::<Self>(None));
+        }
+        let format = column.format();
+        let value = column.try_into_value()?;
+        match format {
+            PgFormat::Binary => decode_binary(&value),
+            PgFormat::Text => str::from_utf8(&value)?
+                .parse()
+                .map_err(|_| DecodeError::Invalid("invalid inet text".into())),
+        }
+    }
+}
+
+fn decode_binary(value: &[u8]) -> Result<PgInet, DecodeError> {
+    let invalid = || DecodeError::Invalid("invalid inet".into());
+    let [family, prefix, _is_cidr, len, addr @ ..] = value else {
+        return Err(invalid());
+    };
+    let addr = match (*family, *len, addr.len()) {
+        (PGSQL_AF_INET, 4, 4) => IpAddr::V4(Ipv4Addr::from(<[u8; 4]>::try_from(addr).unwrap())),
+        (PGSQL_AF_INET6, 16, 16) => IpAddr::V6(Ipv6Addr::from(<[u8; 16]>::try_from(addr).unwrap())),
+        _ => return Err(invalid()),
+    };
+    PgInet::new(addr, *prefix).ok_or_else(invalid)
+}
+
+impl Decode for IpAddr {
+    /// Returns [`DecodeError::Invalid`] if the value is a network, use [`PgInet`] instead.
+    fn decode(column: Column) -> Result<Self, DecodeError> {
+        let inet = PgInet::decode(column)?;
+        if inet.prefix != max_prefix(inet.addr) {
+            return Err(DecodeError::Invalid(
+                "inet with network prefix cannot be decoded as `IpAddr`, use `PgInet` instead".into(),
+            ));
+        }
+        Ok(inet.addr)
+    }
+}
+
+impl Encode<'static> for PgInet {
+    fn encode(self) -> Encoded<'static> {
+        let mut buf = Vec::with_capacity(4 + 16);
+        match self.addr {
+            IpAddr::V4(v4) => {
+                buf.extend_from_slice(&[PGSQL_AF_INET, self.prefix, 0, 4]);
+                buf.extend_from_slice(&v4.octets());
+            },
+            IpAddr::V6(v6) => {
+                buf.extend_from_slice(&[PGSQL_AF_INET6, self.prefix, 0, 16]);
+                buf.extend_from_slice(&v6.octets());
+            },
+        }
+        Encoded::owned(buf, Self::OID)
+    }
+}
+
+impl Encode<'static> for IpAddr {
+    fn encode(self) -> Encoded<'static> {
+        PgInet::from(self).encode()
+    }
+}
+
+impl Encode<'static> for Ipv4Addr {
+    fn encode(self) -> Encoded<'static> {
+        IpAddr::V4(self).encode()
+    }
+}
+
+impl Encode<'static> for Ipv6Addr {
+    fn encode(self) -> Encoded<'static> {
+        IpAddr::V6(self).encode()
+    }
+}
+
+impl fmt::Display for PgInet {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}/{}", self.addr, self.prefix)
+    }
+}
+
+impl FromStr for PgInet {
+    type Err = std::net::AddrParseError;
+
+    /// Parse address with optional prefix length, e.g: `10.0.0.0/8` or `::1`.
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let Some((addr, prefix)) = s.split_once('/') else {
+            return s.parse().map(IpAddr::into);
+        };
+        let addr = addr.parse()?;
+        // reuse `AddrParseError` for invalid prefix, it has no public constructor
+        let invalid = || "".parse::<IpAddr>().unwrap_err();
+        let prefix = prefix.parse().map_err(|_| invalid())?;
+        Self::new(addr, prefix).ok_or_else(invalid)
+    }
+}
+
+/// Postgres `macaddr`, 6 bytes MAC address.
+///
+/// Displayed and parsed in the postgres `08:00:2b:01:02:03` format.
+///
+/// <https://www.postgresql.org/docs/current/datatype-net-types.html#DATATYPE-MACADDR>
+#[derive(Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, Default)]
+pub struct MacAddr(pub [u8; 6]);
+
+impl PgType for MacAddr {
+    /// macaddr, XX:XX:XX:XX:XX:XX, MAC address
+    const OID: Oid = 829;
+}
+
+impl Decode for MacAddr {
+    fn decode(column: Column) -> Result<Self, DecodeError> {
+        if column.oid() != Self::OID {
+            return Err(column.oid_missmatch::<Self>(Some(Self::OID)));
+        }
+        let format = column.format();
+        let value = column.try_into_value()?;
+        match format {
+            PgFormat::Binary => match value[..].try_into() {
+                Ok(octets) => Ok(Self(octets)),
+                Err(_) => Err(DecodeError::Invalid("invalid macaddr length".into())),
+            },
+            PgFormat::Text => str::from_utf8(&value)?
+                .parse()
+                .map_err(|_| DecodeError::Invalid("invalid macaddr text".into())),
+        }
+    }
+}
+
+impl Encode<'static> for MacAddr {
+    fn encode(self) -> Encoded<'static> {
+        Encoded::copy_from_slice(&self.0, Self::OID)
+    }
+}
+
+impl fmt::Display for MacAddr {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let [a, b, c, d, e, g] = self.0;
+        write!(f, "{a:02x}:{b:02x}:{c:02x}:{d:02x}:{e:02x}:{g:02x}")
+    }
+}
+
+impl fmt::Debug for MacAddr {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "MacAddr({self})")
+    }
+}
+
+impl FromStr for MacAddr {
+    type Err = std::num::ParseIntError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let mut octets = [0; 6];
+        let mut parts = s.split(':');
+        for octet in &mut octets {
+            *octet = u8::from_str_radix(parts.next().unwrap_or_default(), 16)?;
+        }
+        match parts.next() {
+            // reuse `ParseIntError` for extra octet, it has no public constructor
+            Some(_) => Err(u8::from_str_radix("", 16).unwrap_err()),
+            None => Ok(Self(octets)),
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use std::net::IpAddr;
+
+    use super::{MacAddr, PgInet, decode_binary};
+    use crate::Encode;
+    use bytes::Buf;
+
+    #[test]
+    fn inet_binary() {
+        for inet in ["10.0.0.7/32", "10.0.0.0/8", "::1/128", "2001:db8::/32"] {
+            let inet = inet.parse::<PgInet>().unwrap();
+            let mut encoded = inet.encode();
+            let value = encoded.copy_to_bytes(encoded.remaining());
+            assert_eq!(decode_binary(&value).unwrap(), inet);
+        }
+
+        let network = "10.0.0.0/8".parse::<PgInet>().unwrap();
+        assert!(network.contains("10.1.2.3".parse().unwrap()));
+        assert!(!network.contains("11.0.0.0".parse().unwrap()));
+        assert!(!network.contains("::1".parse().unwrap()));
+        assert!("10.0.0.0/33".parse::<PgInet>().is_err());
+        assert_eq!(PgInet::from("::1".parse::<IpAddr>().unwrap()).prefix(), 128);
+    }
+
+    #[test]
+    fn macaddr_text() {
+        let mac = "08:00:2b:01:02:03".parse::<MacAddr>().unwrap();
+        assert_eq!(mac.0, [0x08, 0x00, 0x2b, 0x01, 0x02, 0x03]);
+        assert_eq!(mac.to_string(), "08:00:2b:01:02:03");
+        assert!("08:00:2b:01:02".parse::<MacAddr>().is_err());
+        assert!("08:00:2b:01:02:03:04".parse::<MacAddr>().is_err());
+    }
+}
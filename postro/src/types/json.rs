@@ -1,19 +1,24 @@
-use bytes::Buf;
+use bytes::{Buf, Bytes};
 use serde::{Deserialize, Serialize, de::DeserializeOwned};
 
 use crate::{
     Decode, DecodeError, Encode,
     encode::Encoded,
-    postgres::{Oid, PgType},
+    postgres::{Oid, PgFormat, PgType},
     row::Column,
 };
 
+/// `json` oid, JSON stored as text.
+const JSON: Oid = 114;
+
+/// `jsonb` version byte prefix of binary format.
+const JSONB_VERSION: u8 = 1;
+
 /// Decode and Encode postgres json value.
 ///
-/// # Panics
+/// Encoded as `jsonb` binary format. Decoding accepts both `json` and `jsonb` column.
 ///
-/// Note that when performing [`Encode`], if [`Serialize`] implementation decide
-/// to fail, it will will panics.
+/// If [`Serialize`] implementation fails, the error is returned when the query is executed.
 #[derive(Debug)]
 pub struct Json<T>(pub T);
 
@@ -27,18 +32,43 @@ where
     T: DeserializeOwned,
 {
     fn decode(column: Column) -> Result<Self, DecodeError> {
-        if column.oid() != Self::OID {
-            return Err(DecodeError::OidMissmatch);
-        }
-        let mut value = column.try_into_value()?;
-        assert_eq!(value.get_u8(), b'\x01', "jsonb version");
+        let value = json_value::<Self>(column)?;
+        serde_json::from_slice(&value).map_err(Into::into)
+    }
+}
+
+impl Decode for serde_json::Value {
+    fn decode(column: Column) -> Result<Self, DecodeError> {
+        let value = json_value::<Self>(column)?;
         serde_json::from_slice(&value).map_err(Into::into)
     }
 }
 
+/// Returns the json text of `json` or `jsonb` column.
+fn json_value<T>(column: Column) -> Result<Bytes, DecodeError> {
+    match column.oid() {
+        JSON => column.try_into_value(),
+        // text format has no version byte
+        Json::<()>::OID if column.format() == PgFormat::Text => column.try_into_value(),
+        Json::<()>::OID => {
+            let mut value = column.try_into_value()?;
+            match value.try_get_u8() {
+                Ok(JSONB_VERSION) => Ok(value),
+                Ok(version) => Err(DecodeError::Invalid(format!("unsupported jsonb version {version}").into())),
+                Err(_) => Err(DecodeError::Invalid("empty jsonb value".into())),
+            }
+        },
+        _ => Err(column.oid_missmatch::<T>(Some(Json::<()>::OID))),
+    }
+}
+
 impl<T: Serialize> Encode<'static> for Json<T> {
     fn encode(self) -> Encoded<'static> {
-        Encoded::owned(serde_json::to_vec(&self).unwrap(), Self::OID)
+        let mut buf = vec![JSONB_VERSION];
+        match serde_json::to_writer(&mut buf, &self) {
+            Ok(()) => Encoded::owned(buf, Self::OID),
+            Err(err) => Encoded::invalid(Self::OID, err.to_string()),
+        }
     }
 }
 
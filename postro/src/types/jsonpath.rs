@@ -0,0 +1,105 @@
+use std::fmt;
+
+use bytes::Buf;
+
+use crate::{
+    Decode, DecodeError, Encode,
+    encode::Encoded,
+    postgres::{Oid, PgFormat, PgType},
+    row::Column,
+};
+
+/// Postgres SQL/JSON path expression.
+///
+/// Can be bound as the path argument of `jsonb_path_query` and friends without
+/// casting from text.
+///
+/// ```no_run
+/// # async fn test(mut conn: postro::Connection) -> postro::Result<()> {
+/// use postro::types::JsonPath;
+///
+/// let rows = postro::query_scalar::<_, _, String>(
+///     "SELECT jsonb_path_query($1::jsonb, $2)::text",
+///     &mut conn,
+/// )
+/// .bind(r#"{"a":[1,2]}"#)
+/// .bind(JsonPath::new("$.a[*]"))
+/// .fetch_all()
+/// .await?;
+/// # Ok(())
+/// # }
+/// ```
+///
+/// <https://www.postgresql.org/docs/current/datatype-json.html#DATATYPE-JSONPATH>
+#[derive(Clone, PartialEq, Eq, Hash, Default)]
+pub struct JsonPath(pub String);
+
+impl JsonPath {
+    /// Create new [`JsonPath`].
+    ///
+    /// The expression is validated by postgres when it is bound.
+    pub fn new(path: impl Into<String>) -> Self {
+        Self(path.into())
+    }
+
+    /// Returns the path expression.
+    pub fn as_str(&self) -> &str {
+        &self.0
+    }
+}
+
+impl PgType for JsonPath {
+    /// jsonpath, JSON path
+    const OID: Oid = 4072;
+}
+
+impl Decode for JsonPath {
+    fn decode(column: Column) -> Result<Self, DecodeError> {
+        if column.oid() != Self::OID {
+            return Err(column.oid_missmatch::<Self>(Some(Self::OID)));
+        }
+        let format = column.format();
+        let mut value = column.try_into_value()?;
+        if format == PgFormat::Binary {
+            match value.try_get_u8() {
+                Ok(b'\x01') => { },
+                Ok(version) => return Err(DecodeError::Invalid(format!("unsupported jsonpath version {version}").into())),
+                Err(_) => return Err(DecodeError::Invalid("empty jsonpath value".into())),
+            }
+        }
+        Ok(Self(String::from_utf8(value.into())?))
+    }
+}
+
+impl Encode<'static> for JsonPath {
+    fn encode(self) -> Encoded<'static> {
+        let mut buf = Vec::with_capacity(1 + self.0.len());
+        buf.push(b'\x01');
+        buf.extend_from_slice(self.0.as_bytes());
+        Encoded::owned(buf, Self::OID)
+    }
+}
+
+impl From<&str> for JsonPath {
+    fn from(value: &str) -> Self {
+        Self(value.into())
+    }
+}
+
+impl From<String> for JsonPath {
+    fn from(value: String) -> Self {
+        Self(value)
+    }
+}
+
+impl fmt::Display for JsonPath {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(&self.0)
+    }
+}
+
+impl fmt::Debug for JsonPath {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "JsonPath({:?})", self.0)
+    }
+}
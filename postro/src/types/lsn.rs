@@ -0,0 +1,69 @@
+use std::{fmt, str::FromStr};
+
+use crate::{
+    Decode, DecodeError, Encode,
+    encode::Encoded,
+    postgres::{Oid, PgFormat, PgType},
+    row::Column,
+};
+
+/// Postgres write-ahead log location.
+///
+/// Displayed and parsed in the postgres `XXX/XXX` format.
+///
+/// <https://www.postgresql.org/docs/current/datatype-pg-lsn.html>
+#[derive(Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, Default)]
+pub struct PgLsn(pub u64);
+
+impl PgType for PgLsn {
+    /// pg_lsn, PostgreSQL LSN
+    const OID: Oid = 3220;
+}
+
+impl Decode for PgLsn {
+    fn decode(column: Column) -> Result<Self, DecodeError> {
+        if column.oid() != Self::OID {
+            return Err(column.oid_missmatch::<Self>(Some(Self::OID)));
+        }
+        let format = column.format();
+        let value = column.try_into_value()?;
+        match format {
+            PgFormat::Binary => match <[u8; 8]>::try_from(&value[..]) {
+                Ok(bytes) => Ok(Self(u64::from_be_bytes(bytes))),
+                Err(_) => Err(DecodeError::Invalid("invalid pg_lsn length".into())),
+            },
+            PgFormat::Text => str::from_utf8(&value)?
+                .parse()
+                .map_err(|_| DecodeError::Invalid("invalid pg_lsn text".into())),
+        }
+    }
+}
+
+impl Encode<'static> for PgLsn {
+    fn encode(self) -> Encoded<'static> {
+        Encoded::copy_from_slice(&self.0.to_be_bytes(), Self::OID)
+    }
+}
+
+impl fmt::Display for PgLsn {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{:X}/{:X}", self.0 >> 32, self.0 & 0xFFFF_FFFF)
+    }
+}
+
+impl fmt::Debug for PgLsn {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "PgLsn({self})")
+    }
+}
+
+impl FromStr for PgLsn {
+    type Err = std::num::ParseIntError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let (hi, lo) = s.split_once('/').unwrap_or(("", s));
+        let hi = u32::from_str_radix(hi, 16)?;
+        let lo = u32::from_str_radix(lo, 16)?;
+        Ok(Self(((hi as u64) << 32) | lo as u64))
+    }
+}
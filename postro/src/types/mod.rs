@@ -5,7 +5,19 @@
 //! Available for:
 //!
 //! - [`serde`]'s [`Deserialize`][sd] and [`Serialize`][ss] via [`Json`], requires `json` feature
+//! - [`PgLsn`] for postgres `pg_lsn`
+//! - [`JsonPath`] for postgres `jsonpath`
+//! - [`EmptyAsNull`] to treat empty string as `NULL`
+//! - [`Composite`] for postgres composite type and `record`
+//! - [`PgRange`] for postgres range types, e.g: `int4range` or `tstzrange`
+//! - [`std::net::IpAddr`] and [`PgInet`] for postgres `inet` and `cidr`, [`MacAddr`] for `macaddr`
 //! - [`time`][::time]'s [`PrimitiveDateTime`][tp], [`UtcDateTime`][tu], requires `time` feature
+//! - [`bigdecimal`][::bigdecimal]'s [`BigDecimal`][bd] via postgres `NUMERIC`, requires `bigdecimal` feature
+//! - [`rust_decimal`][::rust_decimal]'s [`Decimal`][rd] via postgres `NUMERIC`, requires `rust_decimal` feature
+//! - [`sea_query`][::sea_query]'s built statement as [`Sql`][s], where the values become bound
+//!   parameters, requires `sea-query` feature
+//! - [`postgres_types`][::postgres_types]'s [`FromSql`][fs] via [`Compat`], requires
+//!   `tokio-postgres-compat` feature
 //!
 //! [d]: crate::Decode
 //! [e]: crate::Encode
@@ -14,6 +26,28 @@
 //! [ss]: serde::Serialize
 //! [tp]: ::time::PrimitiveDateTime
 //! [tu]: ::time::UtcDateTime
+//! [bd]: ::bigdecimal::BigDecimal
+//! [rd]: ::rust_decimal::Decimal
+//! [s]: crate::sql::Sql
+//! [fs]: ::postgres_types::FromSql
+
+mod lsn;
+pub use lsn::PgLsn;
+
+mod empty;
+pub use empty::EmptyAsNull;
+
+mod composite;
+pub use composite::Composite;
+
+mod range;
+pub use range::PgRange;
+
+mod inet;
+pub use inet::{MacAddr, PgInet};
+
+mod jsonpath;
+pub use jsonpath::JsonPath;
 
 #[cfg(feature = "json")]
 mod json;
@@ -23,3 +57,22 @@ pub use json::Json;
 #[cfg(feature = "time")]
 mod time;
 
+
+#[cfg(any(feature = "bigdecimal", feature = "rust_decimal"))]
+mod numeric;
+
+#[cfg(feature = "bigdecimal")]
+mod bigdecimal;
+
+#[cfg(feature = "rust_decimal")]
+mod rust_decimal;
+
+#[cfg(feature = "sea-query")]
+mod sea_query;
+#[cfg(feature = "sea-query")]
+pub use sea_query::UnsupportedValue;
+
+#[cfg(feature = "tokio-postgres-compat")]
+mod postgres_types;
+#[cfg(feature = "tokio-postgres-compat")]
+pub use postgres_types::Compat;
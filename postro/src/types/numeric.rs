@@ -0,0 +1,205 @@
+//! Binary `NUMERIC` codec shared by decimal integrations.
+//!
+//! Numeric binary representation:
+//!
+//! - `Int16` number of base 10000 digits
+//! - `Int16` weight of the first digit, in base 10000 exponent
+//! - `Int16` sign, see [`SIGN_POS`], [`SIGN_NEG`], [`SIGN_NAN`]
+//! - `Int16` display scale
+//! - `Int16[]` base 10000 digits
+//!
+//! Integrations convert to and from decimal string representation, which every
+//! decimal crate can parse and produce losslessly.
+use bytes::{Buf, BufMut, BytesMut};
+
+use crate::{DecodeError, encode::Encoded, postgres::Oid};
+
+/// numeric, "numeric(precision, scale) arbitrary precision number"
+pub(crate) const NUMERIC_OID: Oid = 1700;
+
+const SIGN_POS: u16 = 0x0000;
+const SIGN_NEG: u16 = 0x4000;
+const SIGN_NAN: u16 = 0xC000;
+const SIGN_PINF: u16 = 0xD000;
+const SIGN_NINF: u16 = 0xF000;
+
+const NBASE_DIGITS: usize = 4;
+
+/// Maximum display scale.
+const MAX_DSCALE: i64 = 0x3FFF;
+/// Maximum digits before the decimal point.
+const MAX_INT_DIGITS: i64 = 131072;
+
+/// Encode decimal number.
+///
+/// Value is `digits * 10^-scale`, where `digits` is ascii decimal digits without sign.
+pub(crate) fn encode(negative: bool, digits: &str, scale: i64) -> Encoded<'static> {
+    debug_assert!(digits.bytes().all(|e| e.is_ascii_digit()));
+
+    if scale > MAX_DSCALE {
+        return Encoded::invalid(NUMERIC_OID, format!("numeric scale {scale} exceeds {MAX_DSCALE}"));
+    }
+    if digits.trim_start_matches('0').len() as i64 - scale > MAX_INT_DIGITS {
+        return Encoded::invalid(NUMERIC_OID, format!("numeric exceeds {MAX_INT_DIGITS} digits before the decimal point"));
+    }
+
+    // normalize negative scale by appending zeros
+    let mut digits = digits.trim_start_matches('0').to_owned();
+    let mut scale = scale;
+    if scale < 0 {
+        digits.extend(std::iter::repeat_n('0', scale.unsigned_abs() as usize));
+        scale = 0;
+    }
+    let dscale = scale as u16;
+
+    // pad fraction so it align with base 10000 digit
+    let pad = (NBASE_DIGITS - scale as usize % NBASE_DIGITS) % NBASE_DIGITS;
+    digits.extend(std::iter::repeat_n('0', pad));
+    let frac_groups = (scale as usize + pad) / NBASE_DIGITS;
+
+    // pad integer part so it align with base 10000 digit
+    let lead = (NBASE_DIGITS - digits.len() % NBASE_DIGITS) % NBASE_DIGITS;
+    let digits = "0".repeat(lead) + &digits;
+
+    let mut groups = digits
+        .as_bytes()
+        .chunks(NBASE_DIGITS)
+        .map(|chunk| chunk.iter().fold(0i16, |acc, d| acc * 10 + (d - b'0') as i16))
+        .collect::<Vec<_>>();
+
+    let mut weight = groups.len() as i32 - frac_groups as i32 - 1;
+
+    let leading_zeros = groups.iter().take_while(|e| **e == 0).count();
+    groups.drain(..leading_zeros);
+    weight -= leading_zeros as i32;
+
+    while groups.last() == Some(&0) {
+        groups.pop();
+    }
+
+    if groups.len() > i16::MAX as usize {
+        return Encoded::invalid(NUMERIC_OID, "numeric exceeds maximum digits");
+    }
+
+    if groups.is_empty() {
+        weight = 0;
+    }
+
+    let sign = match negative && !groups.is_empty() {
+        true => SIGN_NEG,
+        false => SIGN_POS,
+    };
+
+    let mut buf = BytesMut::with_capacity(8 + groups.len() * 2);
+    buf.put_i16(groups.len() as i16);
+    buf.put_i16(weight as i16);
+    buf.put_u16(sign);
+    buf.put_u16(dscale);
+    for group in groups {
+        buf.put_i16(group);
+    }
+
+    Encoded::owned(buf, NUMERIC_OID)
+}
+
+/// Decode numeric into decimal string, e.g. `-12.3400`.
+pub(crate) fn decode(mut value: &[u8]) -> Result<String, DecodeError> {
+    if value.len() < 8 {
+        return Err(DecodeError::Invalid("numeric header too short".into()));
+    }
+
+    let ndigits = value.get_i16();
+    let weight = value.get_i16();
+    let sign = value.get_u16();
+    let dscale = value.get_u16() as usize;
+
+    match sign {
+        SIGN_POS | SIGN_NEG => {}
+        SIGN_NAN => return Err(DecodeError::Invalid("numeric is NaN".into())),
+        SIGN_PINF | SIGN_NINF => return Err(DecodeError::Invalid("numeric is infinity".into())),
+        _ => return Err(DecodeError::Invalid("invalid numeric sign".into())),
+    }
+
+    if ndigits < 0 || value.len() != ndigits as usize * 2 {
+        return Err(DecodeError::Invalid("invalid numeric digits length".into()));
+    }
+
+    let groups = (0..ndigits).map(|_| value.get_i16()).collect::<Vec<_>>();
+
+    let mut int = String::new();
+    let mut frac = String::new();
+
+    for (i, group) in groups.iter().enumerate() {
+        let exp = weight as isize - i as isize;
+        let group = format!("{group:04}");
+        if exp >= 0 {
+            int.push_str(&group);
+            // fill skipped zero digits between current group and decimal point
+            if i + 1 == groups.len() {
+                int.extend(std::iter::repeat_n('0', exp as usize * NBASE_DIGITS));
+            }
+        } else {
+            if frac.is_empty() {
+                // fill leading zero digits after decimal point
+                frac.extend(std::iter::repeat_n('0', (-exp - 1) as usize * NBASE_DIGITS));
+            }
+            frac.push_str(&group);
+        }
+    }
+
+    let int = int.trim_start_matches('0');
+    frac.truncate(dscale);
+    while frac.len() < dscale {
+        frac.push('0');
+    }
+
+    let mut output = String::with_capacity(int.len() + frac.len() + 2);
+    if sign == SIGN_NEG {
+        output.push('-');
+    }
+    match int.is_empty() {
+        true => output.push('0'),
+        false => output.push_str(int),
+    }
+    if !frac.is_empty() {
+        output.push('.');
+        output.push_str(&frac);
+    }
+
+    Ok(output)
+}
+
+#[cfg(test)]
+mod test {
+    use bytes::Buf;
+
+    fn roundtrip(negative: bool, digits: &str, scale: i64) -> String {
+        let mut encoded = super::encode(negative, digits, scale);
+        let bytes = encoded.copy_to_bytes(encoded.remaining());
+        super::decode(&bytes).unwrap()
+    }
+
+    #[test]
+    fn numeric_roundtrip() {
+        assert_eq!(roundtrip(false, "0", 0), "0");
+        assert_eq!(roundtrip(false, "12345", 2), "123.45");
+        assert_eq!(roundtrip(true, "12345", 2), "-123.45");
+        assert_eq!(roundtrip(false, "1", 6), "0.000001");
+        assert_eq!(roundtrip(false, "100000000", 0), "100000000");
+        assert_eq!(roundtrip(false, "12", -5), "1200000");
+        assert_eq!(roundtrip(false, "1000", 4), "0.1000");
+        assert_eq!(roundtrip(true, "0", 2), "0.00");
+    }
+
+    #[test]
+    fn numeric_out_of_range() {
+        use crate::encode::{Encode, check_params};
+
+        let err = check_params(&[1.encode(), super::encode(false, "1", 0x4000)]).unwrap_err();
+        assert_eq!(err.index(), 2);
+        assert_eq!(err.oid(), super::NUMERIC_OID);
+
+        assert!(check_params(&[super::encode(false, "1", -131071)]).is_ok());
+        assert!(check_params(&[super::encode(false, "1", -131072)]).is_err());
+    }
+}
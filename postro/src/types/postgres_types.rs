@@ -0,0 +1,94 @@
+use postgres_types::{FromSqlOwned, Type};
+
+use crate::{
+    Decode, DecodeError,
+    postgres::PgFormat,
+    row::Column,
+};
+
+/// Decode value using [`postgres-types`][postgres_types]'s [`FromSql`][postgres_types::FromSql]
+/// implementation.
+///
+/// This allows reusing types that only implement `FromSql`, such as types written for
+/// `tokio-postgres`, which ease incremental migration to postro.
+///
+/// Only builtin postgres types are supported, custom types such as enum and domain
+/// returns [`DecodeError::OidMissmatch`].
+///
+/// ```no_run
+/// use postro::types::Compat;
+///
+/// # async fn test(mut conn: postro::Connection) -> postro::Result<()> {
+/// let Compat(tags) = postro::query_scalar::<_, _, Compat<Vec<String>>>("SELECT tags FROM post", &mut conn)
+///     .fetch_one()
+///     .await?;
+/// # Ok(())
+/// # }
+/// ```
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub struct Compat<T>(pub T);
+
+impl<T> Compat<T> {
+    /// Returns the inner value.
+    pub fn into_inner(self) -> T {
+        self.0
+    }
+}
+
+impl<T: FromSqlOwned> Decode for Compat<T> {
+    fn decode(column: Column) -> Result<Self, DecodeError> {
+        let Some(ty) = Type::from_oid(column.oid()) else {
+            return Err(column.oid_missmatch::<T>(None));
+        };
+        if !T::accepts(&ty) {
+            return Err(column.oid_missmatch::<T>(None));
+        }
+        // `FromSql` only decode binary format
+        if column.format() != PgFormat::Binary {
+            return Err(DecodeError::Invalid("text format is not supported by `FromSql`".into()));
+        }
+        let value = match column.as_slice() {
+            Some(raw) => T::from_sql(&ty, raw),
+            None => T::from_sql_null(&ty),
+        };
+        value
+            .map(Self)
+            .map_err(|e| DecodeError::Invalid(e.to_string().into()))
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use bytes::{BufMut, Bytes, BytesMut};
+
+    use super::*;
+    use crate::postgres::Oid;
+
+    fn column(oid: Oid, value: Option<&[u8]>) -> Column {
+        let mut desc = BytesMut::new();
+        desc.put_u32(0);
+        desc.put_u16(0);
+        desc.put_u32(oid);
+        desc.put_i16(-1);
+        desc.put_i32(-1);
+        desc.put_u16(PgFormat::Binary.format_code());
+        Column::new("c".into(), &desc, value.map(Bytes::copy_from_slice), None)
+    }
+
+    #[test]
+    fn decode_from_sql() {
+        let value = column(23, Some(&420i32.to_be_bytes())).decode::<Compat<i32>>().unwrap();
+        assert_eq!(value, Compat(420));
+
+        let value = column(25, Some(b"Deez")).decode::<Compat<String>>().unwrap();
+        assert_eq!(value.into_inner(), "Deez");
+
+        // `FromSql` decide NULL handling
+        let value = column(23, None).decode::<Compat<Option<i32>>>().unwrap();
+        assert_eq!(value, Compat(None));
+        assert!(column(23, None).decode::<Compat<i32>>().is_err());
+
+        assert!(column(25, Some(b"Deez")).decode::<Compat<i32>>().is_err());
+        assert!(column(0x00ff_ffff, Some(b"")).decode::<Compat<i32>>().is_err());
+    }
+}
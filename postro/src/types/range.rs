@@ -0,0 +1,302 @@
+use bytes::{Buf, BufMut, Bytes, BytesMut};
+use std::ops::{Bound, Range, RangeFrom, RangeFull, RangeInclusive, RangeTo, RangeToInclusive};
+
+use crate::{
+    Decode, DecodeError, Encode,
+    common::ByteStr,
+    encode::Encoded,
+    ext::BindParams,
+    postgres::{Oid, PgFormat, PgType},
+    row::Column,
+};
+
+// <https://github.com/postgres/postgres/blob/master/src/include/utils/rangetypes.h>
+const EMPTY: u8 = 0x01;
+const LB_INC: u8 = 0x02;
+const UB_INC: u8 = 0x04;
+const LB_INF: u8 = 0x08;
+const UB_INF: u8 = 0x10;
+
+/// Returns the builtin range type [`Oid`] of the element type.
+const fn range_oid(element: Oid) -> Option<Oid> {
+    match element {
+        23 => Some(3904),   // int4range
+        1700 => Some(3906), // numrange
+        1114 => Some(3908), // tsrange
+        1184 => Some(3910), // tstzrange
+        1082 => Some(3912), // daterange
+        20 => Some(3926),   // int8range
+        _ => None,
+    }
+}
+
+/// Postgres range value, e.g: `int4range`, `int8range`, `numrange` or `tstzrange`.
+///
+/// The range type is determined by the element type `T`. Note that postgres normalize
+/// discrete range, e.g: `int4range` `[1,10]` is returned as `[1,11)`.
+///
+/// ```no_run
+/// # async fn test(mut conn: postro::Connection) -> postro::Result<()> {
+/// use std::ops::Bound;
+/// use postro::types::PgRange;
+///
+/// let range = postro::query_scalar::<_, _, PgRange<i32>>("SELECT int4range($1, $2, '[]')", &mut conn)
+///     .bind(1)
+///     .bind(10)
+///     .fetch_one()
+///     .await?;
+/// assert_eq!(range, PgRange::from(1..11));
+/// assert!(range.contains(&10));
+///
+/// let overlap = postro::query_scalar::<_, _, bool>("SELECT $1 && int8range(5, NULL)", &mut conn)
+///     .bind(PgRange::<i64>::from(..7))
+///     .fetch_one()
+///     .await?;
+/// # Ok(())
+/// # }
+/// ```
+///
+/// <https://www.postgresql.org/docs/current/rangetypes.html>
+#[derive(Clone, Debug, PartialEq, Eq, Hash)]
+pub struct PgRange<T> {
+    /// [`None`] if the range is empty
+    bounds: Option<(Bound<T>, Bound<T>)>,
+}
+
+impl<T> PgRange<T> {
+    /// Create range with given lower and upper bound.
+    pub fn new(lower: Bound<T>, upper: Bound<T>) -> Self {
+        Self { bounds: Some((lower, upper)) }
+    }
+
+    /// Create empty range, which contains no value.
+    pub fn empty() -> Self {
+        Self { bounds: None }
+    }
+
+    /// Returns `true` if the range is empty.
+    ///
+    /// Note that range with bounds that contains no value, e.g: `[1,1)`, is only
+    /// normalized as empty by postgres.
+    pub fn is_empty(&self) -> bool {
+        self.bounds.is_none()
+    }
+
+    /// Returns the lower bound, [`None`] if the range is empty.
+    pub fn lower(&self) -> Option<Bound<&T>> {
+        self.bounds.as_ref().map(|(lower, _)| lower.as_ref())
+    }
+
+    /// Returns the upper bound, [`None`] if the range is empty.
+    pub fn upper(&self) -> Option<Bound<&T>> {
+        self.bounds.as_ref().map(|(_, upper)| upper.as_ref())
+    }
+
+    /// Consume self into the lower and upper bound, [`None`] if the range is empty.
+    pub fn into_bounds(self) -> Option<(Bound<T>, Bound<T>)> {
+        self.bounds
+    }
+
+    /// Returns `true` if `value` is within the range.
+    pub fn contains(&self, value: &T) -> bool
+    where
+        T: PartialOrd,
+    {
+        let Some((lower, upper)) = &self.bounds else {
+            return false;
+        };
+        let above = match lower {
+            Bound::Included(lower) => lower <= value,
+            Bound::Excluded(lower) => lower < value,
+            Bound::Unbounded => true,
+        };
+        let below = match upper {
+            Bound::Included(upper) => value <= upper,
+            Bound::Excluded(upper) => value < upper,
+            Bound::Unbounded => true,
+        };
+        above && below
+    }
+}
+
+impl<T: PgType> PgType for PgRange<T> {
+    /// Range of element type, or `0` if there is no builtin range type of `T`
+    const OID: Oid = match range_oid(T::OID) {
+        Some(oid) => oid,
+        None => 0,
+    };
+}
+
+impl<T: Decode + PgType> Decode for PgRange<T> {
+    fn decode(column: Column) -> Result<Self, DecodeError> {
+        if column.oid() == 0 || column.oid() != Self::OID {
+            return Err(column.oid_missmatch::<Self>(range_oid(T::OID)));
+        }
+        if column.format() != PgFormat::Binary {
+            return Err(DecodeError::Invalid("range text format is not supported".into()));
+        }
+
+        let name = ByteStr::copy_from_str(column.name());
+        let codecs = column.shared_codecs();
+        let mut value = column.try_into_value()?;
+
+        if !value.has_remaining() {
+            return Err(DecodeError::Invalid("invalid range length".into()));
+        }
+        let flags = value.get_u8();
+        if flags & EMPTY != 0 {
+            return Ok(Self::empty());
+        }
+
+        let mut bound = |inf: u8, inc: u8| -> Result<Bound<T>, DecodeError> {
+            if flags & inf != 0 {
+                return Ok(Bound::Unbounded);
+            }
+            if value.remaining() < size_of::<i32>() {
+                return Err(DecodeError::Invalid("invalid range length".into()));
+            }
+            let len = match usize::try_from(value.get_i32()) {
+                Ok(len) if len <= value.remaining() => len,
+                _ => return Err(DecodeError::Invalid("invalid range length".into())),
+            };
+            let column = Column::from_parts(
+                name.clone(),
+                T::OID,
+                PgFormat::Binary,
+                Some(value.split_to(len)),
+                codecs.clone(),
+            );
+            let element = T::decode(column)?;
+            Ok(match flags & inc != 0 {
+                true => Bound::Included(element),
+                false => Bound::Excluded(element),
+            })
+        };
+
+        let lower = bound(LB_INF, LB_INC)?;
+        let upper = bound(UB_INF, UB_INC)?;
+        Ok(Self::new(lower, upper))
+    }
+}
+
+impl<'q, T: Encode<'q> + PgType> Encode<'static> for PgRange<T> {
+    fn encode(self) -> Encoded<'static> {
+        let Some(oid) = range_oid(T::OID) else {
+            return Encoded::invalid(0, format!("no range type of oid {}", T::OID));
+        };
+        let Some((lower, upper)) = self.bounds else {
+            return Encoded::owned(Bytes::from_static(&[EMPTY]), oid);
+        };
+
+        let mut buf = BytesMut::new();
+        let mut flags = 0;
+        buf.put_u8(flags);
+
+        for (bound, inf, inc) in [(lower, LB_INF, LB_INC), (upper, UB_INF, UB_INC)] {
+            let element = match bound {
+                Bound::Included(element) => {
+                    flags |= inc;
+                    element
+                },
+                Bound::Excluded(element) => element,
+                Bound::Unbounded => {
+                    flags |= inf;
+                    continue;
+                },
+            };
+            let mut element = element.encode();
+            if let Some(reason) = element.error() {
+                return Encoded::invalid(oid, reason.to_owned());
+            }
+            if element.size() == -1 {
+                return Encoded::invalid(oid, "range bound cannot be NULL, use `Bound::Unbounded`");
+            }
+            buf.put_i32(element.size());
+            buf.put(&mut element);
+        }
+
+        buf[0] = flags;
+        Encoded::owned(buf.freeze(), oid)
+    }
+}
+
+impl<T> From<Range<T>> for PgRange<T> {
+    fn from(value: Range<T>) -> Self {
+        Self::new(Bound::Included(value.start), Bound::Excluded(value.end))
+    }
+}
+
+impl<T> From<RangeInclusive<T>> for PgRange<T> {
+    fn from(value: RangeInclusive<T>) -> Self {
+        let (start, end) = value.into_inner();
+        Self::new(Bound::Included(start), Bound::Included(end))
+    }
+}
+
+impl<T> From<RangeFrom<T>> for PgRange<T> {
+    fn from(value: RangeFrom<T>) -> Self {
+        Self::new(Bound::Included(value.start), Bound::Unbounded)
+    }
+}
+
+impl<T> From<RangeTo<T>> for PgRange<T> {
+    fn from(value: RangeTo<T>) -> Self {
+        Self::new(Bound::Unbounded, Bound::Excluded(value.end))
+    }
+}
+
+impl<T> From<RangeToInclusive<T>> for PgRange<T> {
+    fn from(value: RangeToInclusive<T>) -> Self {
+        Self::new(Bound::Unbounded, Bound::Included(value.end))
+    }
+}
+
+impl<T> From<RangeFull> for PgRange<T> {
+    fn from(_: RangeFull) -> Self {
+        Self::new(Bound::Unbounded, Bound::Unbounded)
+    }
+}
+
+impl<T> From<(Bound<T>, Bound<T>)> for PgRange<T> {
+    fn from((lower, upper): (Bound<T>, Bound<T>)) -> Self {
+        Self::new(lower, upper)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use bytes::Buf;
+    use std::ops::Bound;
+
+    use super::PgRange;
+    use crate::{Encode, postgres::{PgFormat, PgType}, row::Column};
+
+    fn roundtrip(range: PgRange<i64>) -> PgRange<i64> {
+        let mut encoded = range.encode();
+        assert_eq!(encoded.oid(), 3926);
+        let value = encoded.copy_to_bytes(encoded.remaining());
+        Column::from_parts("r".into(), PgRange::<i64>::OID, PgFormat::Binary, Some(value), None)
+            .decode()
+            .unwrap()
+    }
+
+    #[test]
+    fn range_roundtrip() {
+        for range in [
+            PgRange::from(1..10),
+            PgRange::from(-5..=5),
+            PgRange::from(7..),
+            PgRange::from(..=7),
+            PgRange::from(..),
+            PgRange::new(Bound::Excluded(1), Bound::Included(2)),
+            PgRange::empty(),
+        ] {
+            assert_eq!(roundtrip(range.clone()), range);
+        }
+
+        let range = PgRange::from(1..10);
+        assert!(range.contains(&1) && range.contains(&9) && !range.contains(&10));
+        assert!(!PgRange::<i32>::empty().contains(&0));
+        assert_eq!(PgRange::<i32>::OID, 3904);
+    }
+}
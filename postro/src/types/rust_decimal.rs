@@ -0,0 +1,61 @@
+use rust_decimal::Decimal;
+
+use crate::{
+    Decode, DecodeError, Encode,
+    encode::Encoded,
+    postgres::{Oid, PgType},
+    row::Column,
+};
+
+use super::numeric;
+
+impl PgType for Decimal {
+    /// numeric, arbitrary precision number
+    const OID: Oid = numeric::NUMERIC_OID;
+}
+
+impl Decode for Decimal {
+    fn decode(column: Column) -> Result<Self, DecodeError> {
+        if column.oid() != Self::OID {
+            return Err(column.oid_missmatch::<Self>(Some(Self::OID)));
+        }
+        let value = numeric::decode(&column.try_into_value()?)?;
+        // exact, instead of silently rounding value that exceed `Decimal` precision
+        Decimal::from_str_exact(&value).map_err(|e| DecodeError::Invalid(e.to_string().into()))
+    }
+}
+
+impl<'q> Encode<'q> for &Decimal {
+    fn encode(self) -> Encoded<'q> {
+        numeric::encode(
+            self.is_sign_negative(),
+            &self.mantissa().unsigned_abs().to_string(),
+            self.scale().into(),
+        )
+    }
+}
+
+impl<'q> Encode<'q> for Decimal {
+    fn encode(self) -> Encoded<'q> {
+        (&self).encode()
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use bytes::Buf;
+    use rust_decimal::Decimal;
+    use std::str::FromStr;
+
+    use crate::Encode;
+
+    #[test]
+    fn decimal_roundtrip() {
+        for value in ["0", "123.45", "-123.45", "0.000001", "1200000", "-0.10", "79228162514264337593543950335"] {
+            let mut encoded = Decimal::from_str(value).unwrap().encode();
+            let bytes = encoded.copy_to_bytes(encoded.remaining());
+            let decoded = super::numeric::decode(&bytes).unwrap();
+            assert_eq!(Decimal::from_str_exact(&decoded).unwrap().to_string(), value);
+        }
+    }
+}
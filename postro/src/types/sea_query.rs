@@ -0,0 +1,94 @@
+use sea_query::{Value, Values};
+use std::mem;
+
+use crate::{
+    Encode,
+    common::unit_error,
+    encode::Encoded,
+    postgres::PgType,
+    sql::Sql,
+};
+
+/// Statement built by [`sea_query`] [`PostgresQueryBuilder`][1], the values become bound parameters.
+///
+/// [1]: sea_query::PostgresQueryBuilder
+impl Sql for (String, Values) {
+    fn sql(&self) -> &str {
+        &self.0
+    }
+
+    fn persistent(&self) -> bool {
+        true
+    }
+
+    fn params(&mut self) -> crate::Result<Vec<Encoded<'static>>> {
+        mem::take(&mut self.1.0)
+            .into_iter()
+            .map(|value| encode(value).map_err(Into::into))
+            .collect()
+    }
+}
+
+macro_rules! nullable {
+    ($value:expr, |$v:ident| $encode:expr) => {
+        match $value {
+            Some($v) => $encode,
+            None => Encoded::null(),
+        }
+    };
+}
+
+fn encode(value: Value) -> Result<Encoded<'static>, UnsupportedValue> {
+    let encoded = match value {
+        Value::Bool(v) => nullable!(v, |v| v.encode()),
+        Value::TinyInt(v) => nullable!(v, |v| i16::from(v).encode()),
+        Value::SmallInt(v) => nullable!(v, |v| v.encode()),
+        Value::Int(v) => nullable!(v, |v| v.encode()),
+        Value::BigInt(v) => nullable!(v, |v| v.encode()),
+        Value::TinyUnsigned(v) => nullable!(v, |v| i16::from(v).encode()),
+        Value::SmallUnsigned(v) => nullable!(v, |v| i32::from(v).encode()),
+        Value::Unsigned(v) => nullable!(v, |v| i64::from(v).encode()),
+        Value::BigUnsigned(v) => nullable!(v, |v| i64::try_from(v).map_err(|_| UnsupportedValue)?.encode()),
+        Value::Float(v) => nullable!(v, |v| v.encode()),
+        Value::Double(v) => nullable!(v, |v| v.encode()),
+        Value::String(v) => nullable!(v, |v| Encoded::owned(v.into_bytes(), String::OID)),
+        Value::Char(v) => nullable!(v, |v| Encoded::owned(v.to_string().into_bytes(), String::OID)),
+        Value::Bytes(v) => nullable!(v, |v| Encoded::owned(*v, Vec::<u8>::OID)),
+        // variants enabled by other `sea-query` features
+        #[allow(unreachable_patterns)]
+        _ => return Err(UnsupportedValue),
+    };
+    Ok(encoded)
+}
+
+unit_error! {
+    /// An error when [`sea_query`] value type is not supported as parameter.
+    ///
+    /// This also returned for `u64` value that does not fit in `int8`.
+    pub struct UnsupportedValue("unsupported sea-query value type");
+}
+
+#[cfg(test)]
+mod test {
+    use sea_query::{Alias, Expr, PostgresQueryBuilder, Query};
+
+    use crate::{postgres::PgType, sql::Sql};
+
+    #[test]
+    fn built_statement() {
+        let mut stmt = Query::select()
+            .column(Alias::new("id"))
+            .from(Alias::new("post"))
+            .and_where(Expr::col(Alias::new("name")).eq("Deez"))
+            .and_where(Expr::col(Alias::new("id")).gt(4u64))
+            .and_where(Expr::col(Alias::new("parent")).eq(Option::<i32>::None))
+            .build(PostgresQueryBuilder);
+
+        assert_eq!(stmt.sql(), r#"SELECT "id" FROM "post" WHERE "name" = $1 AND "id" > $2 AND "parent" = $3"#);
+
+        let params = stmt.params().unwrap();
+        let oids = params.iter().map(|e| e.oid()).collect::<Vec<_>>();
+        assert_eq!(oids, [String::OID, i64::OID, 0]);
+        assert!(stmt.params().unwrap().is_empty());
+    }
+}
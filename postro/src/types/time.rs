@@ -41,7 +41,7 @@ const UTC_PG_EPOCH: UtcDateTime = {
 impl Decode for PrimitiveDateTime {
     fn decode(column: Column) -> Result<Self, DecodeError> {
         if column.oid() != Self::OID {
-            return Err(DecodeError::OidMissmatch);
+            return Err(column.oid_missmatch::<Self>(Some(Self::OID)));
         }
         let value = column.try_into_value()?;
         assert_eq!(
@@ -60,7 +60,7 @@ impl Decode for PrimitiveDateTime {
 impl Decode for UtcDateTime {
     fn decode(column: Column) -> Result<Self, DecodeError> {
         if column.oid() != Self::OID {
-            return Err(DecodeError::OidMissmatch);
+            return Err(column.oid_missmatch::<Self>(Some(Self::OID)));
         }
         let value = column.try_into_value()?;
         assert_eq!(
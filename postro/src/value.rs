@@ -2,6 +2,7 @@ use bytes::{Buf, Bytes};
 
 const INLINE_LEN: usize = 15;
 
+#[derive(Clone)]
 pub(crate) enum ValueRef<'a> {
     Slice(&'a [u8]),
     Inline {
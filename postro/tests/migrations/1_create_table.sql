@@ -0,0 +1 @@
+CREATE TABLE conformance_migrate(id int);
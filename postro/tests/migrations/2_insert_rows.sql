@@ -0,0 +1 @@
+INSERT INTO conformance_migrate VALUES (1), (2);
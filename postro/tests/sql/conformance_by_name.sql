@@ -0,0 +1,5 @@
+-- rows matching the name, `$1`
+SELECT id, name
+FROM conformance
+WHERE name = $1
+ORDER BY id